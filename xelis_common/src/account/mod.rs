@@ -166,4 +166,35 @@ impl PartialEq for CiphertextCache {
     }
 }
 
-impl Eq for CiphertextCache {}
\ No newline at end of file
+impl Eq for CiphertextCache {}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::Scalar;
+    use crate::crypto::{proofs::G, KeyPair};
+    use super::*;
+
+    #[test]
+    fn serde_ciphertext_cache_roundtrip() {
+        let keypair = KeyPair::new();
+        let ciphertext = keypair.get_public_key().encrypt(42u64);
+
+        let variants = [
+            CiphertextCache::Compressed(ciphertext.compress()),
+            CiphertextCache::Decompressed(None, ciphertext.clone()),
+        ];
+
+        for cache in variants {
+            let bytes = cache.to_bytes();
+            assert_eq!(bytes.len(), cache.size());
+
+            // Always restored in its compact form
+            let restored = CiphertextCache::from_bytes(&bytes).unwrap();
+            assert!(matches!(restored, CiphertextCache::Compressed(_)));
+            assert_eq!(restored, cache);
+
+            let decrypted = keypair.decrypt_to_point(&restored.take_ciphertext().unwrap());
+            assert_eq!(decrypted, Scalar::from(42u64) * (*G));
+        }
+    }
+}
@@ -561,6 +561,11 @@ pub enum AccountHistoryType {
         participants: Vec<Address>,
         threshold: u8,
     },
+    MultiSigUpdate {
+        add: Vec<Address>,
+        remove: Vec<Address>,
+        new_threshold: Option<u8>,
+    },
     InvokeContract {
         contract: Hash,
         entry_id: u16,
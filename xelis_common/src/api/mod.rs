@@ -20,12 +20,13 @@ use crate::{
         Signature
     },
     transaction::{
-        extra_data::UnknownExtraDataFormat,
+        extra_data::{SelfMemo, UnknownExtraDataFormat},
         multisig::MultiSig,
         BurnPayload,
         DeployContractPayload,
         InvokeContractPayload,
         MultiSigPayload,
+        MultiSigUpdatePayload,
         Reference,
         SourceCommitment,
         Transaction,
@@ -87,7 +88,8 @@ pub enum RPCTransactionType<'a> {
     Burn(Cow<'a, BurnPayload>),
     MultiSig(Cow<'a, MultiSigPayload>),
     InvokeContract(Cow<'a, InvokeContractPayload>),
-    DeployContract(Cow<'a, DeployContractPayload>)
+    DeployContract(Cow<'a, DeployContractPayload>),
+    MultiSigUpdate(Cow<'a, MultiSigUpdatePayload>)
 }
 
 impl<'a> RPCTransactionType<'a> {
@@ -111,7 +113,8 @@ impl<'a> RPCTransactionType<'a> {
             TransactionType::Burn(burn) => Self::Burn(Cow::Borrowed(burn)),
             TransactionType::MultiSig(payload) => Self::MultiSig(Cow::Borrowed(payload)),
             TransactionType::InvokeContract(payload) => Self::InvokeContract(Cow::Borrowed(payload)),
-            TransactionType::DeployContract(payload) => Self::DeployContract(Cow::Borrowed(payload))
+            TransactionType::DeployContract(payload) => Self::DeployContract(Cow::Borrowed(payload)),
+            TransactionType::MultiSigUpdate(payload) => Self::MultiSigUpdate(Cow::Borrowed(payload))
         }
     }
 }
@@ -125,7 +128,8 @@ impl From<RPCTransactionType<'_>> for TransactionType {
             RPCTransactionType::Burn(burn) => TransactionType::Burn(burn.into_owned()),
             RPCTransactionType::MultiSig(payload) => TransactionType::MultiSig(payload.into_owned()),
             RPCTransactionType::InvokeContract(payload) => TransactionType::InvokeContract(payload.into_owned()),
-            RPCTransactionType::DeployContract(payload) => TransactionType::DeployContract(payload.into_owned())
+            RPCTransactionType::DeployContract(payload) => TransactionType::DeployContract(payload.into_owned()),
+            RPCTransactionType::MultiSigUpdate(payload) => TransactionType::MultiSigUpdate(payload.into_owned())
         }
     }
 }
@@ -169,6 +173,9 @@ pub struct RPCTransaction<'a> {
     pub range_proof: Cow<'a, RangeProof>,
     /// Reference at which block the transaction was built
     pub reference: Cow<'a, Reference>,
+    /// Memo encrypted for the source key only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub self_memo: Option<Cow<'a, SelfMemo>>,
     /// Multisig data if the transaction is a multisig transaction
     pub multisig: Cow<'a, Option<MultiSig>>,
     /// Signature of the transaction
@@ -194,6 +201,7 @@ impl<'a> RPCTransaction<'a> {
             source_commitments: Cow::Borrowed(tx.get_source_commitments()),
             range_proof: Cow::Borrowed(tx.get_range_proof()),
             reference: Cow::Borrowed(tx.get_reference()),
+            self_memo: tx.get_self_memo().map(Cow::Borrowed),
             multisig: Cow::Borrowed(tx.get_multisig()),
             signature: Cow::Borrowed(tx.get_signature()),
             size
@@ -213,6 +221,7 @@ impl<'a> From<RPCTransaction<'a>> for Transaction {
             tx.source_commitments.into_owned(),
             tx.range_proof.into_owned(),
             tx.reference.into_owned(),
+            tx.self_memo.map(Cow::into_owned),
             tx.multisig.into_owned(),
             tx.signature.into_owned()
         )
@@ -46,6 +46,28 @@ pub struct XSWDPrefetchPermissions {
     pub permissions: IndexSet<String>,
 }
 
+// Update the metadata of an already registered application
+// Only the provided fields are updated
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+pub struct XSWDUpdateMetadata {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+// Outcome of a prefetch permissions request
+// for each method requested by the application
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct PrefetchResult {
+    // Methods set to always allow
+    pub granted: Vec<String>,
+    // Methods not granted by the user
+    pub denied: Vec<String>,
+}
+
 // Signer ID to use for signing the transaction
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SignerId {
@@ -14,6 +14,18 @@ use crate::{
 };
 use super::BlockHeader;
 
+// Weight of the transactions included in a block
+// It only covers the transactions, the header isn't counted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockWeight {
+    // Sum of the transactions sizes in bytes
+    pub size: usize,
+    // Count of transactions
+    pub tx_count: usize,
+    // Sum of the transactions gas budgets
+    pub total_gas: u64,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct Block {
     #[serde(flatten)]
@@ -50,6 +62,16 @@ impl Block {
         &self.transactions
     }
 
+    // Compute the weight of the block from its transactions
+    pub fn weight(&self) -> BlockWeight {
+        self.transactions.iter()
+            .fold(BlockWeight::default(), |weight, tx| BlockWeight {
+                size: weight.size + tx.size(),
+                tx_count: weight.tx_count + 1,
+                total_gas: weight.total_gas.saturating_add(tx.get_max_gas()),
+            })
+    }
+
     #[inline]
     pub fn split(self) -> (Arc<BlockHeader>, Vec<Arc<Transaction>>) {
         (self.header, self.transactions)
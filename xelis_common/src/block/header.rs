@@ -1,7 +1,9 @@
 use std::{fmt::{Display, Formatter}, fmt::Error};
+use curve25519_dalek::traits::IsIdentity;
 use indexmap::IndexSet;
 use serde::Deserialize;
 use log::debug;
+use thiserror::Error as ThisError;
 use crate::{
     block::{BLOCK_WORK_SIZE, HEADER_WORK_SIZE, BlockVersion},
     config::TIPS_LIMIT,
@@ -12,10 +14,12 @@ use crate::{
         },
         hash,
         pow_hash,
+        Address,
         Hash,
         Hashable,
         HASH_SIZE
     },
+    network::Network,
     serializer::{Reader, ReaderError, Serializer, Writer},
     time::TimestampMillis,
     immutable::Immutable
@@ -23,6 +27,14 @@ use crate::{
 use xelis_hash::Error as XelisHashError;
 use super::{Algorithm, MinerWork, EXTRA_NONCE_SIZE};
 
+#[derive(Debug, ThisError)]
+pub enum MinerKeyError {
+    #[error("miner {0} is the identity key")]
+    Identity(Address),
+    #[error("miner {0} is not a valid point")]
+    InvalidPoint(Address),
+}
+
 // Serialize the extra nonce in a hexadecimal string
 pub fn serialize_extra_nonce<S: serde::Serializer>(extra_nonce: &[u8; EXTRA_NONCE_SIZE], s: S) -> Result<S::Ok, S::Error> {
     s.serialize_str(&hex::encode(extra_nonce))
@@ -153,6 +165,31 @@ impl BlockHeader {
         &self.miner
     }
 
+    // Key receiving the block rewards
+    #[inline]
+    pub fn miner(&self) -> &CompressedPublicKey {
+        &self.miner
+    }
+
+    // Check that the miner key is a valid point and not the identity
+    // Rewards sent to the identity key would be unspendable
+    // Only enforced starting V6 to not invalidate older blocks
+    pub fn validate_miner(&self, network: &Network) -> Result<(), MinerKeyError> {
+        if self.version < BlockVersion::V6 {
+            return Ok(())
+        }
+
+        if self.miner.as_point().is_identity() {
+            return Err(MinerKeyError::Identity(self.miner.as_address(network.is_mainnet())))
+        }
+
+        if self.miner.decompress().is_err() {
+            return Err(MinerKeyError::InvalidPoint(self.miner.as_address(network.is_mainnet())))
+        }
+
+        Ok(())
+    }
+
     #[inline]
     pub fn get_extra_nonce(&self) -> &[u8; EXTRA_NONCE_SIZE] {
         &self.extra_nonce
@@ -350,9 +387,10 @@ impl Display for BlockHeader {
 
 #[cfg(test)]
 mod tests {
+    use curve25519_dalek::{ristretto::CompressedRistretto, traits::Identity};
     use indexmap::IndexSet;
-    use crate::{block::BlockVersion, crypto::{Hash, Hashable, KeyPair}, serializer::Serializer};
-    use super::BlockHeader;
+    use crate::{block::BlockVersion, crypto::{elgamal::CompressedPublicKey, Hash, Hashable, KeyPair}, network::Network, serializer::Serializer};
+    use super::{BlockHeader, MinerKeyError};
 
     #[test]
     fn test_block_template() {
@@ -375,4 +413,28 @@ mod tests {
         let header = BlockHeader::from_hex(serialized).unwrap();
         assert!(header.to_hex() == serialized);
     }
+
+    #[test]
+    fn test_valid_miner() {
+        let miner = KeyPair::new().get_public_key().compress();
+        let header = BlockHeader::new(BlockVersion::V6, 0, 0, IndexSet::new(), [0u8; 32], miner.clone(), IndexSet::new());
+        assert_eq!(header.miner(), &miner);
+        assert!(header.validate_miner(&Network::Mainnet).is_ok());
+    }
+
+    #[test]
+    fn test_identity_miner_rejected() {
+        let miner = CompressedPublicKey::new(CompressedRistretto::identity());
+        let header = BlockHeader::new(BlockVersion::V6, 0, 0, IndexSet::new(), [0u8; 32], miner.clone(), IndexSet::new());
+        assert!(matches!(header.validate_miner(&Network::Mainnet), Err(MinerKeyError::Identity(_))));
+
+        // Invalid points are rejected too
+        let miner = CompressedPublicKey::new(CompressedRistretto([0xFF; 32]));
+        let header = BlockHeader::new(BlockVersion::V6, 0, 0, IndexSet::new(), [0u8; 32], miner, IndexSet::new());
+        assert!(matches!(header.validate_miner(&Network::Testnet), Err(MinerKeyError::InvalidPoint(_))));
+
+        // Older blocks are still accepted
+        let header = BlockHeader::new(BlockVersion::V5, 0, 0, IndexSet::new(), [0u8; 32], CompressedPublicKey::new(CompressedRistretto::identity()), IndexSet::new());
+        assert!(header.validate_miner(&Network::Mainnet).is_ok());
+    }
 }
\ No newline at end of file
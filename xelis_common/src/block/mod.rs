@@ -3,8 +3,8 @@ mod block;
 mod miner;
 mod version;
 
-pub use header::BlockHeader;
-pub use block::Block;
+pub use header::{BlockHeader, MinerKeyError};
+pub use block::{Block, BlockWeight};
 pub use miner::{MinerWork, Worker, Algorithm};
 pub use version::BlockVersion;
 
@@ -67,6 +67,9 @@ pub const FEE_PER_STORE_CONTRACT: u64 = FEE_PER_BYTE_STORED_CONTRACT * 256;
 // Fee per read operation in a contract
 // Each read operation has a fixed cost of 0.00000200 XEL
 pub const FEE_PER_READ_CONTRACT: u64 = 200;
+// Fee per byte of data read from another contract storage
+// 0.00000001 XEL per byte
+pub const FEE_PER_BYTE_READ_CONTRACT: u64 = 1;
 // Max payload size for transfer calls from contract and entry calls from user
 // This allows to limit the data that can be sent from a contract
 // This is to prevent bloating the chain with large data
@@ -87,6 +90,9 @@ pub const FEE_PER_BYTE_OF_EVENT_DATA: u64 = 2;
 // Max gas usage available per transaction
 // Currently, set to 5 XEL
 pub const MAX_GAS_USAGE_PER_TX: u64 = COIN_VALUE * 5;
+// Max dependencies a contract module can declare
+// Each dependency must be deployed before the module
+pub const MAX_CONTRACT_DEPENDENCIES: usize = 16;
 
 // 8 decimals numbers
 pub const COIN_DECIMALS: u8 = 8;
@@ -5,6 +5,7 @@ use crate::{
     context::NoOpBuildHasher,
     contract::DeterministicRandom,
     crypto::Hash,
+    serializer::Serializer,
     versioned_type::VersionedState
 };
 
@@ -68,4 +69,118 @@ impl ContractCache {
         // We clean the temporary memory from it
         self.memory.clear();
     }
+
+    // Number of keys currently holding a value in the storage cache
+    // Deleted and unknown keys are not counted
+    pub fn storage_len(&self) -> usize {
+        self.storage.values()
+            .filter(|entry| matches!(entry, Some((_, Some(_)))))
+            .count()
+    }
+
+    // Check if the key is currently holding a value in the storage cache
+    pub fn storage_contains_key(&self, key: &ValueCell) -> bool {
+        self.storage_get(key).is_some()
+    }
+
+    // Get the current value for the key in the storage cache
+    // Returns None if the key was deleted or isn't cached
+    pub fn storage_get(&self, key: &ValueCell) -> Option<&ValueCell> {
+        self.storage.get(key)
+            .and_then(|entry| entry.as_ref())
+            .and_then(|(_, value)| value.as_ref())
+    }
+
+    // Approximate memory used by the cached entries
+    // Based on the serialized sizes of the keys and values
+    pub fn approx_memory_bytes(&self) -> usize {
+        let storage = self.storage.iter()
+            .map(|(key, entry)| key.size() + entry.as_ref()
+                .and_then(|(_, value)| value.as_ref())
+                .map_or(0, |value| value.size())
+            )
+            .sum::<usize>();
+
+        let balances = self.balances.iter()
+            .map(|(asset, entry)| asset.size() + entry.as_ref().map_or(0, |(_, balance)| balance.size()))
+            .sum::<usize>();
+
+        let memory = self.memory_shared.iter()
+            .chain(self.memory.iter())
+            .map(|(key, value)| key.size() + value.size())
+            .sum::<usize>();
+
+        storage + balances + memory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_vm::Primitive;
+    use super::*;
+
+    fn populated_cache() -> ContractCache {
+        let mut cache = ContractCache::new();
+        // Written value
+        cache.storage.insert(Primitive::U64(1).into(), Some((VersionedState::New, Some(Primitive::U64(10).into()))));
+        // Fetched value
+        cache.storage.insert(Primitive::U64(2).into(), Some((VersionedState::FetchedAt(5), Some(Primitive::U64(20).into()))));
+        // Deleted value
+        cache.storage.insert(Primitive::U64(3).into(), Some((VersionedState::Updated(5), None)));
+        // Not found in storage
+        cache.storage.insert(Primitive::U64(4).into(), None);
+
+        cache
+    }
+
+    #[test]
+    fn test_storage_len() {
+        assert_eq!(ContractCache::new().storage_len(), 0);
+        assert_eq!(populated_cache().storage_len(), 2);
+    }
+
+    #[test]
+    fn test_storage_contains_key() {
+        let cache = populated_cache();
+        assert!(cache.storage_contains_key(&Primitive::U64(1).into()));
+        assert!(cache.storage_contains_key(&Primitive::U64(2).into()));
+        assert!(!cache.storage_contains_key(&Primitive::U64(3).into()));
+        assert!(!cache.storage_contains_key(&Primitive::U64(4).into()));
+        assert!(!cache.storage_contains_key(&Primitive::U64(5).into()));
+    }
+
+    #[test]
+    fn test_approx_memory_bytes() {
+        let mut cache = ContractCache::new();
+        assert_eq!(cache.approx_memory_bytes(), 0);
+
+        cache.storage.insert(Primitive::U64(1).into(), Some((VersionedState::New, Some(Primitive::U64(10).into()))));
+        let storage = cache.approx_memory_bytes();
+        assert!(storage > 0);
+
+        cache.balances.insert(Hash::zero(), Some((VersionedState::New, 100)));
+        let balances = cache.approx_memory_bytes();
+        assert_eq!(balances, storage + Hash::zero().size() + 8);
+
+        cache.memory_shared.insert(Primitive::U64(2).into(), Primitive::U64(20).into());
+        let shared = cache.approx_memory_bytes();
+        assert!(shared > balances);
+
+        cache.memory.insert(Primitive::U64(3).into(), Primitive::U64(30).into());
+        assert!(cache.approx_memory_bytes() > shared);
+
+        // Temporary memory is dropped
+        cache.clean_up();
+        assert_eq!(cache.approx_memory_bytes(), shared);
+    }
+
+    #[test]
+    fn test_storage_get() {
+        let cache = populated_cache();
+        assert_eq!(cache.storage_get(&Primitive::U64(1).into()), Some(&Primitive::U64(10).into()));
+        assert_eq!(cache.storage_get(&Primitive::U64(2).into()), Some(&Primitive::U64(20).into()));
+        assert_eq!(cache.storage_get(&Primitive::U64(3).into()), None);
+        assert_eq!(cache.storage_get(&Primitive::U64(4).into()), None);
+        assert_eq!(cache.storage_get(&Primitive::U64(5).into()), None);
+    }
 }
\ No newline at end of file
@@ -223,6 +223,9 @@ pub struct ChainState<'a> {
     pub injected_gas: IndexMap<Source, u64>,
     // The contract logs
     // This is similar to an event log
+    // Logs are kept in strict emission order:
+    // nested inter-contract calls share this list,
+    // so the callee logs are interleaved at the point of the call
     pub logs: Vec<ContractLog>,
     // executions manager
     pub executions: ExecutionsManager<'a>,
@@ -2215,6 +2218,19 @@ pub fn build_environment<P: ContractProvider>(version: ContractVersion) -> Envir
             1,
             Some(hash_type.clone())
         );
+
+        // Read a value from another contract storage
+        env.register_native_function(
+            "read_contract_data",
+            None,
+            vec![
+                ("contract", hash_type.clone()),
+                ("key", Type::Bytes),
+            ],
+            FunctionHandler::Async(async_handler!(read_contract_data::<P>)),
+            FEE_PER_READ_CONTRACT,
+            Some(Type::Optional(Box::new(Type::Bytes)))
+        );
     }
 
     env
@@ -1,9 +1,13 @@
-use std::sync::Arc;
+use std::{collections::HashSet, fmt, sync::Arc};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use xelis_vm::{NumberType, TypePacked};
-use crate::serializer::*;
+use xelis_vm::{Access, NumberType, TypePacked};
+use crate::{
+    config::MAX_CONTRACT_DEPENDENCIES,
+    crypto::{elgamal::CompressedPublicKey, hash, Hash, HASH_SIZE},
+    serializer::*
+};
 use super::ContractVersion;
 
 pub use xelis_vm::Module;
@@ -13,32 +17,285 @@ pub struct ContractModule {
     pub version: ContractVersion,
     // keep it behind Arc to reduce cloning overhead
     pub module: Arc<Module>,
+    // Contracts this module expects to call
+    // Only serialized starting from V1
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Hash>,
+    // Account allowed to upgrade the module in place
+    // None means the contract is immutable
+    // Only serialized starting from V1, always immutable before
+    #[serde(default)]
+    pub upgrade_authority: Option<CompressedPublicKey>,
+    // Hash of the source code the module was built from
+    // Only serialized starting from V3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_hash: Option<Hash>,
+}
+
+impl ContractModule {
+    // Create a new contract module without any declared dependency
+    pub fn new(version: ContractVersion, module: Arc<Module>) -> Self {
+        Self {
+            version,
+            module,
+            dependencies: Vec::new(),
+            upgrade_authority: None,
+            source_hash: None,
+        }
+    }
+
+    // Can the module be replaced by an upgrade
+    #[inline(always)]
+    pub fn is_upgradeable(&self) -> bool {
+        self.upgrade_authority.is_some()
+    }
+
+    // Does this version support the dependencies declaration
+    #[inline(always)]
+    pub fn has_dependencies_support(version: ContractVersion) -> bool {
+        version >= ContractVersion::V1
+    }
+
+    // Does this version support an upgrade authority
+    #[inline(always)]
+    pub fn has_upgrade_support(version: ContractVersion) -> bool {
+        version >= ContractVersion::V1
+    }
+
+    // Does this version support the source hash attestation
+    #[inline(always)]
+    pub fn has_source_hash_support(version: ContractVersion) -> bool {
+        version >= ContractVersion::V3
+    }
+
+    // Check that the provided source matches the attested source hash
+    // Always false if the module has no source hash
+    pub fn verify_reproducible(&self, source: &[u8]) -> bool {
+        self.source_hash.as_ref()
+            .is_some_and(|source_hash| *source_hash == hash(source))
+    }
+}
+
+// Chunks access introspection on the VM module
+pub trait ModuleAccess {
+    // Get the access of the chunk
+    fn chunk_access(&self, chunk_id: u16) -> Option<&Access>;
+
+    // Can the chunk be invoked from outside of the module
+    fn is_externally_callable(&self, chunk_id: u16) -> bool;
+
+    // Human readable parameters of an entry, joined by a comma
+    // None if the chunk is not callable or has no declared parameters
+    fn entry_signature(&self, entry_id: u16) -> Option<String>;
+}
+
+impl ModuleAccess for Module {
+    fn chunk_access(&self, chunk_id: u16) -> Option<&Access> {
+        self.chunks()
+            .get(chunk_id as usize)
+            .map(|chunk| &chunk.access)
+    }
+
+    fn is_externally_callable(&self, chunk_id: u16) -> bool {
+        matches!(self.chunk_access(chunk_id), Some(Access::All { .. } | Access::Entry { .. }))
+    }
+
+    fn entry_signature(&self, entry_id: u16) -> Option<String> {
+        match self.chunk_access(entry_id)? {
+            Access::All { parameters: Some(parameters) } | Access::Entry { parameters: Some(parameters) } => Some(join_types(parameters)),
+            _ => None
+        }
+    }
+}
+
+// Readable rendering of a TypePacked for tooling
+// TypePacked is defined in the VM, so it is wrapped to implement Display
+pub struct TypePackedDisplay<'a>(pub &'a TypePacked);
+
+impl fmt::Display for TypePackedDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            TypePacked::Number(number) => write!(f, "{}", number_name(number)),
+            TypePacked::Bool => write!(f, "bool"),
+            TypePacked::Bytes => write!(f, "bytes"),
+            TypePacked::String => write!(f, "string"),
+            TypePacked::Opaque(id) => write!(f, "opaque<{}>", id),
+            TypePacked::Range(inner) => write!(f, "range<{}>", number_name(inner)),
+            TypePacked::Array(inner) => write!(f, "array<{}>", TypePackedDisplay(inner)),
+            TypePacked::Tuples(fields) => write!(f, "({})", join_types(fields)),
+            TypePacked::Map(key, value) => write!(f, "map<{}, {}>", TypePackedDisplay(key), TypePackedDisplay(value)),
+            TypePacked::Optional(inner) => write!(f, "optional<{}>", TypePackedDisplay(inner)),
+            TypePacked::Any => write!(f, "any"),
+            TypePacked::OneOf(variants) => {
+                let variants = variants.iter()
+                    .map(|variant| format!("({})", join_types(variant)))
+                    .collect::<Vec<_>>();
+                write!(f, "oneof<{}>", variants.join(", "))
+            }
+        }
+    }
+}
+
+fn number_name(number: &NumberType) -> &'static str {
+    match number {
+        NumberType::U8 => "u8",
+        NumberType::U16 => "u16",
+        NumberType::U32 => "u32",
+        NumberType::U64 => "u64",
+        NumberType::U128 => "u128",
+        NumberType::U256 => "u256",
+    }
+}
+
+fn join_types(types: &[TypePacked]) -> String {
+    types.iter()
+        .map(|ty| TypePackedDisplay(ty).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Canonical form verification of the VM module
+// Contract hashes are derived from the module bytes,
+// so a module must have only one valid encoding
+pub trait ModuleCanonical {
+    // Verify that the constants are sorted by their encoding
+    // and that no constant is a structural duplicate of another one
+    fn verify_canonical(&self) -> Result<(), ReaderError>;
+}
+
+impl ModuleCanonical for Module {
+    fn verify_canonical(&self) -> Result<(), ReaderError> {
+        let encoded = self.constants()
+            .iter()
+            .map(Serializer::to_bytes)
+            .collect::<Vec<_>>();
+
+        // Strictly ascending order also reject the same encoding twice
+        if encoded.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        // Maps with a different entries order are equal
+        // while having different encodings
+        let mut seen = HashSet::with_capacity(self.constants().len());
+        if !self.constants().iter().all(|constant| seen.insert(constant)) {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(())
+    }
 }
 
 impl Serializer for ContractModule {
     fn write(&self, writer: &mut Writer) {
         self.version.write(writer);
 
+        // The module encoding depends on the version in the context
+        // Always encode it using the declared version, a different one
+        // set by the caller is restored once the module is written
+        let previous = writer.context().get_optional::<ContractVersion>().copied();
         writer.context_mut().store(self.version);
+
         self.module.write(writer);
+
+        match previous {
+            Some(version) => writer.context_mut().store(version),
+            None => writer.context_mut().remove::<ContractVersion>(),
+        }
+
+        if Self::has_dependencies_support(self.version) {
+            writer.write_u8(self.dependencies.len() as u8);
+            for dependency in self.dependencies.iter() {
+                dependency.write(writer);
+            }
+        }
+
+        if Self::has_upgrade_support(self.version) {
+            self.upgrade_authority.write(writer);
+        }
+
+        if Self::has_source_hash_support(self.version) {
+            self.source_hash.write(writer);
+        }
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
         let version = ContractVersion::read(reader)?;
 
-        // Store the version in the context for later use
+        // Same rule as the write: always decode it using the declared version,
+        // a different one set by the caller is restored once the module is read
+        let previous = reader.context().get_optional::<ContractVersion>().copied();
         reader.context_mut().store(version);
 
-        let module = Module::read(reader)?;
+        let module = Module::read(reader);
+
+        match previous {
+            Some(version) => reader.context_mut().store(version),
+            None => reader.context_mut().remove::<ContractVersion>(),
+        }
+
+        let module = module?;
+
+        let dependencies = if Self::has_dependencies_support(version) {
+            let len = reader.read_u8()? as usize;
+            if len > MAX_CONTRACT_DEPENDENCIES {
+                return Err(ReaderError::InvalidSize);
+            }
+
+            let mut dependencies = Vec::with_capacity(len);
+            for _ in 0..len {
+                dependencies.push(Hash::read(reader)?);
+            }
+
+            dependencies
+        } else {
+            Vec::new()
+        };
+
+        let upgrade_authority = if Self::has_upgrade_support(version) {
+            Option::read(reader)?
+        } else {
+            None
+        };
+
+        let source_hash = if Self::has_source_hash_support(version) {
+            Option::read(reader)?
+        } else {
+            None
+        };
 
         Ok(Self {
             version,
             module: Arc::new(module),
+            dependencies,
+            upgrade_authority,
+            source_hash,
         })
     }
 
     fn size(&self) -> usize {
-        self.version.size() + self.module.size()
+        let mut size = self.version.size() + self.module.size();
+        // V1 and V2 write a parameters presence flag for each public chunk
+        if matches!(self.version, ContractVersion::V1 | ContractVersion::V2) {
+            size += self.module.chunks()
+                .iter()
+                .filter(|entry| matches!(entry.access, Access::All { .. } | Access::Entry { .. }))
+                .count();
+        }
+
+        if Self::has_dependencies_support(self.version) {
+            size += 1 + self.dependencies.len() * HASH_SIZE;
+        }
+
+        if Self::has_upgrade_support(self.version) {
+            size += self.upgrade_authority.size();
+        }
+
+        if Self::has_source_hash_support(self.version) {
+            size += self.source_hash.size();
+        }
+
+        size
     }
 }
 
@@ -302,6 +559,8 @@ impl Serializer for TypePacked {
 
 #[cfg(test)]
 mod tests {
+    use indexmap::IndexSet;
+    use xelis_vm::{Chunk, ModuleChunk};
     use super::*;
 
     fn roundtrip(value: TypePacked) -> TypePacked {
@@ -471,4 +730,49 @@ mod tests {
         assert_eq!(roundtrip(first.clone()), first);
         assert_eq!(roundtrip(second.clone()), second);
     }
+
+    #[test]
+    fn test_type_packed_display() {
+        let cases = vec![
+            (TypePacked::Number(NumberType::U256), "u256"),
+            (TypePacked::Opaque(42), "opaque<42>"),
+            (TypePacked::Range(Box::new(NumberType::U128)), "range<u128>"),
+            (TypePacked::Array(Box::new(TypePacked::Number(NumberType::U64))), "array<u64>"),
+            (TypePacked::Map(Box::new(TypePacked::String), Box::new(TypePacked::Bool)), "map<string, bool>"),
+            (TypePacked::Optional(Box::new(TypePacked::Number(NumberType::U8))), "optional<u8>"),
+            (TypePacked::Tuples(vec![TypePacked::Bytes, TypePacked::Optional(Box::new(TypePacked::Bool))]), "(bytes, optional<bool>)"),
+            (nested_array(3), "array<array<array<bool>>>"),
+            (nested_optional(2), "optional<optional<u64>>"),
+            (nested_map_value(2), "map<u8, map<u8, string>>"),
+            (
+                TypePacked::OneOf(vec![
+                    vec![nested_array(1)],
+                    vec![TypePacked::String, nested_optional(1)],
+                    vec![],
+                ]),
+                "oneof<(array<bool>), (string, optional<u64>), ()>"
+            ),
+        ];
+
+        for (ty, expected) in cases {
+            assert_eq!(TypePackedDisplay(&ty).to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_entry_signature() {
+        let chunks = vec![
+            ModuleChunk { chunk: Chunk::new(), access: Access::Internal },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Entry { parameters: Some(vec![TypePacked::Number(NumberType::U64), nested_map_value(1)]) } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::All { parameters: None } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Entry { parameters: Some(Vec::new()) } },
+        ];
+        let module = Module::with(IndexSet::new(), chunks, Default::default());
+
+        assert_eq!(module.entry_signature(0), None);
+        assert_eq!(module.entry_signature(1).as_deref(), Some("u64, map<u8, string>"));
+        assert_eq!(module.entry_signature(2), None);
+        assert_eq!(module.entry_signature(3).as_deref(), Some(""));
+        assert_eq!(module.entry_signature(4), None);
+    }
 }
\ No newline at end of file
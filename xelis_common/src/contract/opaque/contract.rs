@@ -165,6 +165,8 @@ pub async fn contract_call<'a, 'ty, 'r, P: ContractProvider>(zelf: FnInstance<'a
         None
     };
 
+    // The callee is executed in the same VM and chain state,
+    // so its logs are recorded right after the ones emitted before the call
     Ok(SysCallResult::ModuleCall {
         module: opaque.contract_module.module.clone(),
         metadata: Arc::new(ContractMetadata {
@@ -165,6 +165,9 @@ macro_rules! init_test {
         $chain.internal_set_contract_module($contract.clone(), ContractModule {
             module: Arc::new(Module::new()),
             version: ContractVersion::V0,
+            dependencies: Vec::new(),
+            upgrade_authority: None,
+            source_hash: None,
         });
         let (_, mut $state) = $chain.get_contract_environment_for(
             Cow::Borrowed(&$contract),
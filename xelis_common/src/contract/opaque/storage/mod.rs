@@ -28,7 +28,7 @@ use crate::{
         ModuleMetadata,
     },
     crypto::Hash,
-    versioned_type::VersionedState
+    versioned_type::{Versioned, VersionedState}
 };
 use super::Serializer;
 
@@ -56,6 +56,11 @@ pub trait ContractStorage {
 
     // check if a contract hash exists in the storage
     async fn has_contract(&self, contract: &Hash, topoheight: TopoHeight) -> Result<bool, anyhow::Error>;
+
+    // persist all the writes of a contract at once
+    // each version already points to its previous topoheight
+    // a None value is stored as a deletion
+    async fn commit_data_batch(&mut self, contract: &Hash, topoheight: TopoHeight, writes: Vec<(ValueCell, Versioned<Option<ValueCell>>)>) -> Result<(), anyhow::Error>;
 }
 
 impl JSONHelper for OpaqueStorage {}
@@ -9,9 +9,11 @@ use xelis_vm::{
     FnReturnType,
     OpaqueWrapper,
     Primitive,
-    SysCallResult
+    SysCallResult,
+    ValueCell
 };
 use crate::{
+    config::FEE_PER_BYTE_READ_CONTRACT,
     contract::{
         from_context,
         get_cache_for_contract,
@@ -21,8 +23,10 @@ use crate::{
         ModuleMetadata,
     },
     crypto::Hash,
+    serializer::Serializer,
     versioned_type::VersionedState
 };
+use super::data_size_in_bytes;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct OpaqueReadOnlyStorage(Hash);
@@ -112,4 +116,65 @@ pub async fn read_only_storage_has<'a, 'ty, 'r, P: ContractProvider>(zelf: FnIns
     };
 
     Ok(SysCallResult::Return(Primitive::Boolean(contains).into()))
-}
\ No newline at end of file
+}
+
+// Read a value from another contract storage without invoking it
+// The value is returned as bytes, non-bytes values are serialized
+pub async fn read_contract_data<'a, 'ty, 'r, P: ContractProvider>(_: FnInstance<'a>, mut params: FnParams, metadata: &ModuleMetadata<'_>, context: &mut VMContext<'ty, 'r>) -> FnReturnType<ContractMetadata> {
+    let key = params.remove(1)
+        .into_owned();
+    let contract: Hash = params.remove(0)
+        .into_owned()
+        .into_opaque_type()?;
+
+    if !key.is_serializable() {
+        return Err(EnvironmentError::Static("Key is not serializable"))
+    }
+
+    // Charge the key bytes before touching the storage
+    // so a contract out of gas can't trigger a load
+    context.increase_gas_usage(data_size_in_bytes(&key) as u64 * FEE_PER_BYTE_READ_CONTRACT)?;
+
+    let value = {
+        let (storage, state) = from_context::<P>(context)?;
+
+        // A contract can always read its own storage
+        if contract != metadata.metadata.contract_executor && !state.permission.allows_read(&contract) {
+            return Err(EnvironmentError::Static("Reading this contract data is not allowed"))
+        }
+
+        // Read from global cache first, then fallback to provider
+        match get_cache_for_contract(&mut state.changes.caches, state.global_caches, contract.clone())
+            .storage
+            .entry(key.clone()) {
+                Entry::Occupied(v) => v.get()
+                    .as_ref()
+                    .and_then(|(_, v)| v.clone()),
+                Entry::Vacant(v) => {
+                    let data = storage.load_data(&contract, &key, state.topoheight).await?
+                        .map(|(topo, v)| (VersionedState::FetchedAt(topo), v));
+
+                    v.insert(data)
+                        .as_ref()
+                        .and_then(|(_, v)| v.clone())
+                }
+        }
+    };
+
+    let Some(value) = value else {
+        return Ok(SysCallResult::Return(Primitive::Null.into()))
+    };
+
+    // Charge the gas per byte of value read
+    let size = data_size_in_bytes(&value);
+    context.increase_gas_usage(size as u64 * FEE_PER_BYTE_READ_CONTRACT)?;
+
+    // We are forced to do a deep clone in case a contract try to attack
+    // another contract memory due to how XVM handle references
+    let bytes = match value.deep_clone() {
+        ValueCell::Bytes(bytes) => bytes,
+        value => value.to_bytes(),
+    };
+
+    Ok(SysCallResult::Return(ValueCell::Bytes(bytes).into()))
+}
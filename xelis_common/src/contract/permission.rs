@@ -24,6 +24,15 @@ impl ContractCallChunk {
             ContractCallChunk::Exclude(chunks) => !chunks.contains(&chunk_id),
         }
     }
+
+    // Check if at least one chunk is allowed
+    pub fn allows_any(&self) -> bool {
+        match self {
+            ContractCallChunk::All => true,
+            ContractCallChunk::Specific(chunks) => !chunks.is_empty(),
+            ContractCallChunk::Exclude(chunks) => chunks.len() <= u16::MAX as usize,
+        }
+    }
 }
 
 impl Serializer for ContractCallChunk {
@@ -172,6 +181,19 @@ impl InterContractPermission {
                 .map_or(false, |call| call.chunk.allows(chunk_id)),
         }
     }
+
+    // Check if the permission allows reading the target contract storage
+    // A contract is readable as soon as one of its chunks is callable
+    pub fn allows_read(&self, target: &Hash) -> bool {
+        match self {
+            InterContractPermission::None => false,
+            InterContractPermission::All => true,
+            InterContractPermission::Specific(allowed) => allowed.get(target)
+                .map_or(false, |call| call.chunk.allows_any()),
+            InterContractPermission::Exclude(excluded) => !excluded.get(target)
+                .map_or(false, |call| matches!(call.chunk, ContractCallChunk::All)),
+        }
+    }
 }
 
 impl Serializer for InterContractPermission {
@@ -291,4 +313,36 @@ mod tests {
         assert!(!permission.allows(&Hash::new([3u8; 32]), 5));
         assert!(!permission.allows(&Hash::new([3u8; 32]), 6));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_allowed_contract_read() {
+        let a = Hash::new([1u8; 32]);
+        let b = Hash::new([2u8; 32]);
+
+        assert!(!InterContractPermission::None.allows_read(&a));
+        assert!(InterContractPermission::All.allows_read(&a));
+
+        let specific = InterContractPermission::Specific(
+            vec![ContractCall { contract: a.clone(), chunk: ContractCallChunk::Specific(vec![0].into_iter().collect()) }].into_iter().collect()
+        );
+        assert!(specific.allows_read(&a));
+        assert!(!specific.allows_read(&b));
+
+        // No callable chunk means no read access
+        let empty = InterContractPermission::Specific(
+            vec![ContractCall { contract: a.clone(), chunk: ContractCallChunk::Specific(IndexSet::new()) }].into_iter().collect()
+        );
+        assert!(!empty.allows_read(&a));
+
+        // Only a full exclusion prevents reading the contract
+        let exclude = InterContractPermission::Exclude(
+            vec![
+                ContractCall { contract: a.clone(), chunk: ContractCallChunk::All },
+                ContractCall { contract: b.clone(), chunk: ContractCallChunk::Specific(vec![0].into_iter().collect()) },
+            ].into_iter().collect()
+        );
+        assert!(!exclude.allows_read(&a));
+        assert!(exclude.allows_read(&b));
+        assert!(exclude.allows_read(&Hash::new([3u8; 32])));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::contract::ContractLog;
+use crate::{block::BlockVersion, contract::ContractLog};
 
 use super::*;
 
@@ -69,4 +69,151 @@ async fn contract_event_flow() {
     // - call_event execution
     // - on_contract_event execution
     assert_eq!(executions, 3);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn contract_logs_emission_order() {
+    // Contract A emits an event, calls B which emits one,
+    // then A emits again: logs must follow the emission order
+
+    let code = r#"
+        entry emit_b() {
+            emit_event(2, ["b"]);
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    let b_hash = create_contract(&mut chain_state, code).expect("create contract B");
+
+    let code = r#"
+        entry emit_a() {
+            emit_event(1, ["a"]);
+
+            let contract_hash = Hash::from_hex("CONTRACT_HASH");
+            let contract = Contract::new(contract_hash).expect("load contract");
+            let deposits: map<Hash, u64> = {};
+            contract.call(0, [], deposits);
+
+            emit_event(3, ["a"]);
+            return 0
+        }
+    "#.replace("CONTRACT_HASH", &b_hash.to_string());
+
+    let a_hash = create_contract(&mut chain_state, &code).expect("create contract A");
+
+    let execution = vm::invoke_contract(
+        ContractCaller::System,
+        &mut chain_state,
+        Cow::Owned(a_hash.clone()),
+        None,
+        std::iter::empty(),
+        IndexMap::new(),
+        100000,
+        InvokeContract::Entry(0),
+        Cow::Owned(InterContractPermission::All),
+        true,
+    ).await.expect("invoke contract A");
+
+    assert!(execution.is_success(), "contract A execution failed {:?}", execution);
+
+    let events = chain_state.contract_logs
+        .values()
+        .flatten()
+        .filter_map(|log| match log {
+            ContractLog::Event { contract, event_id } => Some((contract.clone(), *event_id)),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(events, vec![
+        (a_hash.clone(), 1),
+        (b_hash, 2),
+        (a_hash, 3),
+    ]);
+}
+
+// Register three listeners to the same event in descending hash order
+// Returns the listeners in registration order and in dispatch order
+async fn dispatch_event_callbacks(version: BlockVersion) -> (Vec<Hash>, Vec<Hash>) {
+    let code = r#"
+        entry call_event() {
+            emit_event(42, ["hello"]);
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    chain_state.set_block_version(version);
+    let emitter_hash = create_contract(&mut chain_state, code).expect("create emit event contract");
+
+    let code = r#"
+        fn on_contract_event(a: string) -> u64 {
+            emit_event(7, [a]);
+            return 0
+        }
+
+        hook constructor() -> u64 {
+            let contract_hash = Hash::from_hex("CONTRACT_HASH");
+            let contract = Contract::new(contract_hash).expect("load contract");
+            contract.listen_event(42, on_contract_event, 5000);
+
+            return 0
+        }
+    "#.replace("CONTRACT_HASH", &emitter_hash.to_string());
+
+    let mut listeners = Vec::new();
+    for _ in 0..3 {
+        listeners.push(create_contract(&mut chain_state, &code).expect("create listener contract"));
+    }
+    listeners.sort_by(|a, b| b.cmp(a));
+
+    for listener in listeners.iter() {
+        let execution = vm::invoke_contract(
+            ContractCaller::System,
+            &mut chain_state,
+            Cow::Owned(listener.clone()),
+            None,
+            std::iter::empty(),
+            IndexMap::new(),
+            100000,
+            InvokeContract::Hook(0),
+            Cow::Owned(Default::default()),
+            true,
+        ).await.expect("deploy listener contract");
+
+        assert!(execution.is_success(), "listener contract deployment failed {:?}", execution);
+    }
+
+    let execution = invoke_contract(
+        &mut chain_state,
+        &emitter_hash,
+        InvokeContract::Entry(0),
+        vec![],
+    ).await.expect("invoke emitter contract");
+
+    assert!(execution.is_success(), "emitter contract execution failed {:?}", execution);
+
+    let dispatched = chain_state.contract_logs
+        .values()
+        .flatten()
+        .filter_map(|log| match log {
+            ContractLog::Event { contract, event_id: 7 } => Some(contract.clone()),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    (listeners, dispatched)
+}
+
+#[tokio::test]
+async fn contract_event_callbacks_dispatch_order() {
+    // Starting V6, pending callbacks are dispatched by listener hash
+    let (mut listeners, dispatched) = dispatch_event_callbacks(BlockVersion::V6).await;
+    listeners.sort();
+    assert_eq!(dispatched, listeners);
+
+    // Before, they keep their registration order
+    let (listeners, dispatched) = dispatch_event_callbacks(BlockVersion::V5).await;
+    assert_eq!(dispatched, listeners);
+}
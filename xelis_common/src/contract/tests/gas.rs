@@ -1,10 +1,19 @@
+use std::sync::Arc;
 use curve25519_dalek::Scalar;
 use indexmap::IndexMap;
 
 use crate::{
     config::XELIS_ASSET,
+    contract::{
+        vm::{refund_gas_sources, verify_scheduled_executions, ContractError},
+        ExecutionsChanges,
+        ScheduledExecution,
+        ScheduledExecutionKind,
+        Source
+    },
     crypto::{
         Hash,
+        KeyPair,
         proofs::G
     },
     transaction::{
@@ -37,6 +46,22 @@ async fn test_blockchain_apply_state_gas_tracking() {
     assert_eq!(state.burned_coins.get(&XELIS_ASSET), Some(&150));
 }
 
+#[tokio::test]
+async fn test_blockchain_apply_state_burned_coins_per_asset() {
+    let mut state = MockChainState::new();
+    let asset = Hash::new([1u8; 32]);
+
+    state.add_burned_coins(&XELIS_ASSET, 100).await.unwrap();
+    state.add_burned_coins(&asset, 30).await.unwrap();
+    state.add_burned_coins(&XELIS_ASSET, 20).await.unwrap();
+
+    assert_eq!(state.get_burned_coins(&XELIS_ASSET).await.unwrap(), 120);
+    assert_eq!(state.get_burned_coins(&asset).await.unwrap(), 30);
+
+    // Nothing burned yet
+    assert_eq!(state.get_burned_coins(&Hash::zero()).await.unwrap(), 0);
+}
+
 #[tokio::test]
 async fn test_contract_balance_for_gas() {
     let mut state = MockChainState::new();
@@ -85,8 +110,6 @@ async fn test_contract_balance_for_gas() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_single_contract() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-
     let mut state = MockChainState::new();
     let contract_hash = Hash::zero();
     
@@ -113,9 +136,6 @@ async fn test_refund_gas_sources_single_contract() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_single_account() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-    use crate::crypto::KeyPair;
-
     let mut state = MockChainState::new();
     let keypair = KeyPair::new();
     let account = keypair.get_public_key().compress();
@@ -145,8 +165,6 @@ async fn test_refund_gas_sources_single_account() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_multiple_contracts() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-
     let mut state = MockChainState::new();
     let contract1 = Hash::zero();
     let contract2 = Hash::new([1u8; 32]);
@@ -186,8 +204,6 @@ async fn test_refund_gas_sources_multiple_contracts() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_proportional_different_amounts() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-
     let mut state = MockChainState::new();
     let contract1 = Hash::zero();
     let contract2 = Hash::new([1u8; 32]);
@@ -228,8 +244,6 @@ async fn test_refund_gas_sources_proportional_different_amounts() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_all_gas_used() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-
     let mut state = MockChainState::new();
     let contract = Hash::zero();
     
@@ -256,8 +270,6 @@ async fn test_refund_gas_sources_all_gas_used() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_no_overflow() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-
     let mut state = MockChainState::new();
     let contract = Hash::zero();
     
@@ -278,9 +290,6 @@ async fn test_refund_gas_sources_no_overflow() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_mixed_sources() {
-    use crate::contract::{vm::refund_gas_sources, Source};
-    use crate::crypto::KeyPair;
-
     let mut state = MockChainState::new();
     let contract = Hash::zero();
     let keypair = KeyPair::new();
@@ -329,8 +338,6 @@ async fn test_refund_gas_sources_mixed_sources() {
 
 #[tokio::test]
 async fn test_refund_gas_sources_empty_sources() {
-    use crate::contract::vm::refund_gas_sources;
-
     let mut state = MockChainState::new();
     
     // Empty gas sources - should not error
@@ -340,4 +347,79 @@ async fn test_refund_gas_sources_empty_sources() {
     
     // Nothing should have changed
     assert!(state.contract_caches.is_empty());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_verify_scheduled_executions_gas_sources() {
+    let contract = Hash::zero();
+    let account = KeyPair::new().get_public_key().compress();
+    let hash = Arc::new(Hash::max());
+
+    let mut execution = ScheduledExecution {
+        hash: hash.clone(),
+        contract: contract.clone(),
+        chunk_id: 0,
+        params: Vec::new(),
+        max_gas: 1000,
+        kind: ScheduledExecutionKind::BlockEnd,
+        gas_sources: [
+            (Source::Contract(contract.clone()), 600),
+            (Source::Account(account), 400)
+        ].into_iter().collect(),
+    };
+
+    let mut changes = ExecutionsChanges::default();
+    changes.executions.insert(hash.clone(), execution.clone());
+    assert!(verify_scheduled_executions::<()>(&changes).is_ok());
+
+    // Gas sources don't cover the max gas
+    execution.max_gas = 1500;
+    changes.executions.insert(hash.clone(), execution.clone());
+    assert!(matches!(
+        verify_scheduled_executions::<()>(&changes),
+        Err(ContractError::ScheduledExecutionUnderfunded(h)) if h == *hash
+    ));
+
+    // Another contract can't fund the execution
+    execution.max_gas = 1000;
+    execution.gas_sources = [(Source::Contract(Hash::new([1; 32])), 1000)].into_iter().collect();
+    changes.executions.insert(hash.clone(), execution);
+    assert!(matches!(
+        verify_scheduled_executions::<()>(&changes),
+        Err(ContractError::ScheduledExecutionUnderfunded(h)) if h == *hash
+    ));
+}
+
+#[tokio::test]
+async fn test_refund_gas_sources_attribution() {
+    let mut state = MockChainState::new();
+    let contract = Hash::zero();
+    let keypair = KeyPair::new();
+    let account = keypair.get_public_key().compress();
+
+    {
+        let (_, balance) = state.get_contract_balance_for_gas(&contract).await.unwrap();
+        *balance = 1000;
+    }
+    state.accounts.insert(account.clone(), MockAccount {
+        balances: [(XELIS_ASSET, keypair.get_public_key().encrypt(1000u64))].into_iter().collect(),
+        nonce: 0,
+    });
+
+    // Account paid 700 gas, contract injected 300 gas
+    let mut gas_sources = IndexMap::new();
+    gas_sources.insert(Source::Account(account.clone()), 700);
+    gas_sources.insert(Source::Contract(contract.clone()), 300);
+
+    // Used gas: 333, max gas: 1000, so 667 must be refunded
+    // account is refunded 466 (466.9 rounded down)
+    // and the contract 200 (200.1 rounded down)
+    // the rounding dust isn't refunded and stays attributed to its payers
+    let attribution = refund_gas_sources(&mut state, gas_sources, 333, 1000).await.unwrap();
+    assert_eq!(attribution.per_source.get(&Source::Account(account)), Some(&234));
+    assert_eq!(attribution.per_source.get(&Source::Contract(contract.clone())), Some(&100));
+    assert_eq!(attribution.total(), 334);
+
+    let (_, balance) = state.get_contract_balance_for_gas(&contract).await.unwrap();
+    assert_eq!(*balance, 1200);
+}
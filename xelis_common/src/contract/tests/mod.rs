@@ -5,18 +5,25 @@ use xelis_builder::EnvironmentBuilder;
 use xelis_compiler::Compiler;
 use xelis_lexer::Lexer;
 use xelis_parser::Parser;
-use xelis_vm::{Module, Primitive, ValueCell};
+use xelis_vm::{Access, Module, OpaqueWrapper, Primitive, ValueCell};
 
 use crate::{
+    block::BlockVersion,
     config::TX_GAS_BURN_PERCENT,
     contract::{
         ContractMetadata,
         ContractModule,
+        ContractVersion,
+        ExitError,
+        InterContractPermission,
+        ModuleAccess,
         Source,
-        vm::{self, ContractCaller, ContractError, InvokeContract}
+        vm::{self, ContractCaller, ContractError, ExitValue, InvokeContract}
     },
-    crypto::Hash,
-    transaction::{tests::MockChainState, verify::BlockchainContractState}
+    crypto::{hash, Hash},
+    serializer::Serializer,
+    transaction::{tests::MockChainState, verify::BlockchainContractState},
+    versioned_type::VersionedState
 };
 
 mod gas;
@@ -48,6 +55,9 @@ pub fn create_contract(state: &mut MockChainState, code: &str) -> anyhow::Result
         ContractModule {
             version: Default::default(),
             module: Arc::new(module),
+            dependencies: Vec::new(),
+            upgrade_authority: None,
+            source_hash: None,
         },
     );
 
@@ -260,4 +270,231 @@ async fn test_refund_with_gas_sources() {
     assert_eq!(*contract_balance_2, expected_refund_per_source, "contract gas balance should receive a refund");
 
     assert_eq!(chain_state.contract_caches.len(), 3);
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_read_contract_data() {
+    let mut chain_state = MockChainState::new();
+
+    // Contract B only holds some data
+    let code = r#"
+        entry main() {
+            return 0
+        }
+    "#;
+    let contract_b = create_contract(&mut chain_state, code).expect("create contract B");
+
+    let key = ValueCell::Bytes(b"key".to_vec());
+    let value = ValueCell::Bytes(b"value".to_vec());
+    chain_state.contract_caches.entry(contract_b.clone())
+        .or_default()
+        .storage
+        .insert(key.clone(), Some((VersionedState::New, Some(value.clone()))));
+
+    // Contract A reads the data stored by B and keeps a copy in its own storage
+    let code = r#"
+        entry read(contract: Hash, key: bytes) {
+            let value: bytes = read_contract_data(contract, key).expect("value");
+            let storage: Storage = Storage::new();
+            storage.store("read", value);
+            return 0
+        }
+
+        entry read_missing(contract: Hash, key: bytes) {
+            require(read_contract_data(contract, key).is_none(), "value should not exist");
+            return 0
+        }
+    "#;
+    let contract_a = create_contract(&mut chain_state, code).expect("create contract A");
+
+    let params = || vec![
+        Primitive::Opaque(contract_b.clone().into()).into(),
+        key.clone(),
+    ];
+
+    let result = vm::invoke_contract(
+        ContractCaller::System,
+        &mut chain_state,
+        Cow::Owned(contract_a.clone()),
+        None,
+        params().into_iter(),
+        IndexMap::new(),
+        10000,
+        InvokeContract::Entry(0),
+        Cow::Owned(InterContractPermission::All),
+        true,
+    ).await.expect("read contract data");
+    assert!(result.is_success(), "reading B data should succeed: {:?}", result);
+
+    // The value read from B was copied into A storage
+    let stored = chain_state.contract_caches.get(&contract_a)
+        .and_then(|cache| cache.storage_get(&Primitive::String("read".to_owned()).into()));
+    assert_eq!(stored, Some(&value));
+
+    // Unknown key returns null
+    let result = vm::invoke_contract(
+        ContractCaller::System,
+        &mut chain_state,
+        Cow::Owned(contract_a.clone()),
+        None,
+        vec![
+            Primitive::Opaque(contract_b.clone().into()).into(),
+            ValueCell::Bytes(b"unknown".to_vec()),
+        ].into_iter(),
+        IndexMap::new(),
+        10000,
+        InvokeContract::Entry(1),
+        Cow::Owned(InterContractPermission::All),
+        true,
+    ).await.expect("read missing contract data");
+    assert!(result.is_success(), "reading unknown key should return null: {:?}", result);
+
+    // The key is charged before the lookup, even if no value is found
+    let result = vm::invoke_contract(
+        ContractCaller::System,
+        &mut chain_state,
+        Cow::Owned(contract_a.clone()),
+        None,
+        vec![
+            Primitive::Opaque(contract_b.clone().into()).into(),
+            ValueCell::Bytes(vec![0; 20000]),
+        ].into_iter(),
+        IndexMap::new(),
+        10000,
+        InvokeContract::Entry(1),
+        Cow::Owned(InterContractPermission::All),
+        true,
+    ).await.expect("read missing contract data with a large key");
+    assert!(!result.is_success(), "reading with a key above the gas limit should fail: {:?}", result);
+
+    // Without the permission, reading B data is denied
+    let result = invoke_contract(&mut chain_state, &contract_a, InvokeContract::Entry(0), params()).await
+        .expect("read contract data without permission");
+    assert!(
+        matches!(&result.exit_value, ExitValue::Error(ExitError::RuntimeError(msg)) if msg == "Reading this contract data is not allowed"),
+        "reading B data without permission should fail: {:?}", result
+    );
+}
+
+#[tokio::test]
+async fn test_invoke_internal_chunk_rejected() {
+    let code = r#"
+        fn helper() -> u64 {
+            return 0
+        }
+
+        entry main() {
+            return helper()
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    let module = compile_contract(&chain_state.env, code).expect("compile contract");
+
+    let chunk_id = |f: fn(&Access) -> bool| (0..module.chunks().len() as u16)
+        .find(|id| module.chunk_access(*id).is_some_and(f))
+        .expect("chunk");
+    let internal = chunk_id(|access| matches!(access, Access::Internal));
+    let entry = chunk_id(|access| matches!(access, Access::Entry { .. }));
+
+    assert!(!module.is_externally_callable(internal));
+    assert!(module.is_externally_callable(entry));
+    assert!(module.chunk_access(module.chunks().len() as u16).is_none());
+
+    let contract = create_contract(&mut chain_state, code).expect("create contract");
+
+    let result = invoke_contract(&mut chain_state, &contract, InvokeContract::Entry(entry), Vec::new()).await
+        .expect("invoke entry");
+    assert!(result.is_success(), "entry should be callable: {:?}", result);
+
+    let result = invoke_contract(&mut chain_state, &contract, InvokeContract::Entry(internal), Vec::new()).await
+        .expect("invoke internal chunk");
+    assert!(matches!(result.exit_value, ExitValue::Error(ExitError::InvalidEntry)), "internal chunk should be rejected: {:?}", result);
+}
+
+#[tokio::test]
+async fn test_block_context() {
+    let code = r#"
+        entry main(topoheight: u64, block_hash: Hash, timestamp: u64) {
+            require(get_current_topoheight() == topoheight, "invalid topoheight");
+
+            let block: Block = Block::current();
+            require(block.hash() == block_hash, "invalid block hash");
+            require(block.timestamp() == timestamp, "invalid timestamp");
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    chain_state.block_hash = Hash::new([7u8; 32]);
+    let contract_hash = create_contract(&mut chain_state, code).expect("compile contract");
+
+    // The mock executes contracts at topoheight 1
+    let params = |block_hash: Hash| vec![
+        Primitive::U64(1).into(),
+        Primitive::Opaque(OpaqueWrapper::new(block_hash)).into(),
+        Primitive::U64(chain_state.block.get_timestamp()).into(),
+    ];
+    let expected = params(chain_state.block_hash.clone());
+    let invalid = params(Hash::zero());
+
+    // Same result on every execution
+    for _ in 0..2 {
+        let result = invoke_contract(&mut chain_state, &contract_hash, InvokeContract::Entry(0), expected.clone()).await
+            .expect("contract execution");
+        assert!(result.is_success(), "block context mismatch: {:?}", result);
+    }
+
+    let result = invoke_contract(&mut chain_state, &contract_hash, InvokeContract::Entry(0), invalid).await
+        .expect("contract execution");
+    assert!(!result.is_success());
+}
+
+#[tokio::test]
+async fn test_block_version_context() {
+    let code = r#"
+        entry main(version: u8) {
+            let block: Block = Block::current();
+            require(block.version() == version, "invalid block version");
+            return 0
+        }
+    "#;
+
+    let mut chain_state = MockChainState::new();
+    let contract_hash = create_contract(&mut chain_state, code).expect("compile contract");
+
+    // Contracts see the version of the block in which they are executed
+    for version in [BlockVersion::V2, BlockVersion::V5, BlockVersion::V6] {
+        chain_state.set_block_version(version);
+        let result = invoke_contract(&mut chain_state, &contract_hash, InvokeContract::Entry(0), vec![Primitive::U8(version as u8).into()]).await
+            .expect("invoke contract");
+        assert!(result.is_success(), "block version {} should be seen: {:?}", version, result);
+    }
+
+    let result = invoke_contract(&mut chain_state, &contract_hash, InvokeContract::Entry(0), vec![Primitive::U8(BlockVersion::V5 as u8).into()]).await
+        .expect("invoke contract");
+    assert!(!result.is_success(), "previous block version should not be seen: {:?}", result);
+}
+
+#[test]
+fn test_verify_reproducible() {
+    let source = b"entry main() { return 0 }";
+    let mut module = ContractModule::new(ContractVersion::V3, Arc::new(Module::new()));
+    assert!(!module.verify_reproducible(source));
+
+    module.source_hash = Some(hash(source));
+    assert!(module.verify_reproducible(source));
+    assert!(!module.verify_reproducible(b"entry main() { return 1 }"));
+
+    // The source hash is kept through the serialization
+    let decoded = ContractModule::from_bytes(&module.to_bytes()).unwrap();
+    assert_eq!(decoded.source_hash, module.source_hash);
+    assert_eq!(decoded.size(), module.size());
+    assert!(decoded.verify_reproducible(source));
+
+    // But not written before V3
+    module.version = ContractVersion::V2;
+    let decoded = ContractModule::from_bytes(&module.to_bytes()).unwrap();
+    assert!(decoded.source_hash.is_none());
+    assert_eq!(module.to_bytes().len(), module.size());
+}
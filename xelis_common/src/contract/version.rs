@@ -12,14 +12,22 @@ pub enum ContractVersion {
     #[default]
     V0,
     V1,
+    // Scaffold for upcoming features
+    // Behaves like V1 for now
+    V2,
+    // Packs the chunks access in the module encoding
+    // Behaves like V2 otherwise
+    V3,
 }
 
 impl ContractVersion {
     #[inline(always)]
-    pub const fn variants() -> [ContractVersion; 2] {
+    pub const fn variants() -> [ContractVersion; 4] {
         [
             ContractVersion::V0,
             ContractVersion::V1,
+            ContractVersion::V2,
+            ContractVersion::V3,
         ]
     }
 }
@@ -31,6 +39,8 @@ impl FromStr for ContractVersion {
         match s {
             "v0" | "0" => Ok(ContractVersion::V0),
             "v1" | "1" => Ok(ContractVersion::V1),
+            "v2" | "2" => Ok(ContractVersion::V2),
+            "v3" | "3" => Ok(ContractVersion::V3),
             _ => Err("Invalid contract version"),
         }
     }
@@ -42,6 +52,8 @@ impl fmt::Display for ContractVersion {
         match self {
             ContractVersion::V0 => write!(f, "v0"),
             ContractVersion::V1 => write!(f, "v1"),
+            ContractVersion::V2 => write!(f, "v2"),
+            ContractVersion::V3 => write!(f, "v3"),
         }
     }
 }
@@ -55,6 +67,8 @@ impl Serializer for ContractVersion {
         match reader.read_u8()? {
             0 => Ok(ContractVersion::V0),
             1 => Ok(ContractVersion::V1),
+            2 => Ok(ContractVersion::V2),
+            3 => Ok(ContractVersion::V3),
             _ => Err(ReaderError::InvalidValue),
         }
     }
@@ -71,5 +85,36 @@ mod tests {
     #[test]
     fn test_ordering() {
         assert!(ContractVersion::V0 < ContractVersion::V1);
+        assert!(ContractVersion::V1 < ContractVersion::V2);
+        assert!(ContractVersion::V2 >= ContractVersion::V1);
+        assert!(ContractVersion::V2 < ContractVersion::V3);
+    }
+
+    #[test]
+    fn test_variants_are_sorted() {
+        let variants = ContractVersion::variants();
+        for window in variants.windows(2) {
+            assert!(window[0] < window[1]);
+        }
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        for version in ContractVersion::variants() {
+            let bytes = version.to_bytes();
+            assert_eq!(bytes, vec![version as u8]);
+            assert_eq!(ContractVersion::from_bytes(&bytes).unwrap(), version);
+
+            assert_eq!(version.to_string().parse::<ContractVersion>().unwrap(), version);
+        }
+
+        assert!(ContractVersion::from_bytes(&[4]).is_err());
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let json = serde_json::to_string(&ContractVersion::V2).unwrap();
+        assert_eq!(json, "\"v2\"");
+        assert_eq!(serde_json::from_str::<ContractVersion>(&json).unwrap(), ContractVersion::V2);
     }
 }
\ No newline at end of file
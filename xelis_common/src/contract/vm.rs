@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     collections::HashMap,
+    fmt,
     sync::Arc
 };
 
@@ -21,7 +22,9 @@ use crate::{
         ContractProvider,
         ContractProviderWrapper,
         ContractVersion,
+        ExecutionsChanges,
         InterContractPermission,
+        ModuleAccess,
         Source,
         ExitError,
         data_size_in_bytes,
@@ -72,6 +75,18 @@ impl<'a> ContractCaller<'a> {
     }
 }
 
+// Stable format used in logs to identify the caller
+impl<'a> fmt::Display for ContractCaller<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction(hash, _) => write!(f, "entry(tx={})", hash),
+            Self::Scheduled(hash, contract) => write!(f, "scheduled(id={},contract={})", hash, contract),
+            Self::EventCallback(caller, source) => write!(f, "event_callback(caller={},source={})", caller, source),
+            Self::System => write!(f, "system"),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ContractError<E> {
     #[error(transparent)]
@@ -86,6 +101,8 @@ pub enum ContractError<E> {
     GasBalance,
     #[error("Deposit decompressed not found")]
     DepositNotFound,
+    #[error("scheduled execution {} is underfunded", _0)]
+    ScheduledExecutionUnderfunded(Hash),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,6 +136,22 @@ pub struct ExecutionResult {
     pub vm_max_gas: u64,
     // exit value returned by the contract (if any)
     pub exit_value: ExitValue,
+    // net gas paid by each gas source
+    // empty if no gas sources were provided
+    pub gas_attribution: GasAttribution,
+}
+
+// Net gas paid by each gas source once the unused gas is refunded
+#[derive(Debug, Clone, Default)]
+pub struct GasAttribution {
+    pub per_source: IndexMap<Source, u64>,
+}
+
+impl GasAttribution {
+    // Total gas paid by all the sources
+    pub fn total(&self) -> u64 {
+        self.per_source.values().sum()
+    }
 }
 
 impl ExecutionResult {
@@ -159,6 +192,11 @@ pub(crate) async fn run_virtual_machine<'a, P: ContractProvider>(
     // This is the first chunk to be called
     match invoke {
         InvokeContract::Entry(entry) => {
+            if !contract_environment.module.is_externally_callable(entry) {
+                warn!("Invoke contract {} chunk {} is not externally callable", contract, entry);
+                return Ok((0, max_gas, ExitValue::Error(ExitError::InvalidEntry)))
+            }
+
             vm.invoke_chunk_with_args(entry, parameters)?;
         },
         InvokeContract::Hook(hook) => {
@@ -211,7 +249,7 @@ pub(crate) async fn run_virtual_machine<'a, P: ContractProvider>(
                 Level::Debug
             };
 
-            log!(level, "Invoke contract {} from {} result: {:#}", contract, caller.get_hash(), res);
+            log!(level, "Invoke contract {} from {} result: {:#}", contract, caller, res);
             // If the result return 0 as exit code, it means that everything went well
             let exit_code = match res.as_u64().ok() {
                 Some(v) => ExitValue::ExitCode(v),
@@ -245,7 +283,7 @@ pub(crate) async fn run_virtual_machine<'a, P: ContractProvider>(
             exit_code
         },
         Err(err) => {
-            log!(error_level, "Invoke contract {} from {} error: {:#}", contract, caller.get_hash(), err);
+            log!(error_level, "Invoke contract {} from {} error: {:#}", contract, caller, err);
             ExitValue::Error(err.into())
         }
     };
@@ -321,6 +359,8 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
         max_gas - used_gas
     };
 
+    let mut gas_attribution = GasAttribution::default();
+
     // In case of success, used_gas <= vm_max_gas
     if is_success {
         let mut changes = chain_state.changes;
@@ -333,6 +373,8 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
             refund_extra_gas_injections(state, gas_injections, max_gas, vm_max_gas, &mut logs, &mut changes.caches).await?;
         }
 
+        verify_scheduled_executions(&executions)?;
+
         state.merge_contract_changes(
             changes,
             executions,
@@ -341,7 +383,7 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
 
         if !gas_sources.is_empty() {
             // Refund the whole extra gas injections
-            refund_gas_sources(state, gas_sources, used_gas, max_gas).await?;
+            gas_attribution = refund_gas_sources(state, gas_sources, used_gas, max_gas).await?;
 
             debug!("After refunding gas sources, used gas: {}, refund gas: {}, set refund to 0", used_gas, refund_gas);
             refund_gas = 0;
@@ -357,7 +399,7 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
         logs.clear();
 
         if !gas_sources.is_empty() {
-            refund_gas_sources(state, gas_sources, used_gas, max_gas).await?;
+            gas_attribution = refund_gas_sources(state, gas_sources, used_gas, max_gas).await?;
 
             debug!("After refunding gas sources, used gas: {}, refund gas: {}, set refund to 0", used_gas, refund_gas);
             refund_gas = 0;
@@ -439,18 +481,45 @@ pub async fn invoke_contract<'a, P: ContractProvider, E, B: BlockchainApplyState
         burned_gas,
         fee_gas,
         exit_value,
+        gas_attribution,
     })
 }
 
+// Verify that each scheduled execution is fully covered by its gas sources
+// Contract sources are charged when scheduling, so they can only be
+// the scheduled contract itself
+pub fn verify_scheduled_executions<E>(executions: &ExecutionsChanges) -> Result<(), ContractError<E>> {
+    for execution in executions.executions.values() {
+        let mut total: u64 = 0;
+        for (source, gas) in execution.gas_sources.iter() {
+            if let Source::Contract(contract) = source {
+                if *contract != execution.contract {
+                    return Err(ContractError::ScheduledExecutionUnderfunded(execution.hash.as_ref().clone()));
+                }
+            }
+
+            total = total.checked_add(*gas)
+                .ok_or(ContractError::GasOverflow)?;
+        }
+
+        if total != execution.max_gas {
+            return Err(ContractError::ScheduledExecutionUnderfunded(execution.hash.as_ref().clone()));
+        }
+    }
+
+    Ok(())
+}
+
 // We need to refund the extra (unused) gas
 // this is the tx max gas - used gas
 // We want to refund proportionally to the injections made
+// Returns the net gas paid by each source
 pub async fn refund_gas_sources<'a, P: ContractProvider, E, B: BlockchainApplyState<'a, P, E>>(
     state: &mut B,
     gas_sources: IndexMap<Source, u64>,
     used_gas: u64,
     tx_max_gas: u64,
-) -> Result<(), ContractError<E>> {
+) -> Result<GasAttribution, ContractError<E>> {
     let mut gas_refund_left = tx_max_gas.checked_sub(used_gas)
         .ok_or(ContractError::GasOverflow)?;
 
@@ -463,8 +532,13 @@ pub async fn refund_gas_sources<'a, P: ContractProvider, E, B: BlockchainApplySt
     // refund 1: 100
     // refund 2: 100
     let total_injected: u64 = gas_sources.values().sum();
+    // Each source paid its full gas until refunded
+    let mut attribution = GasAttribution {
+        per_source: gas_sources.clone(),
+    };
+
     if total_injected == 0 {
-        return Ok(());
+        return Ok(attribution);
     }
 
     let initial_gas_refund = gas_refund_left;
@@ -478,6 +552,9 @@ pub async fn refund_gas_sources<'a, P: ContractProvider, E, B: BlockchainApplySt
         let refund = proportion as u64;
 
         let refund_amount = refund.min(gas_refund_left);
+        if let Some(paid) = attribution.per_source.get_mut(&source) {
+            *paid = paid.saturating_sub(refund_amount);
+        }
 
         match source {
             Source::Contract(contract) => {
@@ -501,7 +578,7 @@ pub async fn refund_gas_sources<'a, P: ContractProvider, E, B: BlockchainApplySt
         gas_refund_left = gas_refund_left.saturating_sub(refund_amount);
     }
 
-    Ok(())
+    Ok(attribution)
 }
 
 // Refund extra gas injections when the max gas was increased
@@ -688,4 +765,37 @@ pub async fn refund_deposits<'a, P: ContractProvider, E, B: BlockchainApplyState
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        config::COIN_VALUE,
+        crypto::Hashable,
+        transaction::tests::{create_tx_for, Account},
+    };
+    use super::*;
+
+    #[test]
+    fn test_contract_caller_display() {
+        let mut alice = Account::new();
+        let bob = Account::new();
+        alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+        let tx = create_tx_for(alice, bob.address(), 50, None);
+        let tx_hash = tx.hash();
+        let a = Hash::new([1u8; 32]);
+        let b = Hash::new([2u8; 32]);
+
+        assert_eq!(ContractCaller::Transaction(&tx_hash, &tx).to_string(), format!("entry(tx={})", tx_hash));
+        assert_eq!(
+            ContractCaller::Scheduled(Cow::Borrowed(&a), Cow::Borrowed(&b)).to_string(),
+            format!("scheduled(id={},contract={})", a, b)
+        );
+        assert_eq!(
+            ContractCaller::EventCallback(Cow::Borrowed(&a), Cow::Borrowed(&b)).to_string(),
+            format!("event_callback(caller={},source={})", a, b)
+        );
+        assert_eq!(ContractCaller::System.to_string(), "system");
+    }
+}
@@ -18,7 +18,7 @@ use core::fmt;
 use log::debug;
 use schemars::JsonSchema;
 use serde::de::Error as SerdeError;
-use anyhow::Error;
+use anyhow::{Context, Error};
 
 pub const NORMAL_ADDRESS_LEN: usize = 63;
 
@@ -65,6 +65,17 @@ impl Address {
         self.key
     }
 
+    // Get the base key of the address, without any integrated data
+    pub fn base_key(&self) -> PublicKey {
+        self.key.clone()
+    }
+
+    // Check if both addresses are for the same recipient on the same network
+    // Integrated data is ignored
+    pub fn same_recipient(&self, other: &Address) -> bool {
+        self.is_mainnet() == other.is_mainnet() && self.key == other.key
+    }
+
     // Get the address type
     pub fn get_type(&self) -> &AddressType {
         &self.addr_type
@@ -104,6 +115,18 @@ impl Address {
         }
     }
 
+    // Get the payment ID (integrated data) of the address if present
+    #[inline]
+    pub fn extract_payment_id(&self) -> Option<&DataElement> {
+        self.get_extra_data()
+    }
+
+    // Drop the payment ID (integrated data) to get the base address
+    #[inline]
+    pub fn strip_payment_id(self) -> Address {
+        self.extract_data().1
+    }
+
     // Check if the address is a normal address (no data integrated)
     pub fn is_normal(&self) -> bool {
         match self.addr_type {
@@ -181,6 +204,40 @@ impl Address {
 
         Ok(addr)
     }
+
+    // Parse an address from a string and verify it's for the expected network
+    pub fn from_string_for_network(address: &str, mainnet: bool) -> Result<Self, Error> {
+        let addr = Self::from_string(address)?;
+        if addr.is_mainnet() != mainnet {
+            let (got, expected) = if mainnet {
+                (TESTNET_PREFIX_ADDRESS, PREFIX_ADDRESS)
+            } else {
+                (PREFIX_ADDRESS, TESTNET_PREFIX_ADDRESS)
+            };
+            return Err(Bech32Error::InvalidPrefix(got.to_owned(), expected.to_owned()).into())
+        }
+
+        Ok(addr)
+    }
+
+    // Parse a batch of addresses for the expected network
+    // Each entry is parsed independently and keeps its own error
+    pub fn from_strings(addresses: &[&str], mainnet: bool) -> Vec<Result<Self, Error>> {
+        addresses.iter()
+            .map(|address| Self::from_string_for_network(address, mainnet))
+            .collect()
+    }
+
+    // Parse a batch of addresses for the expected network
+    // Returns an error on the first invalid entry
+    pub fn try_from_strings(addresses: &[&str], mainnet: bool) -> Result<Vec<Self>, Error> {
+        addresses.iter()
+            .enumerate()
+            .map(|(i, address)| Self::from_string_for_network(address, mainnet)
+                .with_context(|| format!("Invalid address at index {}", i))
+            )
+            .collect()
+    }
 }
 
 impl Serializer for Address {
@@ -286,9 +343,12 @@ impl Display for Address {
 
 #[cfg(test)]
 mod tests {
-    use crate::crypto::{KeyPair, NORMAL_ADDRESS_LEN};
+    use crate::{
+        api::{DataElement, DataValue},
+        crypto::{KeyPair, NORMAL_ADDRESS_LEN}
+    };
 
-    use super::{Address, AddressType};
+    use super::{Address, AddressType, Bech32Error};
 
     #[test]
     fn test_serde() {
@@ -307,4 +367,78 @@ mod tests {
         let str = addr.to_string();
         assert_eq!(str.len(), NORMAL_ADDRESS_LEN);
     }
+
+    #[test]
+    fn test_same_recipient() {
+        let (pub_key, _) = KeyPair::new().split();
+        let key = pub_key.compress();
+        let addr = Address::new(false, AddressType::Normal, key.clone());
+        let integrated = Address::new(false, AddressType::Data(DataElement::Value(DataValue::U64(42))), key.clone());
+
+        assert_ne!(addr, integrated);
+        assert!(addr.same_recipient(&integrated));
+        assert!(integrated.same_recipient(&addr));
+        assert_eq!(integrated.base_key(), key);
+
+        let (other_key, _) = KeyPair::new().split();
+        let other = Address::new(false, AddressType::Normal, other_key.compress());
+        assert!(!addr.same_recipient(&other));
+
+        // Same key on another network
+        let mainnet = Address::new(true, AddressType::Normal, key);
+        assert!(!addr.same_recipient(&mainnet));
+        assert!(!mainnet.same_recipient(&integrated));
+    }
+
+    #[test]
+    fn test_payment_id() {
+        let key = KeyPair::new().get_public_key().compress();
+        let base = Address::new(true, AddressType::Normal, key.clone());
+        assert!(base.extract_payment_id().is_none());
+        assert_eq!(base.clone().strip_payment_id(), base);
+
+        let payment_id = DataElement::Value(DataValue::U64(1337));
+        let integrated = Address::new(true, AddressType::Data(payment_id.clone()), key);
+        assert_eq!(integrated.extract_payment_id(), Some(&payment_id));
+
+        // Still available after a string round-trip
+        let parsed = Address::from_string(&integrated.to_string()).unwrap();
+        assert_eq!(parsed.extract_payment_id(), Some(&payment_id));
+
+        let stripped = parsed.strip_payment_id();
+        assert_eq!(stripped, base);
+        assert!(stripped.is_normal());
+        assert_eq!(stripped.to_string(), base.to_string());
+    }
+
+    #[test]
+    fn test_batch_parsing() {
+        let normal = Address::new(true, AddressType::Normal, KeyPair::new().get_public_key().compress());
+        let integrated = Address::new(true, AddressType::Data(DataElement::Value(DataValue::U64(42))), KeyPair::new().get_public_key().compress());
+        let testnet = Address::new(false, AddressType::Normal, KeyPair::new().get_public_key().compress());
+
+        let normal_str = normal.to_string();
+        let integrated_str = integrated.to_string();
+        let testnet_str = testnet.to_string();
+        let addresses = [normal_str.as_str(), "xel:invalid", integrated_str.as_str(), testnet_str.as_str()];
+
+        let results = Address::from_strings(&addresses, true);
+        assert_eq!(results.len(), addresses.len());
+        assert_eq!(results[0].as_ref().unwrap(), &normal);
+        assert!(results[1].is_err());
+        // Integrated data is kept
+        assert_eq!(results[2].as_ref().unwrap(), &integrated);
+        assert_eq!(results[2].as_ref().unwrap().to_string(), integrated_str);
+        // Testnet address in a mainnet batch
+        let err = results[3].as_ref().unwrap_err();
+        assert!(matches!(err.downcast_ref::<Bech32Error>(), Some(Bech32Error::InvalidPrefix(_, _))));
+
+        // Stops on the first invalid entry
+        let err = Address::try_from_strings(&addresses, true).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid address at index 1");
+
+        let valid = Address::try_from_strings(&[normal_str.as_str(), integrated_str.as_str()], true).unwrap();
+        assert_eq!(valid, vec![normal, integrated]);
+        assert!(Address::try_from_strings(&[], true).unwrap().is_empty());
+    }
 }
\ No newline at end of file
@@ -1,5 +1,6 @@
 use curve25519_dalek::{ristretto::CompressedRistretto, traits::Identity, Scalar};
 use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
 use thiserror::Error;
 use schemars::*;
 use crate::{
@@ -159,6 +160,19 @@ impl CompressedPublicKey {
     pub fn as_point(&self) -> &CompressedRistretto {
         &self.0
     }
+
+    /// Compare two keys in constant time
+    /// Must be used instead of `==` for sensitive comparisons
+    pub fn ct_eq(&self, other: &CompressedPublicKey) -> Choice {
+        self.as_bytes().ct_eq(other.as_bytes())
+    }
+
+    /// Check in constant time if the key is part of the given keys
+    /// Every key is compared, even once a match is found
+    pub fn ct_contained_in<'a, I: IntoIterator<Item = &'a CompressedPublicKey>>(&self, keys: I) -> Choice {
+        keys.into_iter()
+            .fold(Choice::from(0), |found, key| found | self.ct_eq(key))
+    }
 }
 
 impl Serializer for CompressedRistretto {
@@ -259,6 +273,7 @@ impl Serializer for Scalar {
 mod tests {
     use super::*;
     use serde_json::json;
+    use crate::crypto::KeyPair;
 
     #[test]
     fn test_compressed_ciphertext_zero() {
@@ -277,4 +292,21 @@ mod tests {
         let deserialized: Ciphertext = serde_json::from_value(json).unwrap();
         assert_eq!(ciphertext, deserialized);
     }
+
+    #[test]
+    fn test_public_key_ct_eq() {
+        let key1 = KeyPair::new().get_public_key().compress();
+        let key2 = key1.clone();
+        let key3 = KeyPair::new().get_public_key().compress();
+
+        assert_eq!(bool::from(key1.ct_eq(&key2)), key1 == key2);
+        assert!(bool::from(key1.ct_eq(&key2)));
+
+        assert_eq!(bool::from(key1.ct_eq(&key3)), key1 == key3);
+        assert!(!bool::from(key1.ct_eq(&key3)));
+
+        assert!(bool::from(key1.ct_contained_in([&key3, &key2])));
+        assert!(!bool::from(key1.ct_contained_in([&key3])));
+        assert!(!bool::from(key1.ct_contained_in([])));
+    }
 }
\ No newline at end of file
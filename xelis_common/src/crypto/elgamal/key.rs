@@ -11,7 +11,9 @@ use zeroize::Zeroize;
 use crate::{
     api::DataElement,
     config::MAXIMUM_SUPPLY,
+    network::Network,
     crypto::{
+        bech32::{CHARSET, SEPARATOR},
         proofs::H,
         Address,
         AddressType,
@@ -215,6 +217,29 @@ impl KeyPair {
     pub fn split(self) -> (PublicKey, PrivateKey) {
         (self.public_key, self.private_key)
     }
+
+    // Generate key pairs until its address starts with the prefix after the HRP
+    // Returns None if the prefix isn't in the bech32 charset
+    // or if no key pair matched in the max attempts
+    pub fn find_vanity(prefix: &str, network: Network, max_attempts: u64) -> Option<(KeyPair, Address)> {
+        if !prefix.chars().all(|c| CHARSET.contains(c)) {
+            return None
+        }
+
+        let mainnet = network.is_mainnet();
+        for _ in 0..max_attempts {
+            let keypair = KeyPair::new();
+            let address = keypair.get_public_key().to_address(mainnet);
+
+            let encoded = address.as_string().ok()?;
+            let (_, body) = encoded.split_once(SEPARATOR)?;
+            if body.starts_with(prefix) {
+                return Some((keypair, address))
+            }
+        }
+
+        None
+    }
 }
 
 impl Serializer for PrivateKey {
@@ -262,6 +287,27 @@ mod tests {
         assert!(signature.verify(message, public_key));
     }
 
+    #[test]
+    fn test_find_vanity() {
+        let (keypair, address) = KeyPair::find_vanity("x", Network::Mainnet, 100_000)
+            .expect("vanity address");
+
+        assert!(address.is_mainnet());
+        assert_eq!(address.get_public_key(), &keypair.get_public_key().compress());
+
+        let encoded = address.as_string().unwrap();
+        let (_, body) = encoded.split_once(SEPARATOR).unwrap();
+        assert!(body.starts_with('x'));
+    }
+
+    #[test]
+    fn test_find_vanity_invalid_charset() {
+        // 'b', 'i', 'o' and '1' are excluded from bech32
+        for prefix in ["b", "i", "o", "1", "X"] {
+            assert!(KeyPair::find_vanity(prefix, Network::Mainnet, 100_000).is_none());
+        }
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let keypair = KeyPair::new();
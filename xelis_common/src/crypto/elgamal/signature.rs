@@ -1,4 +1,11 @@
-use curve25519_dalek::{ristretto::CompressedRistretto, RistrettoPoint, Scalar};
+use std::iter;
+use curve25519_dalek::{
+    ristretto::CompressedRistretto,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+    RistrettoPoint,
+    Scalar
+};
+use rand::rngs::OsRng;
 use schemars::JsonSchema;
 use serde::{de::Error, Serialize};
 use sha3::{Digest, Sha3_512};
@@ -29,8 +36,9 @@ impl Signature {
     }
 
     // Verify the signature using the Public Key and the hash of the message
+    // All the inputs are public, so it is computed in variable time
     pub fn verify_internal(&self, message: &[u8], key: &RistrettoPoint, key_compressed: &CompressedRistretto) -> bool {
-        let r = (*H) * &self.s + key * -self.e;
+        let r = RistrettoPoint::vartime_multiscalar_mul([self.s, -self.e], [*H, *key]);
         let calculated = hash_and_point_to_scalar(&key_compressed, message, &r);
         self.e == calculated
     }
@@ -40,6 +48,42 @@ impl Signature {
         let key_compressed = key.as_point().compress();
         self.verify_internal(message, &key.as_point(), &key_compressed)
     }
+
+    // Verify several signatures, each with its own key and message
+    // The nonce commitment R = s * H - e * P of each signature is recovered
+    // to check its challenge, then all the equations are weighted by a random
+    // factor and checked together in a single multiscalar multiplication
+    pub fn batch_verify(items: &[(&PublicKey, &[u8], &Signature)]) -> bool {
+        let mut h_scalar = Scalar::ZERO;
+        let mut scalars = Vec::with_capacity(items.len() * 2);
+        let mut points = Vec::with_capacity(items.len() * 2);
+
+        for (key, message, signature) in items {
+            let point = key.as_point();
+            let r = RistrettoPoint::vartime_multiscalar_mul([signature.s, -signature.e], [*H, *point]);
+            if signature.e != hash_and_point_to_scalar(&point.compress(), message, &r) {
+                return false;
+            }
+
+            let factor = Scalar::random(&mut OsRng);
+            h_scalar += factor * signature.s;
+            scalars.push(-(factor * signature.e));
+            points.push(*point);
+            scalars.push(-factor);
+            points.push(r);
+        }
+
+        RistrettoPoint::vartime_multiscalar_mul(
+            scalars.iter().chain(iter::once(&h_scalar)),
+            points.iter().cloned().chain(iter::once(*H))
+        ).is_identity()
+    }
+
+    // Fallback of a failed batch: verify each signature on its own
+    // Returns the index of the first invalid signature
+    pub fn find_invalid(items: &[(&PublicKey, &[u8], &Signature)]) -> Option<usize> {
+        items.iter().position(|(key, message, signature)| !signature.verify(message, key))
+    }
 }
 
 // Create a Scalar from Public Key, Hash of the message, and selected point
@@ -87,4 +131,42 @@ impl Serializer for Signature {
     fn size(&self) -> usize {
         SIGNATURE_SIZE
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::KeyPair;
+    use super::*;
+
+    #[test]
+    fn test_batch_verify() {
+        let keypairs = [KeyPair::new(), KeyPair::new(), KeyPair::new()];
+        let messages = [b"first".to_vec(), b"second".to_vec(), b"third".to_vec()];
+        let signatures = keypairs.iter()
+            .zip(&messages)
+            .map(|(keypair, message)| keypair.sign(message))
+            .collect::<Vec<_>>();
+
+        let items = keypairs.iter()
+            .zip(&messages)
+            .zip(&signatures)
+            .map(|((keypair, message), signature)| (keypair.get_public_key(), message.as_slice(), signature))
+            .collect::<Vec<_>>();
+
+        assert!(Signature::batch_verify(&items));
+        assert!(Signature::batch_verify(&[]));
+        assert_eq!(Signature::find_invalid(&items), None);
+
+        // Tamper the second message
+        let mut tampered = items.clone();
+        tampered[1].1 = b"tampered";
+        assert!(!Signature::batch_verify(&tampered));
+        assert_eq!(Signature::find_invalid(&tampered), Some(1));
+
+        // Signature verified against the wrong key
+        let mut swapped = items.clone();
+        swapped[2].0 = keypairs[0].get_public_key();
+        assert!(!Signature::batch_verify(&swapped));
+        assert_eq!(Signature::find_invalid(&swapped), Some(2));
+    }
+}
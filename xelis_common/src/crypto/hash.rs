@@ -10,6 +10,7 @@ use schemars::JsonSchema;
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Serialize};
 use blake3::hash as blake3_hash;
+use subtle::{Choice, ConstantTimeEq};
 
 pub use xelis_hash::Error as XelisHashError;
 use xelis_hash::{v1, v2, v3};
@@ -45,6 +46,24 @@ impl Hash {
     pub fn to_hex(&self) -> String {
         hex::encode(self.0)
     }
+
+    /// Parse a hex encoded hash with an optional `0x` / `0X` prefix
+    /// The decoded value must be exactly HASH_SIZE bytes
+    pub fn from_hex_prefixed(hex: &str) -> Result<Self, ReaderError> {
+        let hex = hex.strip_prefix("0x")
+            .or_else(|| hex.strip_prefix("0X"))
+            .unwrap_or(hex);
+
+        let bytes = hex::decode(hex).map_err(|_| ReaderError::InvalidHex)?;
+        let bytes: [u8; HASH_SIZE] = bytes.try_into().map_err(|_| ReaderError::InvalidSize)?;
+        Ok(Hash::new(bytes))
+    }
+
+    /// Compare two hashes in constant time
+    /// Must be used instead of `==` for secret-derived hashes
+    pub fn ct_eq(&self, other: &Hash) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
 }
 
 impl FromStr for Hash {
@@ -193,4 +212,46 @@ mod tests {
         assert!(hash1 < hash3);
         assert!(hash3 < hash2);
     }
+
+    #[test]
+    fn test_ct_eq() {
+        let hash1 = hash(b"hello");
+        let hash2 = hash(b"hello");
+        let hash3 = hash(b"world");
+
+        assert_eq!(bool::from(hash1.ct_eq(&hash2)), hash1 == hash2);
+        assert!(bool::from(hash1.ct_eq(&hash2)));
+
+        assert_eq!(bool::from(hash1.ct_eq(&hash3)), hash1 == hash3);
+        assert!(!bool::from(hash1.ct_eq(&hash3)));
+    }
+
+    #[test]
+    fn test_from_hex_prefixed() {
+        let expected = hash(b"hello");
+        let hex = expected.to_hex();
+
+        assert_eq!(Hash::from_hex_prefixed(&hex).unwrap(), expected);
+        assert_eq!(Hash::from_hex_prefixed(&format!("0x{}", hex)).unwrap(), expected);
+        assert_eq!(Hash::from_hex_prefixed(&format!("0X{}", hex)).unwrap(), expected);
+        assert_eq!(Hash::from_hex_prefixed(&format!("0x{}", hex.to_uppercase())).unwrap(), expected);
+
+        // Unprefixed parsing is unchanged
+        assert_eq!(Hash::from_hex(&hex).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_from_hex_prefixed_invalid() {
+        let hex = hash(b"hello").to_hex();
+
+        // Odd length
+        assert!(matches!(Hash::from_hex_prefixed(&format!("0x{}0", hex)), Err(ReaderError::InvalidHex)));
+        // Wrong bytes count
+        assert!(matches!(Hash::from_hex_prefixed(&format!("0x{}", &hex[2..])), Err(ReaderError::InvalidSize)));
+        assert!(matches!(Hash::from_hex_prefixed(&format!("{}00", hex)), Err(ReaderError::InvalidSize)));
+        // Only the prefix
+        assert!(matches!(Hash::from_hex_prefixed("0x"), Err(ReaderError::InvalidSize)));
+        // Prefix can't be repeated
+        assert!(matches!(Hash::from_hex_prefixed(&format!("0x0x{}", hex)), Err(ReaderError::InvalidHex)));
+    }
 }
\ No newline at end of file
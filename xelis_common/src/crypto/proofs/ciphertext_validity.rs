@@ -302,6 +302,19 @@ impl CiphertextValidityProof {
     }
 }
 
+impl CiphertextValidityProof {
+    // Y_2 is only present starting from V1
+    // No version means the latest one
+    fn has_y2(version: Option<TxVersion>) -> bool {
+        version.map_or(true, |version| version >= TxVersion::V1)
+    }
+
+    // Serialized size of the proof for the given tx version
+    pub fn size_for_version(version: Option<TxVersion>) -> usize {
+        RISTRETTO_COMPRESSED_SIZE * 2 + SCALAR_SIZE * 2 + if Self::has_y2(version) { RISTRETTO_COMPRESSED_SIZE } else { 0 }
+    }
+}
+
 #[allow(non_snake_case)]
 impl Serializer for CiphertextValidityProof {
     fn write(&self, writer: &mut Writer) {
@@ -315,9 +328,7 @@ impl Serializer for CiphertextValidityProof {
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let bit = reader.context()
-            .get_optional::<TxVersion>()
-            .map_or(true, |version| *version >= TxVersion::V1);
+        let bit = Self::has_y2(reader.context().get_optional::<TxVersion>().copied());
 
         let Y_0 = CompressedRistretto::read(reader)?;
         let Y_1 = CompressedRistretto::read(reader)?;
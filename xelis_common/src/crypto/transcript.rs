@@ -32,6 +32,7 @@ pub trait ProtocolTranscript {
     fn deposit_proof_domain_separator(&mut self);
     fn burn_proof_domain_separator(&mut self);
     fn multisig_proof_domain_separator(&mut self);
+    fn multisig_update_proof_domain_separator(&mut self);
     fn invoke_contract_proof_domain_separator(&mut self);
     fn deploy_contract_proof_domain_separator(&mut self);
     fn invoke_constructor_proof_domain_separator(&mut self);
@@ -109,6 +110,10 @@ impl ProtocolTranscript for Transcript {
         self.append_message(b"dom-sep", b"multisig-proof");
     }
 
+    fn multisig_update_proof_domain_separator(&mut self) {
+        self.append_message(b"dom-sep", b"multisig-update-proof");
+    }
+
     fn invoke_contract_proof_domain_separator(&mut self) {
         self.append_message(b"dom-sep", b"invoke-contract-proof");
     }
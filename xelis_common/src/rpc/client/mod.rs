@@ -25,6 +25,8 @@ const INVALID_REQUEST_CODE: i16 = -32600;
 const METHOD_NOT_FOUND_CODE: i16 = -32601;
 const INVALID_PARAMS_CODE: i16 = -32602;
 const INTERNAL_ERROR_CODE: i16 = -32603;
+const TIMEOUT_CODE: i16 = -32005;
+const RATE_LIMITED_CODE: i16 = -32006;
 
 pub type JsonRPCResult<T> = Result<T, JsonRPCError>;
 
@@ -86,4 +88,60 @@ pub enum JsonRPCError {
     Any(#[from] anyhow::Error),
     #[error("Error while sending message '{}': {}", _0, _1)]
     SendError(String, String)
+}
+
+impl JsonRPCError {
+    // Is the error transient, meaning the same request may succeed later
+    // Only the timeouts, rate limits and connection errors are
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ServerError { code, .. } => matches!(*code, TIMEOUT_CODE | RATE_LIMITED_CODE),
+            Self::TimedOut(_)
+            | Self::NoResponse(_, _)
+            | Self::ConnectionError(_)
+            | Self::SocketError(_) => true,
+            Self::HttpError(e) => e.is_timeout() || e.is_request(),
+            _ => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error(code: i16) -> JsonRPCError {
+        JsonRPCError::ServerError {
+            code,
+            message: "error".to_owned(),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        for error in [
+            server_error(TIMEOUT_CODE),
+            server_error(RATE_LIMITED_CODE),
+            JsonRPCError::TimedOut("get_info".to_owned()),
+            JsonRPCError::NoResponse("get_info".to_owned(), "channel closed".to_owned()),
+            JsonRPCError::ConnectionError("connection reset".to_owned()),
+        ] {
+            assert!(error.is_retryable(), "{} should be retryable", error);
+        }
+    }
+
+    #[test]
+    fn test_permanent_errors() {
+        for error in [
+            server_error(-32004),
+            server_error(-1),
+            JsonRPCError::InvalidParams,
+            JsonRPCError::MethodNotFound,
+            JsonRPCError::InternalError { message: "storage unavailable".to_owned(), data: None },
+            JsonRPCError::MissingResult,
+        ] {
+            assert!(!error.is_retryable(), "{} should not be retryable", error);
+        }
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::fmt::{Display, Formatter};
+use std::{borrow::Cow, fmt::{self, Display, Formatter}};
 
 #[cfg(feature = "rpc-server")]
 use actix_web::{ResponseError, HttpResponse};
@@ -6,7 +6,10 @@ use actix_web::{ResponseError, HttpResponse};
 use serde_json::{Value, Error as SerdeError, json};
 use thiserror::Error;
 use anyhow::Error as AnyError;
-use crate::rpc::{Id, JSON_RPC_VERSION};
+use crate::{
+    rpc::{Id, JSON_RPC_VERSION},
+    transaction::verify::VerificationError
+};
 
 #[derive(Error, Debug)]
 pub enum InternalRpcError {
@@ -44,17 +47,28 @@ pub enum InternalRpcError {
     EventAlreadySubscribed,
     #[error("batch limit exceeded")]
     BatchLimitExceeded,
+    #[error("Request body is too large")]
+    RequestTooLarge,
+    #[error("Request timed out")]
+    Timeout,
+    #[error("Too many requests, rate limited")]
+    RateLimited,
+    #[error("Transaction verification failed: {}", _1)]
+    TransactionVerification(i32, String),
 }
 
 impl InternalRpcError {
-    pub fn get_code(&self) -> i16 {
+    // Stable JSON-RPC error code of the variant
+    // These codes are part of the wire contract and must never change
+    pub fn code(&self) -> i32 {
         match self {
             // JSON RPC errors
             Self::ParseBodyError => -32700,
             Self::InvalidJSONRequest
             | Self::InvalidRequestStr(_)
             | Self::InvalidVersion
-            | Self::BatchLimitExceeded => -32600,
+            | Self::BatchLimitExceeded
+            | Self::RequestTooLarge => -32600,
             Self::MethodNotFound(_) => -32601,
             Self::InvalidJSONParams(_)
             | Self::InvalidParams(_)
@@ -67,13 +81,36 @@ impl InternalRpcError {
             Self::InvalidContext => -32001,
             Self::ClientNotFound => -32002,
             Self::AnyError(_) => -32004,
+            Self::Timeout => -32005,
+            Self::RateLimited => -32006,
+            // Code of the verification error
+            Self::TransactionVerification(code, _) => *code,
             // Events invalid requests
             Self::EventNotSubscribed => -1,
             Self::EventAlreadySubscribed => -2,
         }
     }
+
+    // Is the error transient, meaning the same request may succeed later
+    // Only the timeouts and rate limits are, any other error is permanent
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Timeout | Self::RateLimited)
+    }
 }
 
+// Keep the stable code of the verification error
+// so the clients can react on the failure reason
+impl<T: fmt::Display> From<VerificationError<T>> for InternalRpcError {
+    fn from(error: VerificationError<T>) -> Self {
+        Self::TransactionVerification(error.code(), error.to_string())
+    }
+}
+
+// Error returned when registering a method name already in use
+#[derive(Error, Debug)]
+#[error("RPC method '{}' is already registered", _0)]
+pub struct DuplicateMethodError(pub Cow<'static, str>);
+
 #[derive(Debug)]
 pub struct RpcResponseError {
     id: Option<Id>,
@@ -88,6 +125,11 @@ impl RpcResponseError {
         }
     }
 
+    // Can the request be sent again
+    pub fn is_retryable(&self) -> bool {
+        self.error.is_retryable()
+    }
+
     pub fn get_id(&self) -> Value {
         match &self.id {
             Some(id) => json!(id),
@@ -100,7 +142,7 @@ impl RpcResponseError {
             "jsonrpc": JSON_RPC_VERSION,
             "id": self.get_id(),
             "error": {
-                "code": self.error.get_code(),
+                "code": self.error.code(),
                 "message": format!("{:#}", self.error)
             }
         })
@@ -120,3 +162,94 @@ impl ResponseError for RpcResponseError {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_errors() {
+        for error in [
+            InternalRpcError::Timeout,
+            InternalRpcError::RateLimited,
+        ] {
+            assert!(RpcResponseError::new(None, error).is_retryable());
+        }
+    }
+
+    #[test]
+    fn test_permanent_errors() {
+        for error in [
+            InternalRpcError::InternalError("storage unavailable"),
+            InternalRpcError::ParseBodyError,
+            InternalRpcError::InvalidParams("invalid address"),
+            InternalRpcError::ExpectedParams,
+            InternalRpcError::MethodNotFound("unknown".to_owned()),
+            InternalRpcError::ClientNotFound,
+            InternalRpcError::EventAlreadySubscribed,
+            InternalRpcError::BatchLimitExceeded,
+            InternalRpcError::RequestTooLarge,
+            InternalRpcError::AnyError(anyhow::anyhow!("block not found")),
+        ] {
+            assert!(!RpcResponseError::new(None, error).is_retryable());
+        }
+    }
+
+    #[test]
+    fn test_retryable_error_codes() {
+        assert_eq!(InternalRpcError::Timeout.code(), -32005);
+        assert_eq!(InternalRpcError::RateLimited.code(), -32006);
+    }
+
+    #[test]
+    fn test_stable_error_codes() {
+        let params_error = serde_json::from_str::<u64>("invalid").unwrap_err();
+        let expected = [
+            (InternalRpcError::InternalError("storage unavailable"), -32603),
+            (InternalRpcError::InvalidContext, -32001),
+            (InternalRpcError::ParseBodyError, -32700),
+            (InternalRpcError::InvalidJSONRequest, -32600),
+            (InternalRpcError::InvalidRequestStr("invalid"), -32600),
+            (InternalRpcError::InvalidJSONParams(params_error), -32602),
+            (InternalRpcError::InvalidParams("invalid address"), -32602),
+            (InternalRpcError::InvalidParamsAny(anyhow::anyhow!("invalid")), -32602),
+            (InternalRpcError::ExpectedParams, -32602),
+            (InternalRpcError::UnexpectedParams, -32602),
+            (InternalRpcError::InvalidVersion, -32600),
+            (InternalRpcError::MethodNotFound("unknown".to_owned()), -32601),
+            (InternalRpcError::AnyError(anyhow::anyhow!("block not found")), -32004),
+            (InternalRpcError::ClientNotFound, -32002),
+            (InternalRpcError::EventNotSubscribed, -1),
+            (InternalRpcError::EventAlreadySubscribed, -2),
+            (InternalRpcError::BatchLimitExceeded, -32600),
+            (InternalRpcError::RequestTooLarge, -32600),
+            (InternalRpcError::Timeout, -32005),
+            (InternalRpcError::RateLimited, -32006),
+        ];
+
+        for (error, code) in expected {
+            assert_eq!(error.code(), code, "{:?}", error);
+            assert_eq!(RpcResponseError::new(None, error).to_json()["error"]["code"], json!(code));
+        }
+    }
+
+    #[test]
+    fn test_verification_error_mapping() {
+        let expected = [
+            (VerificationError::State("storage unavailable".to_owned()), -33000, "State error: storage unavailable"),
+            (VerificationError::InvalidSignature, -33003, "Invalid signature"),
+            (VerificationError::TransactionTooLarge(2048, 1024), -33008, "Transaction size 2048 is above the limit of 1024"),
+            (VerificationError::InvalidFormat, -33016, "Invalid format"),
+            (VerificationError::InsufficientFunds, -33024, "Insufficient funds"),
+        ];
+
+        for (error, code, message) in expected {
+            let error = InternalRpcError::from(error);
+            assert!(matches!(error, InternalRpcError::TransactionVerification(c, _) if c == code));
+
+            let json = RpcResponseError::new(None, error).to_json();
+            assert_eq!(json["error"]["code"], json!(code));
+            assert_eq!(json["error"]["message"], json!(format!("Transaction verification failed: {}", message)));
+        }
+    }
+}
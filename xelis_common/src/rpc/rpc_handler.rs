@@ -1,10 +1,11 @@
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     future::Future,
     pin::Pin,
     sync::Arc,
 };
+use async_trait::async_trait;
 use cfg_if::cfg_if;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Map, Value};
@@ -16,6 +17,7 @@ use crate::{
     async_handler,
     time::Instant,
     rpc::{
+        DuplicateMethodError,
         InternalRpcError,
         RpcRequest,
         RpcResponseError,
@@ -34,6 +36,8 @@ cfg_if! {
         pub type HandlerParams<P, R> = for<'a> fn(&'a Context, P) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + 'a>>;
 
         pub type HandlerNoParams<R> = for<'a> fn(&'a Context) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + 'a>>;
+
+        pub type BoxedRateLimiter = Box<dyn RateLimiter>;
     } else {
         pub type Handler = Box<
             dyn for<'a> Fn(&'a Context, Value) -> Pin<Box<dyn Future<Output = Result<Value, InternalRpcError>> + Send + 'a>>
@@ -43,14 +47,27 @@ cfg_if! {
         pub type HandlerParams<P, R> = for<'a> fn(&'a Context, P) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + Send + 'a>>;
 
         pub type HandlerNoParams<R> = for<'a> fn(&'a Context) -> Pin<Box<dyn Future<Output = Result<R, InternalRpcError>> + Send + 'a>>;
+
+        pub type BoxedRateLimiter = Box<dyn RateLimiter + Send + Sync>;
     }
 }
 
+// Hook called before dispatching a method to its handler
+// Returning an error rejects the call, usually with InternalRpcError::RateLimited
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait RateLimiter {
+    async fn check(&self, method: &str, context: &Context<'_, '_>) -> Result<(), InternalRpcError>;
+}
+
 // Information about an RPC method
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RpcMethodInfo<'a> {
     pub name: Cow<'a, str>,
     pub schema: Cow<'a, RpcSchema>,
+    // Sample params value to call the method with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example: Option<Cow<'a, Value>>,
 }
 
 // Schema information about an RPC method
@@ -60,17 +77,183 @@ pub struct RpcSchema {
     pub returns_schema: Schema,
 }
 
+// Params for the built-in "list_methods" method
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ListMethodsParams {
+    // Only list the methods starting with this prefix
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
 /// An RPC method handler with its schema
 pub struct MethodHandler {
     pub handler: Handler,
-    pub schema: RpcSchema
+    pub schema: RpcSchema,
+    // Sample params value for tooling
+    pub example: Option<Value>
+}
+
+impl MethodHandler {
+    // Build a method handler with parameters
+    pub fn with_params<P, R>(f: HandlerParams<P, R>) -> Self
+    where
+        P: JsonSchema + DeserializeOwned + Send + 'static,
+        R: JsonSchema + Serialize + Send + 'static,
+    {
+        let f = Arc::new(f);
+
+        let handler: Handler = Box::new(move |ctx, body| {
+            let f = Arc::clone(&f);
+            Box::pin(async move {
+                let params: P = parse_params(body)?;
+                let res = f(ctx, params).await?;
+                Ok(json!(res))
+            })
+        });
+
+        Self {
+            handler,
+            schema: RpcSchema {
+                params_schema: Some(schema_for!(P)),
+                returns_schema: schema_for!(R),
+            },
+            example: None
+        }
+    }
+
+    // Build a method handler with parameters with a return schema given
+    pub fn with_params_and_return_schema<P, R>(f: HandlerParams<P, Value>) -> Self
+    where
+        P: JsonSchema + DeserializeOwned + Send + 'static,
+        R: JsonSchema + Serialize + Send + 'static,
+    {
+        let f = Arc::new(f);
+
+        let handler: Handler = Box::new(move |ctx, body| {
+            let f = Arc::clone(&f);
+            Box::pin(async move {
+                let params: P = parse_params(body)?;
+                f(ctx, params).await
+            })
+        });
+
+        Self {
+            handler,
+            schema: RpcSchema {
+                params_schema: Some(schema_for!(P)),
+                returns_schema: schema_for!(R),
+            },
+            example: None
+        }
+    }
+
+    // Build a method handler with no parameters
+    pub fn no_params<R>(f: HandlerNoParams<R>) -> Self
+    where
+        R: JsonSchema + Serialize + Send + 'static
+    {
+        let f = Arc::new(f);
+
+        let handler: Handler = Box::new(move |ctx, body| {
+            let f = Arc::clone(&f);
+            Box::pin(async move {
+                require_no_params(body)?;
+                let res = f(ctx).await?;
+                Ok(json!(res))
+            })
+        });
+
+        Self {
+            handler,
+            schema: RpcSchema {
+                params_schema: None,
+                returns_schema: schema_for!(R),
+            },
+            example: None
+        }
+    }
+
+    // Build a method handler with no parameters with a return schema given
+    pub fn no_params_custom_return<R>(f: HandlerNoParams<Value>) -> Self
+    where
+        R: JsonSchema + Serialize + Send + 'static
+    {
+        let f = Arc::new(f);
+
+        let handler: Handler = Box::new(move |ctx, body| {
+            let f = Arc::clone(&f);
+            Box::pin(async move {
+                require_no_params(body)?;
+                f(ctx).await
+            })
+        });
+
+        Self {
+            handler,
+            schema: RpcSchema {
+                params_schema: None,
+                returns_schema: schema_for!(R),
+            },
+            example: None
+        }
+    }
+
+    // Attach a sample params value to the method
+    pub fn with_example(mut self, example: Value) -> Self {
+        self.example = Some(example);
+        self
+    }
+}
+
+// Collect RPC methods to register them at once
+#[derive(Default)]
+pub struct RpcMethodsBuilder {
+    methods: Vec<(Cow<'static, str>, MethodHandler)>
+}
+
+impl RpcMethodsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Add a method handler
+    pub fn method(mut self, name: impl Into<Cow<'static, str>>, handler: MethodHandler) -> Self {
+        self.methods.push((name.into(), handler));
+        self
+    }
+
+    // Add a method with parameters
+    pub fn with_params<P, R>(self, name: impl Into<Cow<'static, str>>, f: HandlerParams<P, R>) -> Self
+    where
+        P: JsonSchema + DeserializeOwned + Send + 'static,
+        R: JsonSchema + Serialize + Send + 'static,
+    {
+        self.method(name, MethodHandler::with_params(f))
+    }
+
+    // Add a method with no parameters
+    pub fn no_params<R>(self, name: impl Into<Cow<'static, str>>, f: HandlerNoParams<R>) -> Self
+    where
+        R: JsonSchema + Serialize + Send + 'static
+    {
+        self.method(name, MethodHandler::no_params(f))
+    }
+
+    // Register all the collected methods into the handler
+    pub fn apply<T: ShareableTid<'static>>(self, handler: &mut RPCHandler<T>) -> Result<(), DuplicateMethodError> {
+        handler.register_methods(self.methods)
+    }
 }
 
 pub struct RPCHandler<T: ShareableTid<'static>> {
     // all RPC methods registered
     methods: HashMap<Cow<'static, str>, MethodHandler>,
     data: T,
-    batch_limit: Option<usize>
+    batch_limit: Option<usize>,
+    // Maximum size in bytes of a request body, checked before parsing it
+    batch_byte_limit: Option<usize>,
+    // Optional limiter checked before each call
+    rate_limiter: Option<BoxedRateLimiter>
 }
 
 tid! { impl<'a, T: 'static> TidAble<'a> for RPCHandler<T> where T: ShareableTid<'static> }
@@ -84,15 +267,29 @@ where
         let mut handler = Self {
             methods: HashMap::new(),
             data,
-            batch_limit: batch_limit.into()
+            batch_limit: batch_limit.into(),
+            batch_byte_limit: None,
+            rate_limiter: None
         };
 
         // Internally register the "schema" method to get all registered methods
         handler.register_method_no_params_custom_return::<Vec<RpcMethodInfo>>("schema", async_handler!(schema::<T>, single));
+        // Same as "schema" but filtered by an optional prefix and sorted by name
+        handler.register_method_with_params_and_return_schema::<ListMethodsParams, Vec<RpcMethodInfo>>("list_methods", async_handler!(list_methods::<T>));
 
         handler
     }
 
+    // Set the maximum size in bytes of a request body
+    pub fn set_batch_byte_limit(&mut self, limit: impl Into<Option<usize>>) {
+        self.batch_byte_limit = limit.into();
+    }
+
+    // Set the limiter checked before executing any method
+    pub fn set_rate_limiter(&mut self, limiter: BoxedRateLimiter) {
+        self.rate_limiter = Some(limiter);
+    }
+
     // Create a new context with a reference to the RPC handler
     pub fn create_context<'ty, 'r>(&'r self) -> Context<'ty, 'r> {
         let mut context = Context::new();
@@ -108,6 +305,10 @@ where
 
     // Handle an RPC request from raw bytes with a given context
     pub async fn handle_request_with_context<'ty, 'r>(&self, mut context: Context<'ty, 'r>, body: &[u8]) -> Result<Option<Value>, RpcResponseError> {
+        if self.batch_byte_limit.is_some_and(|v| body.len() > v) {
+            return Err(RpcResponseError::new(None, InternalRpcError::RequestTooLarge))
+        }
+
         let request: Value = serde_json::from_slice(body)
             .map_err(|_| RpcResponseError::new(None, InternalRpcError::ParseBodyError))?;
 
@@ -118,15 +319,23 @@ where
                     return Err(RpcResponseError::new(None, InternalRpcError::BatchLimitExceeded))
                 }
 
-                let mut responses = Vec::with_capacity(requests.len());
+                let is_empty = requests.is_empty();
+                let mut only_notifications = true;
+                let mut responses = Vec::new();
                 for value in requests {
                     let request = parse_request(value)?;
+                    only_notifications &= request.id.is_none();
                     if let Some(response) = self.execute_method(&mut context, request).await {
                         responses.push(response);
                     }
                 }
 
-                Some(Value::Array(responses))
+                // A batch of notifications only expects no response at all
+                if only_notifications && !is_empty {
+                    None
+                } else {
+                    Some(Value::Array(responses))
+                }
             },
             _ => return Err(RpcResponseError::new(None, InternalRpcError::InvalidJSONRequest))
         })
@@ -136,6 +345,26 @@ where
         self.methods.contains_key(method_name)
     }
 
+    // Check if at least one registered method starts with the prefix
+    pub fn has_method_with_prefix(&self, prefix: &str) -> bool {
+        self.methods.keys().any(|name| name.starts_with(prefix))
+    }
+
+    // List the registered methods starting with the prefix, sorted by name
+    pub fn list_methods<'a>(&'a self, prefix: Option<&str>) -> Vec<RpcMethodInfo<'a>> {
+        let mut methods = self.methods.iter()
+            .filter(|(name, _)| prefix.map_or(true, |prefix| name.starts_with(prefix)))
+            .map(|(name, handler)| RpcMethodInfo {
+                name: Cow::Borrowed(name),
+                schema: Cow::Borrowed(&handler.schema),
+                example: handler.example.as_ref().map(Cow::Borrowed)
+            })
+            .collect::<Vec<_>>();
+
+        methods.sort_by(|a, b| a.name.cmp(&b.name));
+        methods
+    }
+
     // Execute an RPC method from a request
     // Returns None if there is no response expected (notification)
     pub async fn execute_method<'a, 'ty, 'r>(&'a self, context: &'a mut Context<'ty, 'r>, request: RpcRequest) -> Option<Value> {
@@ -157,6 +386,11 @@ where
         trace!("executing '{}' RPC method", request.method);
         counter!("xelis_rpc_calls", "method" => request.method.clone()).increment(1);
 
+        if let Some(limiter) = self.rate_limiter.as_ref() {
+            limiter.check(&request.method, context).await
+                .map_err(|err| RpcResponseError::new(request.id.clone(), err))?;
+        }
+
         let params = request.params.take().unwrap_or(Value::Null);
 
         // insert the request id into the context
@@ -183,6 +417,26 @@ where
         assert!(v.is_none(), "RPC method '{}' is already registered", name);
     }
 
+    // Register many RPC methods at once
+    // Nothing is registered if a name is already in use or duplicated in the batch
+    pub fn register_methods(&mut self, methods: impl IntoIterator<Item = (Cow<'static, str>, MethodHandler)>) -> Result<(), DuplicateMethodError> {
+        let methods = methods.into_iter().collect::<Vec<_>>();
+
+        let mut names = HashSet::with_capacity(methods.len());
+        for (name, _) in methods.iter() {
+            if self.methods.contains_key(name) || !names.insert(name) {
+                return Err(DuplicateMethodError(name.clone()))
+            }
+        }
+
+        for (name, handler) in methods {
+            trace!("Registering RPC method: {}", name);
+            self.methods.insert(name, handler);
+        }
+
+        Ok(())
+    }
+
     // Register a method with parameters
     pub fn register_method_with_params<P, R>(
         &mut self,
@@ -193,24 +447,21 @@ where
         P: JsonSchema + DeserializeOwned + Send + 'static,
         R: JsonSchema + Serialize + Send + 'static,
     {
-        let f = Arc::new(f);
-
-        let handler: Handler = Box::new(move |ctx, body| {
-            let f = Arc::clone(&f);
-            Box::pin(async move {
-                let params: P = parse_params(body)?;
-                let res = f(ctx, params).await?;
-                Ok(json!(res))
-            })
-        });
+        self.register_method(name, MethodHandler::with_params(f));
+    }
 
-        self.register_method(name, MethodHandler {
-            handler,
-            schema: RpcSchema {
-                params_schema: Some(schema_for!(P)),
-                returns_schema: schema_for!(R),
-            }
-        });
+    // Register a method with parameters and a sample params value
+    pub fn register_method_with_params_and_example<P, R>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        f: HandlerParams<P, R>,
+        example: Value,
+    )
+    where
+        P: JsonSchema + DeserializeOwned + Send + 'static,
+        R: JsonSchema + Serialize + Send + 'static,
+    {
+        self.register_method(name, MethodHandler::with_params(f).with_example(example));
     }
 
     // Register a method with parameters with a return schema given
@@ -223,23 +474,7 @@ where
         P: JsonSchema + DeserializeOwned + Send + 'static,
         R: JsonSchema + Serialize + Send + 'static,
     {
-        let f = Arc::new(f);
-
-        let handler: Handler = Box::new(move |ctx, body| {
-            let f = Arc::clone(&f);
-            Box::pin(async move {
-                let params: P = parse_params(body)?;
-                f(ctx, params).await
-            })
-        });
-
-        self.register_method(name, MethodHandler {
-            handler,
-            schema: RpcSchema {
-                params_schema: Some(schema_for!(P)),
-                returns_schema: schema_for!(R),
-            }
-        });
+        self.register_method(name, MethodHandler::with_params_and_return_schema::<P, R>(f));
     }
 
     // Register a method with no parameters
@@ -251,24 +486,7 @@ where
     where
         R: JsonSchema + Serialize + Send + 'static
     {
-        let f = Arc::new(f);
-
-        let handler: Handler = Box::new(move |ctx, body| {
-            let f = Arc::clone(&f);
-            Box::pin(async move {
-                require_no_params(body)?;
-                let res = f(ctx).await?;
-                Ok(json!(res))
-            })
-        });
-
-        self.register_method(name, MethodHandler {
-            handler,
-            schema: RpcSchema {
-                params_schema: None,
-                returns_schema: schema_for!(R),
-            }
-        });
+        self.register_method(name, MethodHandler::no_params(f));
     }
 
     // Register a method with no parameters
@@ -280,23 +498,7 @@ where
     where
         R: JsonSchema + Serialize + Send + 'static
     {
-        let f = Arc::new(f);
-
-        let handler: Handler = Box::new(move |ctx, body| {
-            let f = Arc::clone(&f);
-            Box::pin(async move {
-                require_no_params(body)?;
-                f(ctx).await
-            })
-        });
-
-        self.register_method(name, MethodHandler {
-            handler,
-            schema: RpcSchema {
-                params_schema: None,
-                returns_schema: schema_for!(R),
-            }
-        });
+        self.register_method(name, MethodHandler::no_params_custom_return::<R>(f));
     }
 
     // Get a reference to the data associated with the RPC handler
@@ -314,12 +516,21 @@ async fn schema<'a, T: ShareableTid<'static>>(context: &'a Context<'_, '_>) -> R
     let methods = rpc_handler.methods.iter()
         .map(|(name, handler)| RpcMethodInfo {
             name: Cow::Borrowed(name),
-            schema: Cow::Borrowed(&handler.schema)
+            schema: Cow::Borrowed(&handler.schema),
+            example: handler.example.as_ref().map(Cow::Borrowed)
         }).collect::<Vec<_>>();
 
     Ok(json!(methods))
 }
 
+// Built-in "list_methods" method to get the registered methods matching a prefix
+async fn list_methods<'a, T: ShareableTid<'static>>(context: &'a Context<'_, '_>, params: ListMethodsParams) -> Result<Value, InternalRpcError> {
+    let rpc_handler: &RPCHandler<T> = context.get()
+        .ok_or(InternalRpcError::InternalError("RPCHandler not found in context"))?;
+
+    Ok(json!(rpc_handler.list_methods(params.prefix.as_deref())))
+}
+
 // Parse an RPC request from raw bytes
 pub fn parse_request_from_bytes(body: &[u8]) -> Result<RpcRequest, RpcResponseError> {
     let request: RpcRequest = serde_json::from_slice(body)
@@ -364,4 +575,200 @@ pub fn require_no_params(value: Value) -> Result<(), InternalRpcError> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Data;
+
+    tid! { impl<'a> TidAble<'a> for Data }
+
+    async fn dummy<'a>(_: &'a Context<'_, '_>) -> Result<bool, InternalRpcError> {
+        Ok(true)
+    }
+
+    #[tokio::test]
+    async fn test_batch_notifications() {
+        let mut handler = RPCHandler::new(Data, None);
+        handler.register_method_no_params("get_info", async_handler!(dummy, single));
+
+        let notification = json!({ "jsonrpc": JSON_RPC_VERSION, "method": "get_info" });
+        let request = |id: u64| json!({ "jsonrpc": JSON_RPC_VERSION, "id": id, "method": "get_info" });
+
+        let batch = json!([notification, notification, notification]);
+        let response = handler.handle_request(batch.to_string().as_bytes()).await.unwrap();
+        assert!(response.is_none());
+
+        // Only the requests with an id get a response
+        let batch = json!([notification, request(1), notification, request(2)]);
+        let response = handler.handle_request(batch.to_string().as_bytes()).await
+            .unwrap()
+            .unwrap();
+        let ids = response.as_array()
+            .unwrap()
+            .iter()
+            .map(|response| response["id"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![json!(1), json!(2)]);
+    }
+
+    struct StubLimiter;
+
+    #[async_trait]
+    impl RateLimiter for StubLimiter {
+        async fn check(&self, method: &str, _: &Context<'_, '_>) -> Result<(), InternalRpcError> {
+            if method == "get_account_history" {
+                return Err(InternalRpcError::RateLimited)
+            }
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter() {
+        let mut handler = RPCHandler::new(Data, None);
+        for name in ["get_account_history", "get_info"] {
+            handler.register_method_no_params(name, async_handler!(dummy, single));
+        }
+        handler.set_rate_limiter(Box::new(StubLimiter));
+
+        let call = |method: &str| json!({
+            "jsonrpc": JSON_RPC_VERSION,
+            "id": 1,
+            "method": method
+        }).to_string();
+
+        let response = handler.handle_request(call("get_account_history").as_bytes()).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["error"]["code"], json!(InternalRpcError::RateLimited.code()));
+
+        let response = handler.handle_request(call("get_info").as_bytes()).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["result"], json!(true));
+    }
+
+    #[test]
+    fn test_register_methods_duplicate() {
+        let mut handler = RPCHandler::new(Data, None);
+        RpcMethodsBuilder::new()
+            .no_params("get_info", async_handler!(dummy, single))
+            .no_params("get_height", async_handler!(dummy, single))
+            .apply(&mut handler)
+            .unwrap();
+
+        assert!(handler.has_method("get_info"));
+        assert!(handler.has_method("get_height"));
+
+        // Already registered
+        let err = RpcMethodsBuilder::new()
+            .no_params("get_version", async_handler!(dummy, single))
+            .no_params("get_info", async_handler!(dummy, single))
+            .apply(&mut handler)
+            .unwrap_err();
+        assert_eq!(err.0, "get_info");
+        // Nothing from the batch was registered
+        assert!(!handler.has_method("get_version"));
+
+        // Duplicated inside the batch
+        let methods = ["get_peers", "get_peers"].into_iter()
+            .map(|name| (Cow::Borrowed(name), MethodHandler::no_params(async_handler!(dummy, single))));
+        let err = handler.register_methods(methods).unwrap_err();
+        assert_eq!(err.0, "get_peers");
+        assert!(!handler.has_method("get_peers"));
+    }
+
+    #[tokio::test]
+    async fn test_list_methods_by_prefix() {
+        let mut handler = RPCHandler::new(Data, None);
+        for name in ["wallet.get_balance", "get_info", "wallet.get_address", "node.get_height"] {
+            handler.register_method_no_params(name, async_handler!(dummy, single));
+        }
+
+        let names = |methods: Vec<RpcMethodInfo>| methods.into_iter()
+            .map(|method| method.name.into_owned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names(handler.list_methods(Some("wallet."))), vec!["wallet.get_address", "wallet.get_balance"]);
+        assert_eq!(names(handler.list_methods(Some("unknown."))), Vec::<String>::new());
+        // Built-in methods are listed too
+        assert_eq!(
+            names(handler.list_methods(None)),
+            vec!["get_info", "list_methods", "node.get_height", "schema", "wallet.get_address", "wallet.get_balance"]
+        );
+
+        // Through the RPC method
+        let request = json!({
+            "jsonrpc": JSON_RPC_VERSION,
+            "id": 1,
+            "method": "list_methods",
+            "params": { "prefix": "node." }
+        });
+        let response = handler.handle_request(request.to_string().as_bytes()).await
+            .unwrap()
+            .unwrap();
+        let methods: Vec<RpcMethodInfo> = serde_json::from_value(response["result"].clone()).unwrap();
+        assert_eq!(names(methods), vec!["node.get_height"]);
+    }
+
+    async fn echo<'a>(_: &'a Context<'_, '_>, params: ListMethodsParams) -> Result<Option<String>, InternalRpcError> {
+        Ok(params.prefix)
+    }
+
+    #[tokio::test]
+    async fn test_schema_example() {
+        let mut handler = RPCHandler::new(Data, None);
+        let example = json!({ "prefix": "wallet." });
+        handler.register_method_with_params_and_example("echo", async_handler!(echo), example.clone());
+        handler.register_method_no_params("get_info", async_handler!(dummy, single));
+
+        let request = json!({ "jsonrpc": JSON_RPC_VERSION, "id": 1, "method": "schema" });
+        let response = handler.handle_request(request.to_string().as_bytes()).await
+            .unwrap()
+            .unwrap();
+        let methods: Vec<RpcMethodInfo> = serde_json::from_value(response["result"].clone()).unwrap();
+
+        let echo = methods.iter().find(|method| method.name == "echo").unwrap();
+        assert_eq!(echo.example.as_deref(), Some(&example));
+        let get_info = methods.iter().find(|method| method.name == "get_info").unwrap();
+        assert!(get_info.example.is_none());
+
+        // The example is valid params for the method
+        let request = json!({ "jsonrpc": JSON_RPC_VERSION, "id": 2, "method": "echo", "params": echo.example });
+        let response = handler.handle_request(request.to_string().as_bytes()).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["result"], json!("wallet."));
+    }
+
+    #[tokio::test]
+    async fn test_batch_byte_limit() {
+        let mut handler = RPCHandler::new(Data, 2);
+        handler.register_method_no_params("get_info", async_handler!(dummy, single));
+        handler.set_batch_byte_limit(128);
+
+        // Not even a valid JSON, rejected before being parsed
+        let body = vec![b'x'; 129];
+        let err = handler.handle_request(&body).await.unwrap_err();
+        assert_eq!(err.to_json()["error"]["code"], json!(InternalRpcError::RequestTooLarge.code()));
+        assert_eq!(err.to_json()["error"]["message"], json!(InternalRpcError::RequestTooLarge.to_string()));
+
+        let request = json!({ "jsonrpc": JSON_RPC_VERSION, "id": 1, "method": "get_info" }).to_string();
+        assert!(request.len() <= 128);
+        let response = handler.handle_request(request.as_bytes()).await
+            .unwrap()
+            .unwrap();
+        assert_eq!(response["result"], json!(true));
+
+        // Count based limit still applies under the byte limit
+        let notification = json!({ "jsonrpc": JSON_RPC_VERSION, "method": "get_info" });
+        let batch = json!([notification, notification, notification]).to_string();
+        assert!(batch.len() <= 128);
+        let err = handler.handle_request(batch.as_bytes()).await.unwrap_err();
+        assert_eq!(err.to_json()["error"]["message"], json!(InternalRpcError::BatchLimitExceeded.to_string()));
+    }
+}
@@ -206,6 +206,13 @@ pub trait WebSocketHandler: Sized + Sync + Send {
         Ok(None)
     }
 
+    // called once the WebSocket connection is established
+    // and before any message is delivered to `on_message`
+    // if an error is returned, the session is closed
+    async fn on_connect(&self, _: &WebSocketSessionShared<Self>) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
     // called when a new message is received
     async fn on_message(&self, _: &WebSocketSessionShared<Self>, _: &[u8]) -> Result<(), anyhow::Error> {
         Ok(())
@@ -356,6 +363,13 @@ impl<H> WebSocketServer<H> where H: WebSocketHandler + 'static + Send + Sync {
     // This will send a ping every 5 seconds and close the connection if no pong is received within 30 seconds
     // It will also translate all messages to the handler
     async fn handle_ws_internal(self: Arc<Self>, session: WebSocketSessionShared<H>, mut stream: AggregatedMessageStream, mut rx: UnboundedReceiver<InnerMessage>) {
+        // call on_connect before polling any message from the stream
+        if let Err(e) = self.handler.on_connect(&session).await {
+            debug!("Error while calling on_connect for session #{}: {}", session.id, e);
+            self.delete_session(&session, Some(CloseReason::from(CloseCode::Policy))).await;
+            return;
+        }
+
         let mut interval = actix_rt::time::interval(KEEP_ALIVE_INTERVAL);
         let mut last_pong_received = Instant::now();
         // executor for handling messages
@@ -466,4 +480,71 @@ impl<H> WebSocketServer<H> where H: WebSocketHandler + 'static + Send + Sync {
         self.delete_session(&session, reason).await;
         debug!("Session #{} has been closed", session.id);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{pin::Pin, sync::Mutex as StdMutex};
+    use actix_web::{
+        dev,
+        error::PayloadError,
+        http::header,
+        test::TestRequest,
+        web::Bytes,
+        FromRequest
+    };
+    use futures_util::{stream, Stream};
+    use crate::tokio::{sync::Notify, time::{self, sleep}};
+    use super::*;
+
+    #[derive(Default)]
+    struct OrderHandler {
+        events: StdMutex<Vec<&'static str>>,
+        notify: Notify
+    }
+
+    #[async_trait]
+    impl WebSocketHandler for OrderHandler {
+        async fn on_connect(&self, _: &WebSocketSessionShared<Self>) -> Result<(), anyhow::Error> {
+            // The client message is already available in the stream
+            sleep(Duration::from_secs(5)).await;
+            self.events.lock().unwrap().push("connect");
+            Ok(())
+        }
+
+        async fn on_message(&self, _: &WebSocketSessionShared<Self>, _: &[u8]) -> Result<(), anyhow::Error> {
+            self.events.lock().unwrap().push("message");
+            self.notify.notify_one();
+            Ok(())
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_on_connect_before_on_message() {
+        // The sleep in on_connect is skipped as soon as the runtime is idle
+        time::pause();
+
+        let server = WebSocketServer::new(OrderHandler::default());
+        let request = TestRequest::get()
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_http_request();
+
+        // Masked "hello" text frame sent right after the handshake
+        // The stream is kept open like a real connection
+        let frame = Bytes::from_static(&[0x81, 0x85, 0, 0, 0, 0, b'h', b'e', b'l', b'l', b'o']);
+        let stream: Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>> = Box::pin(
+            stream::once(async move { Ok(frame) }).chain(stream::pending())
+        );
+        let mut payload = dev::Payload::from(stream);
+        let body = Payload::from_request(&request, &mut payload).await.unwrap();
+
+        let _response = server.handle_connection(request, body).await.unwrap();
+        server.get_handler().notify.notified().await;
+
+        let events = server.get_handler().events.lock().unwrap().clone();
+        assert_eq!(events, vec!["connect", "message"]);
+    }
+}
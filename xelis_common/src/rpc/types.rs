@@ -6,7 +6,7 @@ use super::tid;
 
 pub const JSON_RPC_VERSION: &str = "2.0";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Id {
     String(String),
@@ -0,0 +1,198 @@
+use super::*;
+
+// Max bits that can be packed in a single byte
+pub const MAX_BITS: u8 = 8;
+
+// BitWriter packs up to 8 flags into a single byte
+// Bits are written starting from the least significant bit
+// The byte is only written once `flush` is called
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BitWriter {
+    // current packed byte
+    byte: u8,
+    // number of bits already used
+    len: u8,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Write a single flag
+    // Panics if more than 8 bits are written
+    pub fn write_flag(&mut self, value: bool) {
+        self.write_bits(value as u8, 1);
+    }
+
+    // Write the `count` lowest bits of `value`
+    // Panics if more than 8 bits are written
+    pub fn write_bits(&mut self, value: u8, count: u8) {
+        assert!(count > 0 && self.len + count <= MAX_BITS, "BitWriter can only pack up to {} bits", MAX_BITS);
+
+        let mask = ((1u16 << count) - 1) as u8;
+        debug_assert!(value & !mask == 0, "value doesn't fit in {} bits", count);
+
+        self.byte |= (value & mask) << self.len;
+        self.len += count;
+    }
+
+    // Number of bits already written
+    pub fn count(&self) -> u8 {
+        self.len
+    }
+
+    // Packed byte
+    pub fn bits(&self) -> u8 {
+        self.byte
+    }
+
+    // Write the packed byte into the writer
+    pub fn flush(self, writer: &mut Writer) {
+        writer.write_u8(self.byte);
+    }
+}
+
+// BitReader unpacks up to 8 flags from a single byte
+// written by a `BitWriter`
+#[derive(Debug, Clone, Copy)]
+pub struct BitReader {
+    // packed byte
+    byte: u8,
+    // number of bits already read
+    len: u8,
+}
+
+impl BitReader {
+    // Read the packed byte from the reader
+    pub fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        reader.read_u8().map(Self::new)
+    }
+
+    pub fn new(byte: u8) -> Self {
+        Self {
+            byte,
+            len: 0,
+        }
+    }
+
+    // Read a single flag
+    pub fn read_flag(&mut self) -> Result<bool, ReaderError> {
+        self.read_bits(1).map(|v| v == 1)
+    }
+
+    // Read the next `count` bits
+    pub fn read_bits(&mut self, count: u8) -> Result<u8, ReaderError> {
+        if count == 0 || self.len + count > MAX_BITS {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mask = ((1u16 << count) - 1) as u8;
+        let value = (self.byte >> self.len) & mask;
+        self.len += count;
+
+        Ok(value)
+    }
+
+    // Verify that no unread bit is set
+    // This ensures a canonical encoding of the packed byte
+    pub fn finish(self) -> Result<(), ReaderError> {
+        if self.len < MAX_BITS && self.byte >> self.len != 0 {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(flags: &[bool]) {
+        let mut writer = BitWriter::new();
+        for flag in flags {
+            writer.write_flag(*flag);
+        }
+
+        let mut buffer = Vec::new();
+        writer.flush(&mut Writer::new(&mut buffer));
+        assert_eq!(buffer.len(), 1);
+
+        let mut reader = Reader::new(&buffer);
+        let mut bits = BitReader::read(&mut reader).unwrap();
+        for flag in flags {
+            assert_eq!(bits.read_flag().unwrap(), *flag);
+        }
+
+        bits.finish().unwrap();
+        assert_eq!(reader.size(), 0);
+    }
+
+    #[test]
+    fn test_flags_roundtrip() {
+        roundtrip(&[]);
+        roundtrip(&[true]);
+        roundtrip(&[false]);
+        roundtrip(&[true, false, true]);
+        roundtrip(&[false, false, false, true]);
+        roundtrip(&[true; 8]);
+        roundtrip(&[false; 8]);
+        roundtrip(&[true, false, true, false, true, false, true, false]);
+    }
+
+    #[test]
+    fn test_all_combinations() {
+        for byte in 0..=u8::MAX {
+            let flags: Vec<bool> = (0..MAX_BITS).map(|i| byte & (1 << i) != 0).collect();
+            let mut writer = BitWriter::new();
+            for flag in flags.iter() {
+                writer.write_flag(*flag);
+            }
+            assert_eq!(writer.bits(), byte);
+
+            let mut reader = BitReader::new(byte);
+            for flag in flags {
+                assert_eq!(reader.read_flag().unwrap(), flag);
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_bits_roundtrip() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(3, 2);
+        writer.write_flag(true);
+        writer.write_bits(5, 3);
+        assert_eq!(writer.count(), 6);
+
+        let mut reader = BitReader::new(writer.bits());
+        assert_eq!(reader.read_bits(2).unwrap(), 3);
+        assert!(reader.read_flag().unwrap());
+        assert_eq!(reader.read_bits(3).unwrap(), 5);
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn test_read_overflow() {
+        let mut reader = BitReader::new(0);
+        assert!(reader.read_bits(8).is_ok());
+        assert!(reader.read_flag().is_err());
+    }
+
+    #[test]
+    fn test_non_canonical_rejected() {
+        let mut reader = BitReader::new(0b0000_0101);
+        assert!(reader.read_flag().unwrap());
+        assert!(reader.finish().is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_write_overflow() {
+        let mut writer = BitWriter::new();
+        for _ in 0..=MAX_BITS {
+            writer.write_flag(true);
+        }
+    }
+}
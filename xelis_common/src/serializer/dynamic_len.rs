@@ -9,6 +9,20 @@ use super::*;
 // - 0xFF is reserved for future use
 pub struct DynamicLen(pub usize);
 
+impl DynamicLen {
+    // Read a length and reject it if it exceeds the remaining bytes
+    // Every encoded element takes at least one byte, so a bigger
+    // length can only come from a truncated or malicious buffer
+    pub fn read_bounded(reader: &mut Reader) -> Result<usize, ReaderError> {
+        let len = Self::read(reader)?.0;
+        if len > reader.remaining_bytes() {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        Ok(len)
+    }
+}
+
 impl Serializer for DynamicLen {
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
         let tag = reader.read_u8()?;
@@ -68,4 +82,20 @@ mod tests {
             assert_eq!(dynamic_len.0, decoded.0);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_dynamic_len_read_bounded() {
+        let mut bytes = DynamicLen(u32::MAX as usize).to_bytes();
+        bytes.extend_from_slice(&[0u8; 16]);
+
+        let mut reader = Reader::new(&bytes);
+        assert!(matches!(DynamicLen::read_bounded(&mut reader), Err(ReaderError::InvalidSize)));
+
+        let mut bytes = DynamicLen(4).to_bytes();
+        bytes.extend_from_slice(&[0u8; 4]);
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(DynamicLen::read_bounded(&mut reader).unwrap(), 4);
+        assert_eq!(reader.remaining_bytes(), 4);
+    }
+}
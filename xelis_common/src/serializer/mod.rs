@@ -5,6 +5,7 @@ mod hexable;
 mod raw;
 mod count;
 mod dynamic_len;
+mod bits;
 
 use std::marker::Sized;
 
@@ -15,6 +16,7 @@ pub use hexable::*;
 pub use raw::*;
 pub use count::*;
 pub use dynamic_len::*;
+pub use bits::*;
 
 pub trait Serializer {
     fn write(&self, writer: &mut Writer);
@@ -17,7 +17,39 @@ pub enum ReaderError {
     #[error(transparent)]
     TryFromSliceError(#[from] TryFromSliceError),
     #[error(transparent)]
-    Any(#[from] anyhow::Error)
+    Any(#[from] anyhow::Error),
+    #[error("{kind} at position {pos}")]
+    AtPosition {
+        pos: usize,
+        kind: Box<ReaderError>
+    }
+}
+
+impl ReaderError {
+    // Attach the position in the bytes at which the error occurred
+    // If already set, the innermost (most precise) position is kept
+    pub fn at(self, pos: usize) -> Self {
+        match self {
+            Self::AtPosition { .. } => self,
+            kind => Self::AtPosition { pos, kind: Box::new(kind) }
+        }
+    }
+
+    // Position at which the error occurred, if known
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Self::AtPosition { pos, .. } => Some(*pos),
+            _ => None
+        }
+    }
+
+    // Underlying error without its position
+    pub fn kind(&self) -> &ReaderError {
+        match self {
+            Self::AtPosition { kind, .. } => kind,
+            _ => self
+        }
+    }
 }
 
 // Reader help us to read safely from bytes
@@ -203,9 +235,21 @@ impl<'a> Reader<'a> {
         self.bytes.len() - self.total
     }
 
+    // Bytes left to read in the buffer
+    // Used to bound the declared lengths before any allocation
+    pub fn remaining_bytes(&self) -> usize {
+        self.size()
+    }
+
     pub fn total_read(&self) -> usize {
         self.total
     }
+
+    // Current position in the bytes
+    // Used to report where a read has failed
+    pub fn position(&self) -> usize {
+        self.total
+    }
 }
 
 // Skip N bytes before reading the next value
@@ -96,6 +96,11 @@ impl<'a> Writer<'a> {
         self.bytes.len() - self.len
     }
 
+    // Current position relative to the start of this writer
+    pub fn position(&self) -> usize {
+        self.total_write()
+    }
+
     pub fn as_mut_bytes(&mut self) -> &mut Vec<u8> {
         &mut self.bytes
     }
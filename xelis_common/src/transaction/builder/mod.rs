@@ -20,10 +20,11 @@ use xelis_vm::Module;
 use std::{
     collections::HashSet,
     iter,
-    sync::Arc,
+    sync::{Arc, OnceLock},
 };
 use crate::{
-    config::{BURN_PER_CONTRACT, MAX_GAS_USAGE_PER_TX, XELIS_ASSET},
+    api::DataElement,
+    config::{BURN_PER_CONTRACT, MAX_CONTRACT_DEPENDENCIES, MAX_GAS_USAGE_PER_TX, XELIS_ASSET},
     crypto::{
         elgamal::{
             Ciphertext,
@@ -59,6 +60,7 @@ use super::{
         ExtraData,
         ExtraDataType,
         PlaintextData,
+        SelfMemo,
         UnknownExtraDataFormat
     },
     BurnPayload,
@@ -68,6 +70,7 @@ use super::{
     InvokeConstructorPayload,
     InvokeContractPayload,
     MultiSigPayload,
+    MultiSigUpdatePayload,
     Role,
     SourceCommitment,
     Transaction,
@@ -90,6 +93,14 @@ pub enum GenerationError<T> {
     InvalidConstructorInvoke,
     #[error("No contract key provided for private deposits")]
     MissingContractKey,
+    #[error("Private deposits are not supported yet")]
+    PrivateDepositsNotSupported,
+    #[error("MultiSig updates are not supported by this transaction version")]
+    MultiSigUpdateNotSupported,
+    #[error("Memo to self is not supported by this transaction version")]
+    SelfMemoNotSupported,
+    #[error("Memo to self is too large, we got {0} bytes, limit is {1} bytes")]
+    SelfMemoTooLarge(usize, usize),
     #[error("Empty transfers")]
     EmptyTransfers,
     #[error("Max transfer count reached")]
@@ -118,10 +129,34 @@ pub enum GenerationError<T> {
     DepositZero,
     #[error("Invalid module hexadecimal")]
     InvalidModule,
+    #[error("Invalid contract dependencies")]
+    InvalidContractDependencies,
+    #[error("Upgradeable contracts are not supported by this contract version")]
+    UpgradeableNotSupported,
+    #[error("Source hash is not supported by this contract version")]
+    SourceHashNotSupported,
+    #[error("Contract upgrades are not supported by this transaction version")]
+    ContractUpgradeNotSupported,
     #[error("Configured max gas is above the network limit")]
     MaxGasReached,
     #[error("Fee max is lower than calculated fee")]
     FeeMax,
+    #[error("Reference at topoheight {0} is older than the balances at topoheight {1}")]
+    InconsistentReference(u64, u64),
+    #[cfg(feature = "builder-assertions")]
+    #[error("Built transaction size mismatch, expected {0} bytes got {1} bytes")]
+    SizeMismatch(usize, usize),
+}
+
+// Report of a transaction validated without building its proofs
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DryRunReport {
+    // Estimated size of the final transaction in bytes
+    pub estimated_size: usize,
+    // Estimated fee to pay
+    pub fee: u64,
+    // Maximum fee that can be spent
+    pub fee_limit: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
@@ -133,6 +168,7 @@ pub enum TransactionTypeBuilder {
     MultiSig(MultiSigBuilder),
     InvokeContract(InvokeContractBuilder),
     DeployContract(DeployContractBuilder),
+    MultiSigUpdate(MultiSigUpdateBuilder),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -141,7 +177,37 @@ pub struct TransactionBuilder {
     source: CompressedPublicKey,
     required_thresholds: Option<u8>,
     data: TransactionTypeBuilder,
-    fee_builder: FeeBuilder
+    fee_builder: FeeBuilder,
+    // Memo only readable by the source key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    self_memo: Option<DataElement>,
+    // Cached result of `estimate_size`
+    // Reset when the data is mutated
+    #[serde(skip)]
+    estimated_size: OnceLock<usize>,
+}
+
+// Check that the built transaction matches the estimated size
+// and that its serialized form has the same length
+#[cfg(feature = "builder-assertions")]
+pub(crate) fn verify_built_size<E>(tx: &Transaction, estimated_size: usize) -> Result<(), GenerationError<E>> {
+    let size = tx.size();
+    if size != estimated_size {
+        return Err(GenerationError::SizeMismatch(estimated_size, size));
+    }
+
+    let len = tx.to_bytes().len();
+    if len != size {
+        return Err(GenerationError::SizeMismatch(size, len));
+    }
+
+    Ok(())
+}
+
+// Count the size computations to check the cache usage
+#[cfg(test)]
+thread_local! {
+    pub(crate) static SIZE_COMPUTATIONS: std::cell::Cell<usize> = std::cell::Cell::new(0);
 }
 
 // Internal struct for build
@@ -236,12 +302,28 @@ impl TransactionBuilder {
             required_thresholds,
             data,
             fee_builder,
+            self_memo: None,
+            estimated_size: OnceLock::new(),
         }
     }
 
+    // Attach a memo encrypted to the source key
+    pub fn with_self_memo(mut self, memo: DataElement) -> Self {
+        self.self_memo = Some(memo);
+        self.estimated_size.take();
+        self
+    }
+
     /// Estimate by hand the bytes size of a final TX
-    // Returns bytes size and transfers count
+    // The result is cached for this builder
     pub fn estimate_size(&self) -> usize {
+        *self.estimated_size.get_or_init(|| self.compute_size())
+    }
+
+    fn compute_size(&self) -> usize {
+        #[cfg(test)]
+        SIZE_COMPUTATIONS.with(|count| count.set(count.get() + 1));
+
         let assets_used = self.data.used_assets().len();
         // Version byte
         let mut size = 1
@@ -274,6 +356,14 @@ impl TransactionBuilder {
             size += 8;
         }
 
+        if self.version.has_self_memo() {
+            // 1 for optional self memo bool
+            size += 1;
+            if let Some(memo) = self.self_memo.as_ref() {
+                size += SelfMemo::estimate_size(memo);
+            }
+        }
+
         if let Some(threshold) = self.required_thresholds {
             // 1 for Multisig participants count byte
             size += 1 + (threshold as usize * (SIGNATURE_SIZE + 1))
@@ -321,6 +411,12 @@ impl TransactionBuilder {
                 // Payload size
                 size += payload.threshold.size() + 1 + (payload.participants.len() * RISTRETTO_COMPRESSED_SIZE);
             },
+            TransactionTypeBuilder::MultiSigUpdate(payload) => {
+                // 1 byte for each keys count
+                size += 1 + (payload.add.len() * RISTRETTO_COMPRESSED_SIZE)
+                    + 1 + (payload.remove.len() * RISTRETTO_COMPRESSED_SIZE)
+                    + payload.new_threshold.size();
+            },
             TransactionTypeBuilder::InvokeContract(payload) => {
                 let payload_size = payload.contract.size()
                 + payload.max_gas.size()
@@ -339,8 +435,33 @@ impl TransactionBuilder {
             TransactionTypeBuilder::DeployContract(payload) => {
                 // Module is in hex format, so we need to divide by 2 for its bytes size
                 // + 1 for the contract version
-                // + 1 for the invoke option
+                // + 1 for the invoke and upgrade flags
                 size += 1 + payload.module.len() / 2 + 1;
+                if ContractModule::has_dependencies_support(payload.contract_version) {
+                    // + 1 for the dependencies count
+                    size += 1 + payload.dependencies.len() * HASH_SIZE;
+                }
+
+                if ContractModule::has_upgrade_support(payload.contract_version) {
+                    // + 1 for the upgrade authority option
+                    size += 1;
+                    if payload.upgradeable {
+                        size += RISTRETTO_COMPRESSED_SIZE;
+                    }
+                }
+
+                if ContractModule::has_source_hash_support(payload.contract_version) {
+                    // + 1 for the source hash option
+                    size += 1;
+                    if payload.source_hash.is_some() {
+                        size += HASH_SIZE;
+                    }
+                }
+
+                if payload.upgrade.is_some() {
+                    size += HASH_SIZE;
+                }
+
                 if let Some(invoke) = payload.invoke.as_ref() {
                     let (commitments, deposits_size) = self.estimate_deposits_size(&invoke.deposits);
 
@@ -391,6 +512,10 @@ impl TransactionBuilder {
 
     // Estimate the fees for this TX
     pub fn estimate_fees<B: FeeHelper>(&self, state: &mut B) -> Result<u64, GenerationError<B::Error>> {
+        self.compute_fees(state)
+    }
+
+    fn compute_fees<B: FeeHelper>(&self, state: &B) -> Result<u64, GenerationError<B::Error>> {
         let calculated_fee = match self.fee_builder {
             // If the value is set, use it
             FeeBuilder::Fixed(value) => value,
@@ -471,7 +596,8 @@ impl TransactionBuilder {
                     ct -= Scalar::from(payload.amount)
                 }
             },
-            TransactionTypeBuilder::MultiSig(_) => {},
+            TransactionTypeBuilder::MultiSig(_)
+            | TransactionTypeBuilder::MultiSigUpdate(_) => {},
             TransactionTypeBuilder::InvokeContract(payload) => {
                 if let Some(deposit) = payload.deposits.get(asset) {
                     if deposit.private {
@@ -535,7 +661,8 @@ impl TransactionBuilder {
                     cost += payload.amount
                 }
             },
-            TransactionTypeBuilder::MultiSig(_) => {},
+            TransactionTypeBuilder::MultiSig(_)
+            | TransactionTypeBuilder::MultiSigUpdate(_) => {},
             TransactionTypeBuilder::InvokeContract(payload) => {
                 if let Some(deposit) = payload.deposits.get(asset) {
                     cost += deposit.amount;
@@ -566,6 +693,39 @@ impl TransactionBuilder {
         cost
     }
 
+    // Verify the multisig update delta
+    // The resulting setup can only be verified against the chain state
+    fn verify_multisig_update<E>(
+        version: TxVersion,
+        source: &CompressedPublicKey,
+        payload: &MultiSigUpdateBuilder,
+    ) -> Result<(), GenerationError<E>> {
+        if !version.has_multisig_update() {
+            return Err(GenerationError::MultiSigUpdateNotSupported);
+        }
+
+        if payload.add.is_empty() && payload.remove.is_empty() && payload.new_threshold.is_none() {
+            return Err(GenerationError::MultiSigParticipants);
+        }
+
+        if payload.add.len() > MAX_MULTISIG_PARTICIPANTS
+            || payload.remove.len() > MAX_MULTISIG_PARTICIPANTS
+            || payload.add.iter().any(|addr| payload.remove.contains(addr))
+        {
+            return Err(GenerationError::MultiSigParticipants);
+        }
+
+        if payload.new_threshold == Some(0) {
+            return Err(GenerationError::MultiSigThreshold);
+        }
+
+        if payload.add.iter().any(|addr| addr.get_public_key() == source) {
+            return Err(GenerationError::MultiSigSelfParticipant);
+        }
+
+        Ok(())
+    }
+
     // Build the deposits commitments for the contract
     fn build_deposits_commitments<E>(
         deposits: &IndexMap<Hash, ContractDepositBuilder>,
@@ -619,21 +779,12 @@ impl TransactionBuilder {
         let mut deposits = deposits_commitments
             .into_iter()
             .map(|(asset, deposit)| {
-                let commitment = deposit.commitment.compress();
-                let sender_handle = deposit.sender_handle.compress();
-                let receiver_handle = deposit.receiver_handle.compress();
-
-                transcript.deposit_proof_domain_separator();
-                transcript.append_hash(b"deposit_asset", &asset);
-                transcript.append_commitment(b"deposit_commitment", &commitment);
-                transcript.append_handle(b"deposit_sender_handle", &sender_handle);
-                transcript.append_handle(b"deposit_receiver_handle", &receiver_handle);
-
-                let ct_validity_proof = CiphertextValidityProof::new(
-                    contract_key.as_ref().expect("Contract key is required"),
-                    source_keypair.get_public_key(),
+                let private = ContractDeposit::new_private(
+                    &asset,
                     deposit.amount,
                     &deposit.amount_opening,
+                    source_keypair.get_public_key(),
+                    contract_key.as_ref().expect("Contract key is required"),
                     tx_version,
                     transcript,
                 );
@@ -641,10 +792,7 @@ impl TransactionBuilder {
                 range_proof_values.push(deposit.amount);
                 range_proof_openings.push(deposit.amount_opening.as_scalar());
 
-                (
-                    asset,
-                    ContractDeposit::Private { commitment, sender_handle, receiver_handle, ct_validity_proof }
-                )
+                (asset, private)
             }).collect::<IndexMap<_, _>>();
 
             // Now build the public ones
@@ -659,13 +807,183 @@ impl TransactionBuilder {
             Deposits(deposits)
     }
 
+    // Validate the transaction data against the protocol rules
+    // Both the build and the dry run rely on it to reject the same transactions
+    fn validate_data<B: AccountState>(&self, state: &B) -> Result<(), GenerationError<B::Error>> {
+        if self.self_memo.is_some() && !self.version.has_self_memo() {
+            return Err(GenerationError::SelfMemoNotSupported);
+        }
+
+        match &self.data {
+            TransactionTypeBuilder::Transfers(transfers) => {
+                if transfers.is_empty() {
+                    return Err(GenerationError::EmptyTransfers);
+                }
+
+                if transfers.len() > MAX_TRANSFER_COUNT {
+                    return Err(GenerationError::MaxTransferCountReached);
+                }
+
+                let mut extra_data_size = 0;
+                for transfer in transfers {
+                    if *transfer.destination.get_public_key() == self.source {
+                        return Err(GenerationError::SenderIsReceiver);
+                    }
+
+                    if state.is_mainnet() != transfer.destination.is_mainnet() {
+                        return Err(GenerationError::InvalidNetwork);
+                    }
+
+                    // Either extra data provided or an integrated address, not both
+                    if transfer.extra_data.is_some() && !transfer.destination.is_normal() {
+                        return Err(GenerationError::ExtraDataAndIntegratedAddress);
+                    }
+
+                    let extra_data = transfer.extra_data.as_ref()
+                        .or_else(|| transfer.destination.get_extra_data());
+
+                    if let Some(extra_data) = extra_data {
+                        let size = extra_data.size();
+                        if size > EXTRA_DATA_LIMIT_SIZE {
+                            return Err(GenerationError::ExtraDataTooLarge);
+                        }
+                        extra_data_size += size;
+                    }
+                }
+
+                if extra_data_size > EXTRA_DATA_LIMIT_SUM_SIZE {
+                    return Err(GenerationError::ExtraDataTooLarge);
+                }
+            },
+            TransactionTypeBuilder::Burn(payload) => {
+                if payload.amount == 0 {
+                    return Err(GenerationError::BurnZero);
+                }
+            },
+            TransactionTypeBuilder::MultiSig(payload) => {
+                if payload.participants.len() > MAX_MULTISIG_PARTICIPANTS {
+                    return Err(GenerationError::MultiSigParticipants);
+                }
+
+                if payload.threshold as usize > payload.participants.len() || (payload.threshold == 0 && !payload.participants.is_empty()) {
+                    return Err(GenerationError::MultiSigThreshold);
+                }
+
+                if payload.participants.iter().any(|addr| *addr.get_public_key() == self.source) {
+                    return Err(GenerationError::MultiSigSelfParticipant);
+                }
+            },
+            TransactionTypeBuilder::MultiSigUpdate(payload) => {
+                Self::verify_multisig_update(self.version, &self.source, payload)?;
+            },
+            TransactionTypeBuilder::InvokeContract(payload) => {
+                if payload.max_gas > MAX_GAS_USAGE_PER_TX {
+                    return Err(GenerationError::MaxGasReached);
+                }
+
+                // Private deposits are rejected by the verification
+                // until the contracts can spend them
+                if payload.deposits.values().any(|deposit| deposit.private) {
+                    return Err(GenerationError::PrivateDepositsNotSupported);
+                }
+            },
+            TransactionTypeBuilder::DeployContract(payload) => {
+                if payload.invoke.as_ref().is_some_and(|invoke| invoke.max_gas > MAX_GAS_USAGE_PER_TX) {
+                    return Err(GenerationError::MaxGasReached);
+                }
+
+                let module = Module::from_hex(&payload.module)
+                    .map_err(|_| GenerationError::InvalidModule)?;
+
+                if payload.invoke.is_none() != module.get_chunk_id_of_hook(0).is_none() {
+                    return Err(GenerationError::InvalidConstructorInvoke);
+                }
+
+                if !payload.dependencies.is_empty() {
+                    let supported = ContractModule::has_dependencies_support(payload.contract_version);
+                    if !supported || payload.dependencies.len() > MAX_CONTRACT_DEPENDENCIES {
+                        return Err(GenerationError::InvalidContractDependencies);
+                    }
+                }
+
+                if payload.upgradeable && !ContractModule::has_upgrade_support(payload.contract_version) {
+                    return Err(GenerationError::UpgradeableNotSupported);
+                }
+
+                if payload.source_hash.is_some() && !ContractModule::has_source_hash_support(payload.contract_version) {
+                    return Err(GenerationError::SourceHashNotSupported);
+                }
+
+                if payload.upgrade.is_some() {
+                    if !self.version.has_contract_upgrade() {
+                        return Err(GenerationError::ContractUpgradeNotSupported);
+                    }
+
+                    if payload.invoke.is_some() {
+                        return Err(GenerationError::InvalidConstructorInvoke);
+                    }
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    // Validate the transaction against the state without generating any proof
+    // This doesn't require the private key and doesn't update the state
+    // Returns the estimated size and fees of the transaction
+    pub fn dry_run<B: AccountState>(&self, state: &B) -> Result<DryRunReport, GenerationError<B::Error>> {
+        state.validate_reference_consistency()?;
+
+        let fee = self.compute_fees(state)?;
+        let fee_limit = state.get_max_fee(fee);
+        if fee > fee_limit {
+            return Err(GenerationError::FeeMax);
+        }
+
+        self.validate_data(state)?;
+
+        // Verify that we have enough funds for each asset used
+        for asset in self.data.used_assets() {
+            let cost = self.get_transaction_cost(fee_limit, asset);
+            let balance = state.get_account_balance(asset)
+                .map_err(GenerationError::State)?;
+
+            if cost > balance {
+                return Err(ProofGenerationError::InsufficientFunds {
+                    required: cost,
+                    available: balance,
+                }.into());
+            }
+        }
+
+        Ok(DryRunReport {
+            estimated_size: self.estimate_size(),
+            fee,
+            fee_limit,
+        })
+    }
+
     pub fn build<B: AccountState>(
         self,
         state: &mut B,
         source_keypair: &KeyPair,
     ) -> Result<Transaction, GenerationError<B::Error>> {
+        // Multisig signatures are added after the build
+        // so the estimated size can only be checked without them
+        #[cfg(feature = "builder-assertions")]
+        let estimated_size = self.required_thresholds.is_none()
+            .then(|| self.estimate_size());
+
         let unsigned = self.build_unsigned(state, source_keypair)?;
-        Ok(unsigned.finalize(source_keypair))
+        let tx = unsigned.finalize(source_keypair);
+
+        #[cfg(feature = "builder-assertions")]
+        if let Some(estimated_size) = estimated_size {
+            verify_built_size(&tx, estimated_size)?;
+        }
+
+        Ok(tx)
     }
 
     pub fn build_unsigned<B: AccountState>(
@@ -673,6 +991,9 @@ impl TransactionBuilder {
         state: &mut B,
         source_keypair: &KeyPair,
     ) -> Result<UnsignedTransaction, GenerationError<B::Error>> {
+        // Reject a stale reference before touching the state
+        state.validate_reference_consistency()?;
+
         // Compute the fees
         let fee = self.estimate_fees(state)?;
         // Use the configured max fee, otherwise fallback to estimated fee
@@ -681,6 +1002,22 @@ impl TransactionBuilder {
             return Err(GenerationError::FeeMax);
         }
 
+        self.validate_data(state)?;
+
+        // Encrypt the memo to self before consuming the nonce
+        let self_memo = match self.self_memo.as_ref() {
+            Some(memo) => {
+                let memo = SelfMemo::new(memo, source_keypair.get_public_key());
+                let size = memo.size();
+                if size > EXTRA_DATA_LIMIT_SIZE {
+                    return Err(GenerationError::SelfMemoTooLarge(size, EXTRA_DATA_LIMIT_SIZE));
+                }
+
+                Some(memo)
+            },
+            None => None
+        };
+
         // Get the nonce
         let nonce = state.get_nonce().map_err(GenerationError::State)?;
         state.update_nonce(nonce + 1).map_err(GenerationError::State)?;
@@ -688,49 +1025,17 @@ impl TransactionBuilder {
         // 0.a Create the commitments
 
         // Data is mutable only to extract extra data
+        // so the estimated size is not valid anymore
+        self.estimated_size.take();
         let mut transfers_commitments = Vec::new();
         let mut deposits_commitments = IndexMap::new();
         match &mut self.data {
             TransactionTypeBuilder::Transfers(transfers) => {
-                if transfers.len() == 0 {
-                    return Err(GenerationError::EmptyTransfers);
-                }
-
-                if transfers.len() > MAX_TRANSFER_COUNT {
-                    return Err(GenerationError::MaxTransferCountReached);
-                }
-    
-                let mut extra_data_size = 0;
                 for transfer in transfers.iter_mut() {
-                    if *transfer.destination.get_public_key() == self.source {
-                        return Err(GenerationError::SenderIsReceiver);
-                    }
-    
-                    if state.is_mainnet() != transfer.destination.is_mainnet() {
-                        return Err(GenerationError::InvalidNetwork);
-                    }
-    
-                    // Either extra data provided or an integrated address, not both
-                    if transfer.extra_data.is_some() && !transfer.destination.is_normal() {
-                        return Err(GenerationError::ExtraDataAndIntegratedAddress);
-                    }
-    
                     // Set the integrated data as extra data
                     if let Some(extra_data) = transfer.destination.extract_data_only() {
                         transfer.extra_data = Some(extra_data);
                     }
-    
-                    if let Some(extra_data) = &transfer.extra_data {
-                        let size = extra_data.size();
-                        if size > EXTRA_DATA_LIMIT_SIZE {
-                            return Err(GenerationError::ExtraDataTooLarge);
-                        }
-                        extra_data_size += size;
-                    }
-                }
-    
-                if extra_data_size > EXTRA_DATA_LIMIT_SUM_SIZE {
-                    return Err(GenerationError::ExtraDataTooLarge);
                 }
     
                 transfers_commitments = transfers
@@ -761,22 +1066,16 @@ impl TransactionBuilder {
                     .collect::<Result<Vec<_>, GenerationError<B::Error>>>()?;
             },
             TransactionTypeBuilder::InvokeContract(payload) => {
-                if payload.max_gas > MAX_GAS_USAGE_PER_TX {
-                    return Err(GenerationError::MaxGasReached.into())
-                }
-
+                // Private deposits are encrypted for the contract key
+                let contract_key = Some(PublicKey::from_hash(&payload.contract));
                 deposits_commitments = Self::build_deposits_commitments::<B::Error>(
                     &payload.deposits,
                     source_keypair.get_public_key(),
-                    &None
+                    &contract_key
                 )?;
             },
             TransactionTypeBuilder::DeployContract(payload) => {
                 if let Some(invoke) = payload.invoke.as_ref() {
-                    if invoke.max_gas > MAX_GAS_USAGE_PER_TX {
-                        return Err(GenerationError::MaxGasReached.into())
-                    }
-
                     deposits_commitments = Self::build_deposits_commitments::<B::Error>(
                         &invoke.deposits,
                         source_keypair.get_public_key(),
@@ -952,6 +1251,7 @@ impl TransactionBuilder {
                 }
             },
             TransactionTypeBuilder::InvokeContract(payload) => {
+                let contract_key = Some(PublicKey::from_hash(&payload.contract));
                 deposits = Self::finalize_deposits_commitments(
                     &mut transcript,
                     &mut range_proof_values,
@@ -960,7 +1260,7 @@ impl TransactionBuilder {
                     deposits_commitments,
                     source_keypair,
                     self.version,
-                    &None,
+                    &contract_key,
                 );
             },
             TransactionTypeBuilder::DeployContract(payload) => {
@@ -994,12 +1294,6 @@ impl TransactionBuilder {
         let data = match self.data {
             TransactionTypeBuilder::Transfers(_) => TransactionType::Transfers(transfers),
             TransactionTypeBuilder::Burn(payload) => {
-                // Check if the burn amount is zero
-                // Burn of zero are useless and consume fees for nothing
-                if payload.amount == 0 {
-                    return Err(GenerationError::BurnZero);
-                }
-
                 if self.version >= TxVersion::V1 {
                     transcript.burn_proof_domain_separator();
                     transcript.append_hash(b"burn_asset", &payload.asset);
@@ -1009,14 +1303,6 @@ impl TransactionBuilder {
                 TransactionType::Burn(payload)
             },
             TransactionTypeBuilder::MultiSig(payload) => {
-                if payload.participants.len() > MAX_MULTISIG_PARTICIPANTS {
-                    return Err(GenerationError::MultiSigParticipants);
-                }
-
-                if payload.threshold as usize > payload.participants.len() || (payload.threshold == 0 && !payload.participants.is_empty()) {
-                    return Err(GenerationError::MultiSigThreshold);
-                }
-
                 transcript.multisig_proof_domain_separator();
                 transcript.append_u64(b"multisig_threshold", payload.threshold as u64);
 
@@ -1027,16 +1313,37 @@ impl TransactionBuilder {
                     keys.insert(key);
                 }
 
-                // You can't contains yourself in the participants
-                if keys.contains(&self.source) {
-                    return Err(GenerationError::MultiSigSelfParticipant);
-                }
-
                 TransactionType::MultiSig(MultiSigPayload {
                     participants: keys,
                     threshold: payload.threshold,
                 })
             },
+            TransactionTypeBuilder::MultiSigUpdate(payload) => {
+                transcript.multisig_update_proof_domain_separator();
+                let add: IndexSet<_> = payload.add.into_iter()
+                    .map(|addr| addr.to_public_key())
+                    .collect();
+                for key in &add {
+                    transcript.append_public_key(b"multisig_add", key);
+                }
+
+                let remove: IndexSet<_> = payload.remove.into_iter()
+                    .map(|addr| addr.to_public_key())
+                    .collect();
+                for key in &remove {
+                    transcript.append_public_key(b"multisig_remove", key);
+                }
+
+                if let Some(threshold) = payload.new_threshold {
+                    transcript.append_u64(b"multisig_threshold", threshold as u64);
+                }
+
+                TransactionType::MultiSigUpdate(MultiSigUpdatePayload {
+                    add,
+                    remove,
+                    new_threshold: payload.new_threshold,
+                })
+            },
             TransactionTypeBuilder::InvokeContract(payload) => {
                 transcript.invoke_contract_proof_domain_separator();
                 transcript.append_hash(b"contract_hash", &payload.contract);
@@ -1058,18 +1365,20 @@ impl TransactionBuilder {
             TransactionTypeBuilder::DeployContract(payload) => {
                 transcript.deploy_contract_proof_domain_separator();
 
+                // Module was already validated
                 let module = Module::from_hex(&payload.module)
                     .map_err(|_| GenerationError::InvalidModule)?;
 
-                if payload.invoke.is_none() != module.get_chunk_id_of_hook(0).is_none() {
-                    return Err(GenerationError::InvalidConstructorInvoke);
-                }
-
                 TransactionType::DeployContract(DeployContractPayload {
                     contract: ContractModule {
                         version: payload.contract_version,
                         module: Arc::new(module),
+                        dependencies: payload.dependencies,
+                        // The source is the only one allowed to upgrade it
+                        upgrade_authority: payload.upgradeable.then(|| self.source.clone()),
+                        source_hash: payload.source_hash,
                     },
+                    upgrade: payload.upgrade,
                     invoke: payload.invoke.map(|invoke| {
                         transcript.invoke_constructor_proof_domain_separator();
                         transcript.append_u64(b"max_gas", invoke.max_gas);
@@ -1105,6 +1414,7 @@ impl TransactionBuilder {
             source_commitments,
             reference,
             range_proof,
+            self_memo,
         );
 
         Ok(transaction)
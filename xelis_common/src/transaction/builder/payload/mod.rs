@@ -31,6 +31,16 @@ pub struct MultiSigBuilder {
     pub threshold: u8,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct MultiSigUpdateBuilder {
+    #[serde(default)]
+    pub add: IndexSet<Address>,
+    #[serde(default)]
+    pub remove: IndexSet<Address>,
+    #[serde(default)]
+    pub new_threshold: Option<u8>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct ContractDepositBuilder {
     pub amount: u64,
@@ -56,6 +66,22 @@ pub struct DeployContractBuilder {
     pub contract_version: ContractVersion,
     // Module to deploy
     pub module: String,
+    // Contracts called by this module
+    // Requires at least contract version V1
+    #[serde(default)]
+    pub dependencies: Vec<Hash>,
+    // Allow the source to upgrade the contract module later
+    // Requires at least contract version V1
+    #[serde(default)]
+    pub upgradeable: bool,
+    // Replace the module of this upgradeable contract instead of deploying a new one
+    // Requires at least TxVersion V3 and no constructor invoke
+    #[serde(default)]
+    pub upgrade: Option<Hash>,
+    // Hash of the source code the module was built from
+    // Requires at least contract version V3
+    #[serde(default)]
+    pub source_hash: Option<Hash>,
     // Inner invoke during the deploy
     pub invoke: Option<DeployContractInvokeBuilder>
 }
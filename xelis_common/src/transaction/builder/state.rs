@@ -1,10 +1,11 @@
 use crate::{
     account::{Nonce, CiphertextCache},
+    block::TopoHeight,
     crypto::{elgamal::Ciphertext, Hash},
     transaction::Reference
 };
 
-use super::FeeHelper;
+use super::{FeeHelper, GenerationError};
 
 /// If the returned balance and ct do not match, the build function will panic and/or
 /// the proof will be invalid.
@@ -19,6 +20,25 @@ pub trait AccountState: FeeHelper {
     /// Block topoheight at which the transaction is being built
     fn get_reference(&self) -> Reference;
 
+    /// Highest topoheight at which a balance of the source was updated, if known
+    fn get_last_balance_topoheight(&self) -> Result<Option<TopoHeight>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Verify that the reference is not older than the balances used
+    /// Otherwise, the verifier would select another balance version
+    /// and the proofs would be invalid
+    fn validate_reference_consistency(&self) -> Result<(), GenerationError<Self::Error>> {
+        let reference = self.get_reference();
+        if let Some(topoheight) = self.get_last_balance_topoheight().map_err(GenerationError::State)? {
+            if topoheight > reference.topoheight {
+                return Err(GenerationError::InconsistentReference(reference.topoheight, topoheight));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get the balance ciphertext from the source
     fn get_account_ciphertext(&self, asset: &Hash) -> Result<CiphertextCache, Self::Error>;
 
@@ -16,6 +16,7 @@ use crate::{
         Writer
     },
     transaction::{
+        extra_data::SelfMemo,
         multisig::{MultiSig, SignatureId},
         Reference,
         SourceCommitment,
@@ -40,6 +41,8 @@ pub struct UnsignedTransaction {
     reference: Reference,
     #[schemars(with = "Vec<u8>", description = "Binary representation of a range proof")]
     range_proof: RangeProof,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    self_memo: Option<SelfMemo>,
     multisig: Option<MultiSig>,
 }
 
@@ -55,6 +58,7 @@ impl UnsignedTransaction {
         source_commitments: Vec<SourceCommitment>,
         reference: Reference,
         range_proof: RangeProof,
+        self_memo: Option<SelfMemo>,
     ) -> Self {
         Self {
             version,
@@ -66,6 +70,7 @@ impl UnsignedTransaction {
             source_commitments,
             reference,
             range_proof,
+            self_memo,
             multisig: None,
         }
     }
@@ -103,6 +108,10 @@ impl UnsignedTransaction {
 
         self.range_proof.write(writer);
         self.reference.write(writer);
+
+        if self.version.has_self_memo() {
+            self.self_memo.write(writer);
+        }
     }
 
     // Get the hash of the transaction for the multi-signature
@@ -137,6 +146,7 @@ impl UnsignedTransaction {
             self.source_commitments,
             self.range_proof,
             self.reference,
+            self.self_memo,
             self.multisig,
             signature,
         )
@@ -172,6 +182,11 @@ impl Serializer for UnsignedTransaction {
 
         let range_proof = RangeProof::read(reader)?;
         let reference = Reference::read(reader)?;
+        let self_memo = if version.has_self_memo() {
+            Option::read(reader)?
+        } else {
+            None
+        };
 
         let multisig = if version > TxVersion::V0 {
             Option::read(reader)?
@@ -189,6 +204,7 @@ impl Serializer for UnsignedTransaction {
             source_commitments,
             reference,
             range_proof,
+            self_memo,
             multisig,
         })
     }
@@ -216,6 +232,10 @@ impl Serializer for UnsignedTransaction {
             size += self.fee_limit.size();
         }
 
+        if self.version.has_self_memo() {
+            size += self.self_memo.size();
+        }
+
         size
     }
 }
\ No newline at end of file
@@ -3,6 +3,7 @@ mod shared_key;
 mod unknown;
 mod extra_data;
 mod typed;
+mod self_memo;
 
 use std::borrow::Cow;
 
@@ -43,6 +44,7 @@ pub use shared_key::SharedKey;
 pub use unknown::UnknownExtraDataFormat;
 pub use extra_data::ExtraData;
 pub use typed::ExtraDataType;
+pub use self_memo::SelfMemo;
 
 // Key Derivation Function used to derive the shared key
 type KDF = sha3::Sha3_256;
@@ -0,0 +1,79 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::DataElement,
+    crypto::{
+        elgamal::{CompressedHandle, PedersenOpening, PublicKey, RISTRETTO_COMPRESSED_SIZE},
+        PrivateKey
+    },
+    serializer::*
+};
+use super::{
+    derive_shared_key_from_handle,
+    derive_shared_key_from_opening,
+    AEADCipher,
+    CipherFormatError,
+    PlaintextData
+};
+
+// Poly1305 tag appended to the cipher
+const TAG_SIZE: usize = 16;
+
+// Memo only readable by the sender of the transaction
+// A fresh opening is generated for each memo so the shared key is never reused
+#[derive(Serialize, Deserialize, JsonSchema, Clone, Debug)]
+pub struct SelfMemo {
+    handle: CompressedHandle,
+    #[schemars(with = "Vec<u8>", description = "Encrypted memo with its AEAD tag")]
+    cipher: AEADCipher,
+}
+
+impl SelfMemo {
+    // Encrypt the memo for the sender key only
+    pub fn new(data: &DataElement, sender: &PublicKey) -> Self {
+        let opening = PedersenOpening::generate_new();
+        let k = derive_shared_key_from_opening(&opening);
+        Self {
+            cipher: PlaintextData(data.to_bytes()).encrypt_in_place_with_aead(&k),
+            handle: sender.decrypt_handle(&opening).compress(),
+        }
+    }
+
+    // Estimate the final size of the memo based on its plaintext
+    pub fn estimate_size(data: &DataElement) -> usize {
+        // 2 bytes for the cipher length
+        RISTRETTO_COMPRESSED_SIZE + 2 + data.size() + TAG_SIZE
+    }
+
+    // Get the compressed handle of the sender
+    pub fn get_handle(&self) -> &CompressedHandle {
+        &self.handle
+    }
+
+    // Decrypt the memo using the sender private key
+    // The cipher is authenticated, so any other key is rejected
+    pub fn decrypt(&self, private_key: &PrivateKey) -> Result<PlaintextData, CipherFormatError> {
+        let handle = self.handle.decompress().map_err(|_| CipherFormatError)?;
+        let key = derive_shared_key_from_handle(private_key, &handle);
+        self.cipher.decrypt(&key)
+    }
+}
+
+impl Serializer for SelfMemo {
+    fn write(&self, writer: &mut Writer) {
+        self.handle.write(writer);
+        self.cipher.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self {
+            handle: CompressedHandle::read(reader)?,
+            cipher: AEADCipher::read(reader)?,
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.handle.size() + self.cipher.size()
+    }
+}
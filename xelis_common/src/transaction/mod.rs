@@ -6,12 +6,15 @@ use crate::{
         elgamal::CompressedPublicKey,
         Hash,
         Hashable,
+        PrivateKey,
         Signature,
     },
     serializer::*
 };
 
 use bulletproofs::RangeProof;
+use indexmap::IndexSet;
+use extra_data::{PlaintextData, SelfMemo};
 use multisig::MultiSig;
 
 pub mod builder;
@@ -62,6 +65,7 @@ pub enum TransactionType {
     MultiSig(MultiSigPayload),
     InvokeContract(InvokeContractPayload),
     DeployContract(DeployContractPayload),
+    MultiSigUpdate(MultiSigUpdatePayload),
 }
 
 // Transaction to be sent over the network
@@ -89,6 +93,10 @@ pub struct Transaction {
     range_proof: RangeProof,
     /// At which block the TX is built
     reference: Reference,
+    /// Memo encrypted for the source key only
+    /// Only available since V3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    self_memo: Option<SelfMemo>,
     /// MultiSig contains the signatures of the transaction
     /// Only available since V1
     multisig: Option<MultiSig>,
@@ -109,6 +117,7 @@ impl Transaction {
         source_commitments: Vec<SourceCommitment>,
         range_proof: RangeProof,
         reference: Reference,
+        self_memo: Option<SelfMemo>,
         multisig: Option<MultiSig>,
         signature: Signature
     ) -> Self {
@@ -122,6 +131,7 @@ impl Transaction {
             source_commitments,
             range_proof,
             reference,
+            self_memo,
             multisig,
             signature,
         }
@@ -181,6 +191,20 @@ impl Transaction {
         &self.range_proof
     }
 
+    // Get the encrypted memo to self
+    #[inline(always)]
+    pub fn get_self_memo(&self) -> Option<&SelfMemo> {
+        self.self_memo.as_ref()
+    }
+
+    // Decrypt the memo to self using the source private key
+    // None if there is no memo or if the key can't decrypt it
+    pub fn decrypt_self_memo(&self, private_key: &PrivateKey) -> Option<PlaintextData> {
+        self.self_memo.as_ref()?
+            .decrypt(private_key)
+            .ok()
+    }
+
     // Get the multisig
     #[inline(always)]
     pub fn get_multisig(&self) -> &Option<MultiSig> {
@@ -222,6 +246,18 @@ impl Transaction {
         }.max(self.source_commitments.len())
     }
 
+    // Maximum gas the transaction may spend in contracts execution
+    // 0 if it doesn't execute any contract
+    #[inline]
+    pub fn get_max_gas(&self) -> u64 {
+        match &self.data {
+            TransactionType::InvokeContract(payload) => payload.max_gas,
+            TransactionType::DeployContract(payload) => payload.invoke.as_ref()
+                .map_or(0, |invoke| invoke.max_gas),
+            _ => 0
+        }
+    }
+
     // If the transaction is an InvokeContract, return the contract hash
     #[inline]
     pub fn invoked_contract(&self) -> Option<&Hash> {
@@ -231,6 +267,61 @@ impl Transaction {
         }
     }
 
+    // Get all the contracts referenced by this transaction
+    // This includes the invoked contract, the upgraded one
+    // and the dependencies of a deployed module
+    pub fn referenced_contracts(&self) -> IndexSet<&Hash> {
+        match &self.data {
+            TransactionType::InvokeContract(payload) => IndexSet::from([&payload.contract]),
+            TransactionType::DeployContract(payload) => payload.upgrade.iter()
+                .chain(payload.contract.dependencies.iter())
+                .collect(),
+            _ => IndexSet::new()
+        }
+    }
+
+    // Indexes of the transfers carrying extra data
+    // Empty if the transaction isn't a transfers one
+    pub fn transfers_with_extra_data(&self) -> Vec<usize> {
+        match &self.data {
+            TransactionType::Transfers(transfers) => transfers.iter()
+                .enumerate()
+                .filter(|(_, transfer)| transfer.has_extra_data())
+                .map(|(i, _)| i)
+                .collect(),
+            _ => Vec::new()
+        }
+    }
+
+    // Get the lowest transaction version able to decode this transaction
+    // This is used by the P2P layer to know if a peer can handle it
+    // Only the fields in use are considered, not the declared version
+    pub fn min_supported_tx_version(&self) -> TxVersion {
+        if self.self_memo.is_some() {
+            return TxVersion::V3;
+        }
+
+        match &self.data {
+            TransactionType::DeployContract(payload) if payload.upgrade.is_some() => TxVersion::V3,
+            TransactionType::InvokeContract(_)
+            | TransactionType::DeployContract(_) => TxVersion::V2,
+            TransactionType::MultiSig(_) => TxVersion::V1,
+            TransactionType::MultiSigUpdate(_) => TxVersion::V3,
+            TransactionType::Transfers(_)
+            | TransactionType::Burn(_) => if self.multisig.is_some() {
+                TxVersion::V1
+            } else {
+                TxVersion::V0
+            }
+        }
+    }
+
+    // Check if this transaction can be decoded by a peer supporting up to the given version
+    #[inline]
+    pub fn can_encode_for(&self, version: TxVersion) -> bool {
+        self.min_supported_tx_version() <= version
+    }
+
     // Consume the transaction by returning the source public key and the transaction type
     #[inline(always)]
     pub fn consume(self) -> (CompressedPublicKey, TransactionType) {
@@ -265,6 +356,10 @@ impl Serializer for TransactionType {
             TransactionType::DeployContract(module) => {
                 writer.write_u8(4);
                 module.write(writer);
+            },
+            TransactionType::MultiSigUpdate(payload) => {
+                writer.write_u8(5);
+                payload.write(writer);
             }
         };
     }
@@ -290,6 +385,7 @@ impl Serializer for TransactionType {
             2 => TransactionType::MultiSig(MultiSigPayload::read(reader)?),
             3 => TransactionType::InvokeContract(InvokeContractPayload::read(reader)?),
             4 => TransactionType::DeployContract(DeployContractPayload::read(reader)?),
+            5 => TransactionType::MultiSigUpdate(MultiSigUpdatePayload::read(reader)?),
             _ => {
                 return Err(ReaderError::InvalidValue)
             }
@@ -314,6 +410,7 @@ impl Serializer for TransactionType {
             },
             TransactionType::InvokeContract(payload) => payload.size(),
             TransactionType::DeployContract(payload) => payload.size(),
+            TransactionType::MultiSigUpdate(payload) => payload.size(),
         }
     }
 }
@@ -338,6 +435,10 @@ impl Serializer for Transaction {
         self.range_proof.write(writer);
         self.reference.write(writer);
 
+        if self.version.has_self_memo() {
+            self.self_memo.write(writer);
+        }
+
         if self.version != TxVersion::V0 {
             self.multisig.write(writer);
         }
@@ -374,6 +475,12 @@ impl Serializer for Transaction {
 
         let range_proof = RangeProof::read(reader)?;
         let reference = Reference::read(reader)?;
+        let self_memo = if version.has_self_memo() {
+            Option::read(reader)?
+        } else {
+            None
+        };
+
         let multisig = if version == TxVersion::V0 {
             None
         } else {
@@ -392,6 +499,7 @@ impl Serializer for Transaction {
             source_commitments,
             range_proof,
             reference,
+            self_memo,
             multisig,
             signature,
         ))
@@ -419,6 +527,10 @@ impl Serializer for Transaction {
             size += self.fee_limit.size();
         }
 
+        if self.version.has_self_memo() {
+            size += self.self_memo.size();
+        }
+
         size
     }
 }
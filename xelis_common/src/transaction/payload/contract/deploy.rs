@@ -1,9 +1,19 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::{contract::ContractModule, serializer::*};
+use crate::{
+    contract::ContractModule,
+    crypto::Hash,
+    serializer::*,
+    transaction::TxVersion
+};
 use super::Deposits;
 
+// Flags byte written before the optional parts of the deploy
+// Before TxVersion V3, it was the presence byte of the constructor invoke
+const FLAG_INVOKE: u8 = 1 << 0;
+const FLAG_UPGRADE: u8 = 1 << 1;
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct InvokeConstructorPayload {
     pub max_gas: u64,
@@ -16,23 +26,71 @@ pub struct DeployContractPayload {
     #[serde(flatten)]
     pub contract: ContractModule,
     pub invoke: Option<InvokeConstructorPayload>,
+    // Upgradeable contract whose module is replaced by this one
+    // Only available starting TxVersion V3
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade: Option<Hash>,
 }
 
 impl Serializer for DeployContractPayload {
     fn write(&self, writer: &mut Writer) {
         self.contract.write(writer);
-        self.invoke.write(writer);
+
+        let mut flags = 0;
+        if self.invoke.is_some() {
+            flags |= FLAG_INVOKE;
+        }
+        if self.upgrade.is_some() {
+            flags |= FLAG_UPGRADE;
+        }
+        writer.write_u8(flags);
+
+        if let Some(invoke) = self.invoke.as_ref() {
+            invoke.write(writer);
+        }
+
+        if let Some(upgrade) = self.upgrade.as_ref() {
+            upgrade.write(writer);
+        }
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> { 
+        let contract = ContractModule::read(reader)?;
+
+        let upgrade_support = reader.context()
+            .get_optional::<TxVersion>()
+            .is_some_and(TxVersion::has_contract_upgrade);
+
+        let flags = reader.read_u8()?;
+        let allowed = if upgrade_support { FLAG_INVOKE | FLAG_UPGRADE } else { FLAG_INVOKE };
+        if flags & !allowed != 0 {
+            return Err(ReaderError::InvalidValue);
+        }
+
+        let invoke = if flags & FLAG_INVOKE != 0 {
+            Some(InvokeConstructorPayload::read(reader)?)
+        } else {
+            None
+        };
+
+        let upgrade = if flags & FLAG_UPGRADE != 0 {
+            Some(Hash::read(reader)?)
+        } else {
+            None
+        };
+
         Ok(Self {
-            contract: ContractModule::read(reader)?,
-            invoke: Option::read(reader)?
+            contract,
+            invoke,
+            upgrade
         })
     }
 
     fn size(&self) -> usize {
-        self.contract.size() + self.invoke.size()
+        // 1 for the flags byte
+        1 + self.contract.size()
+            + self.invoke.as_ref().map_or(0, Serializer::size)
+            + self.upgrade.as_ref().map_or(0, Serializer::size)
     }
 }
 
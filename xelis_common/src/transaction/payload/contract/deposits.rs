@@ -1,12 +1,123 @@
 use std::ops::{Deref, DerefMut};
 
+use curve25519_dalek::Scalar;
 use indexmap::IndexMap;
+use merlin::Transcript;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::{crypto::Hash, serializer::*};
+use crate::{
+    crypto::{
+        elgamal::{DecryptHandle, PedersenCommitment, PedersenOpening, PublicKey},
+        proofs::CiphertextValidityProof,
+        Hash,
+        ProtocolTranscript
+    },
+    serializer::*,
+    transaction::TxVersion
+};
 use super::ContractDeposit;
 
+#[derive(Error, Debug)]
+pub enum DepositAggregationError {
+    #[error("No private deposit to aggregate")]
+    Empty,
+    #[error("Private deposits assets differ")]
+    AssetMismatch,
+    #[error("Aggregated deposit amount overflow")]
+    Overflow,
+}
+
+// Private deposit with its secret values
+// required to aggregate it with others
+pub struct PrivateDepositInput {
+    pub asset: Hash,
+    pub amount: u64,
+    pub opening: PedersenOpening,
+}
+
+// Private deposit aggregated from several inputs
+// The amount & opening are kept for the range proof
+pub struct AggregatedPrivateDeposit {
+    pub asset: Hash,
+    pub amount: u64,
+    pub opening: PedersenOpening,
+    pub deposit: ContractDeposit,
+}
+
+impl ContractDeposit {
+    // Build a private deposit from its amount and opening
+    // The deposit is appended to the transcript before generating its validity proof,
+    // in the same sequence as replayed by the verification
+    pub(crate) fn new_private(
+        asset: &Hash,
+        amount: u64,
+        opening: &PedersenOpening,
+        source_pubkey: &PublicKey,
+        contract_key: &PublicKey,
+        tx_version: TxVersion,
+        transcript: &mut Transcript,
+    ) -> Self {
+        let commitment = PedersenCommitment::new_with_opening(Scalar::from(amount), opening).compress();
+        let sender_handle = DecryptHandle::new(source_pubkey, opening).compress();
+        let receiver_handle = DecryptHandle::new(contract_key, opening).compress();
+
+        transcript.deposit_proof_domain_separator();
+        transcript.append_hash(b"deposit_asset", asset);
+        transcript.append_commitment(b"deposit_commitment", &commitment);
+        transcript.append_handle(b"deposit_sender_handle", &sender_handle);
+        transcript.append_handle(b"deposit_receiver_handle", &receiver_handle);
+
+        let ct_validity_proof = CiphertextValidityProof::new(
+            contract_key,
+            source_pubkey,
+            amount,
+            opening,
+            tx_version,
+            transcript,
+        );
+
+        ContractDeposit::Private { commitment, sender_handle, receiver_handle, ct_validity_proof }
+    }
+
+    // Aggregate several private deposits of the same asset into a single one
+    // Commitments & handles are homomorphic and are summed, but the
+    // validity proof can't be merged and is generated again
+    // from the aggregated opening
+    pub fn aggregate_private(
+        inputs: &[PrivateDepositInput],
+        source_pubkey: &PublicKey,
+        contract_key: &PublicKey,
+        tx_version: TxVersion,
+        transcript: &mut Transcript,
+    ) -> Result<AggregatedPrivateDeposit, DepositAggregationError> {
+        let (first, others) = inputs.split_first()
+            .ok_or(DepositAggregationError::Empty)?;
+
+        let mut amount = first.amount;
+        let mut opening = first.opening.as_scalar();
+        for input in others {
+            if input.asset != first.asset {
+                return Err(DepositAggregationError::AssetMismatch)
+            }
+
+            amount = amount.checked_add(input.amount)
+                .ok_or(DepositAggregationError::Overflow)?;
+            opening += input.opening.as_scalar();
+        }
+
+        let opening = PedersenOpening::from_scalar(opening);
+        let deposit = Self::new_private(&first.asset, amount, &opening, source_pubkey, contract_key, tx_version, transcript);
+
+        Ok(AggregatedPrivateDeposit {
+            asset: first.asset.clone(),
+            amount,
+            opening,
+            deposit,
+        })
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
 pub struct Deposits(pub IndexMap<Hash, ContractDeposit>);
@@ -52,4 +163,92 @@ impl Serializer for Deposits {
             .map(|(asset, deposit)| asset.size() + deposit.size())
             .sum::<usize>()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::{
+        elgamal::{Ciphertext, KeyPair},
+        proofs::G
+    };
+    use super::*;
+
+    fn input(asset: &Hash, amount: u64) -> PrivateDepositInput {
+        PrivateDepositInput {
+            asset: asset.clone(),
+            amount,
+            opening: PedersenOpening::generate_new(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_private_deposits() {
+        let source = KeyPair::new();
+        let contract = KeyPair::new();
+        let asset = Hash::zero();
+        let inputs = [input(&asset, 10), input(&asset, 25), input(&asset, 7)];
+
+        let aggregated = ContractDeposit::aggregate_private(
+            &inputs,
+            source.get_public_key(),
+            contract.get_public_key(),
+            TxVersion::V3,
+            &mut Transcript::new(b"test"),
+        ).unwrap();
+        assert_eq!(aggregated.amount, 42);
+
+        let ContractDeposit::Private { commitment, sender_handle, receiver_handle, ct_validity_proof } = &aggregated.deposit else {
+            panic!("expected a private deposit");
+        };
+
+        let commitment = commitment.decompress().unwrap();
+        let sender_handle = sender_handle.decompress().unwrap();
+        let receiver_handle = receiver_handle.decompress().unwrap();
+        let expected = Scalar::from(42u64) * *G;
+
+        // Both sender & receiver decrypt to the sum of the inputs
+        let sender_ct = Ciphertext::new(commitment.clone(), sender_handle.clone());
+        assert_eq!(source.decrypt_to_point(&sender_ct), expected);
+
+        let receiver_ct = Ciphertext::new(commitment.clone(), receiver_handle.clone());
+        assert_eq!(contract.decrypt_to_point(&receiver_ct), expected);
+
+        // Replay the transcript to verify the regenerated proof
+        let mut transcript = Transcript::new(b"test");
+        transcript.deposit_proof_domain_separator();
+        transcript.append_hash(b"deposit_asset", &asset);
+        transcript.append_commitment(b"deposit_commitment", &commitment.compress());
+        transcript.append_handle(b"deposit_sender_handle", &sender_handle.compress());
+        transcript.append_handle(b"deposit_receiver_handle", &receiver_handle.compress());
+
+        assert!(ct_validity_proof.verify(
+            &commitment,
+            contract.get_public_key(),
+            source.get_public_key(),
+            &receiver_handle,
+            &sender_handle,
+            &mut transcript,
+        ).is_ok());
+    }
+
+    #[test]
+    fn test_aggregate_private_deposits_errors() {
+        let source = KeyPair::new();
+        let contract = KeyPair::new();
+        let aggregate = |inputs: &[PrivateDepositInput]| ContractDeposit::aggregate_private(
+            inputs,
+            source.get_public_key(),
+            contract.get_public_key(),
+            TxVersion::V3,
+            &mut Transcript::new(b"test"),
+        );
+
+        assert!(matches!(aggregate(&[]), Err(DepositAggregationError::Empty)));
+
+        let inputs = [input(&Hash::zero(), 1), input(&Hash::max(), 1)];
+        assert!(matches!(aggregate(&inputs), Err(DepositAggregationError::AssetMismatch)));
+
+        let inputs = [input(&Hash::zero(), u64::MAX), input(&Hash::zero(), 1)];
+        assert!(matches!(aggregate(&inputs), Err(DepositAggregationError::Overflow)));
+    }
+}
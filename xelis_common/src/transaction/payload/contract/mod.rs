@@ -2,7 +2,12 @@ mod deploy;
 mod invoke;
 mod deposits;
 
-use anyhow::Context;
+use std::{
+    io::{self, Read, Write},
+    mem
+};
+
+use anyhow::Context as AnyhowContext;
 use indexmap::{IndexMap, IndexSet};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -19,12 +24,36 @@ use xelis_vm::{
     U256,
 };
 use crate::{
-    contract::ContractVersion,
+    context::Context,
+    contract::{
+        ContractVersion,
+        ModuleCanonical,
+        ADDRESS_OPAQUE_ID,
+        ARBITRARY_RANGE_PROOF_OPAQUE_ID,
+        BALANCE_PROOF_OPAQUE_ID,
+        CIPHERTEXT_OPAQUE_ID,
+        CIPHERTEXT_VALIDITY_PROOF_OPAQUE_ID,
+        COMMITMENT_EQUALITY_PROOF_OPAQUE_ID,
+        HASH_OPAQUE_ID,
+        OWNERSHIP_PROOF_OPAQUE_ID,
+        RANGE_PROOF_OPAQUE_ID,
+        RISTRETTO_OPAQUE_ID,
+        SCALAR_OPAQUE_ID,
+        SIGNATURE_OPAQUE_ID
+    },
     crypto::{
-        elgamal::{CompressedCommitment, CompressedHandle},
+        elgamal::{
+            CompressedCommitment,
+            CompressedHandle,
+            RISTRETTO_COMPRESSED_SIZE,
+            SCALAR_SIZE
+        },
         proofs::CiphertextValidityProof,
+        HASH_SIZE,
+        SIGNATURE_SIZE
     },
-    serializer::*
+    serializer::*,
+    transaction::{TxVersion, EXTRA_DATA_LIMIT_SIZE}
 };
 
 pub use deploy::*;
@@ -102,6 +131,91 @@ impl Serializer for ContractDeposit {
     }
 }
 
+// Position of a ciphertext validity proof not parsed yet
+// The offset is relative to the start of the buffer used to read the header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LazyProof {
+    pub offset: usize,
+    pub size: usize,
+    // Tx version found in the reader context, required to parse the proof
+    pub version: Option<TxVersion>,
+}
+
+impl LazyProof {
+    // Parse the proof from the same bytes used to read the header
+    pub fn read(&self, bytes: &[u8]) -> Result<CiphertextValidityProof, ReaderError> {
+        let end = self.offset.checked_add(self.size)
+            .ok_or(ReaderError::InvalidSize)?;
+        let bytes = bytes.get(self.offset..end)
+            .ok_or(ReaderError::InvalidSize)?;
+
+        let mut context = crate::context::Context::new();
+        if let Some(version) = self.version {
+            context.store(version);
+        }
+
+        CiphertextValidityProof::read(&mut Reader::with_context(bytes, context))
+    }
+}
+
+// Contract deposit without its ciphertext validity proof parsed
+// Useful for indexers only displaying the deposits
+#[derive(Debug, Clone)]
+pub enum ContractDepositHeader {
+    Public(u64),
+    Private {
+        commitment: CompressedCommitment,
+        sender_handle: CompressedHandle,
+        receiver_handle: CompressedHandle,
+        ct_validity_proof: LazyProof,
+    }
+}
+
+impl ContractDepositHeader {
+    // Parse the proof to build back the full deposit
+    pub fn into_deposit(self, bytes: &[u8]) -> Result<ContractDeposit, ReaderError> {
+        Ok(match self {
+            Self::Public(amount) => ContractDeposit::Public(amount),
+            Self::Private { commitment, sender_handle, receiver_handle, ct_validity_proof } => ContractDeposit::Private {
+                commitment,
+                sender_handle,
+                receiver_handle,
+                ct_validity_proof: ct_validity_proof.read(bytes)?
+            }
+        })
+    }
+}
+
+impl ContractDeposit {
+    // Read the deposit without parsing its proof
+    // The proof bytes are skipped and can be parsed later using its offset
+    pub fn read_header(reader: &mut Reader) -> Result<ContractDepositHeader, ReaderError> {
+        Ok(match reader.read_u8()? {
+            0 => ContractDepositHeader::Public(reader.read_u64()?),
+            1 => {
+                let commitment = CompressedCommitment::read(reader)?;
+                let sender_handle = CompressedHandle::read(reader)?;
+                let receiver_handle = CompressedHandle::read(reader)?;
+
+                let version = reader.context()
+                    .get_optional::<TxVersion>()
+                    .copied();
+                let offset = reader.total_read();
+                let size = CiphertextValidityProof::size_for_version(version);
+                reader.skip(size)?;
+
+                ContractDepositHeader::Private {
+                    commitment,
+                    sender_handle,
+                    receiver_handle,
+                    ct_validity_proof: LazyProof { offset, size, version }
+                }
+            },
+            _ => return Err(ReaderError::InvalidValue)
+        })
+    }
+}
+
 impl Serializer for U256 {
     fn write(&self, writer: &mut Writer) {
         writer.write_bytes(&self.to_be_bytes());
@@ -177,7 +291,7 @@ impl Serializer for Primitive {
             6 => Primitive::U256(U256::read(reader)?),
             7 => Primitive::Boolean(reader.read_bool()?),
             8 => {
-                let len = DynamicLen::read(reader)?.0;
+                let len = DynamicLen::read_bounded(reader)?;
                 Primitive::String(reader.read_string_with_size(len)?)
             },
             9 => {
@@ -221,33 +335,483 @@ impl Serializer for Primitive {
     }
 }
 
-impl Serializer for ValueCell {
-    // Serialize a value cell
+// Default maximum nesting depth allowed for a ValueCell
+pub const VALUE_CELL_MAX_DEPTH: usize = 16;
+
+// Override the ValueCell maximum depth when stored in the reader context
+// This is intended for tooling only, consensus must keep the default
+#[derive(Debug, Clone, Copy)]
+pub struct ValueCellMaxDepth(pub usize);
+
+// Read a ValueCell with a custom maximum nesting depth
+pub trait ValueCellDepth: Sized {
+    fn read_with_depth(reader: &mut Reader, max_depth: usize) -> Result<Self, ReaderError>;
+}
+
+// Cheap equality check for large ValueCells
+pub trait ValueCellFastEq {
+    // Compare the cached serialized sizes first and only fall back
+    // on the structural comparison when they match
+    // Different sizes always mean different values
+    fn fast_eq(&self, other: &Self) -> bool;
+}
+
+// ValueCell with its serialized size computed only once
+// Computing the size walks the whole value, so it is kept
+// to compare the same large value several times
+#[derive(Debug, Clone)]
+pub struct SizedValueCell {
+    value: ValueCell,
+    size: usize,
+}
+
+impl SizedValueCell {
+    pub fn new(value: ValueCell) -> Self {
+        let size = value.size();
+        Self {
+            value,
+            size
+        }
+    }
+
+    // Get the inner value
+    pub fn get(&self) -> &ValueCell {
+        &self.value
+    }
+
+    // Serialized size of the inner value
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // Take the inner value
+    pub fn into_inner(self) -> ValueCell {
+        self.value
+    }
+}
+
+// Streaming serialization of a ValueCell
+// The same encoding as its Serializer impl is used,
+// but without buffering the whole value in memory
+pub trait ValueCellStream: Sized {
+    // Write the value cell directly into the writer
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    // Read a value cell directly from the reader
+    fn read_from<R: Read>(r: &mut R) -> Result<Self, ReaderError> {
+        Self::read_from_with_context(r, Context::new())
+    }
+
+    // Read a value cell directly from the reader
+    // The context is used to decode the primitives
+    fn read_from_with_context<R: Read>(r: &mut R, context: Context) -> Result<Self, ReaderError>;
+}
+
+// Maximum values allocated up front when decoding an object
+const MAX_PREALLOCATED_VALUES: usize = 1024;
+
+// Source used by the iterative value cell decoder
+trait CellSource {
+    fn next_tag(&mut self) -> Result<u8, ReaderError>;
+
+    fn next_len(&mut self) -> Result<usize, ReaderError>;
+
+    fn next_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReaderError>;
+
+    fn next_primitive(&mut self) -> Result<Primitive, ReaderError>;
+
+    // Current offset in the source if it's tracked
+    fn offset(&self) -> Option<usize>;
+}
+
+impl CellSource for Reader<'_> {
+    fn next_tag(&mut self) -> Result<u8, ReaderError> {
+        self.read_u8()
+    }
+
+    fn next_len(&mut self) -> Result<usize, ReaderError> {
+        DynamicLen::read_bounded(self)
+    }
+
+    fn next_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReaderError> {
+        self.read_bytes(len)
+    }
+
+    fn next_primitive(&mut self) -> Result<Primitive, ReaderError> {
+        Primitive::read(self)
+    }
+
+    fn offset(&self) -> Option<usize> {
+        Some(self.position())
+    }
+}
+
+// Wrap a std reader to be used as a cell source
+// The context is given to the primitives decoding
+struct StreamSource<'a, R: Read> {
+    inner: &'a mut R,
+    context: Context,
+}
+
+impl<R: Read> StreamSource<'_, R> {
+    fn fill(&mut self, buffer: &mut Vec<u8>, n: usize) -> Result<(), ReaderError> {
+        let start = buffer.len();
+        buffer.resize(start + n, 0);
+        self.inner.read_exact(&mut buffer[start..])
+            .map_err(map_io_error)
+    }
+
+    // Read the next byte and keep it in the buffer
+    fn fill_u8(&mut self, buffer: &mut Vec<u8>) -> Result<u8, ReaderError> {
+        let value = self.next_tag()?;
+        buffer.push(value);
+        Ok(value)
+    }
+
+    // Read a DynamicLen and keep its encoding in the buffer
+    fn fill_len(&mut self, buffer: &mut Vec<u8>) -> Result<usize, ReaderError> {
+        let start = buffer.len();
+        match self.fill_u8(buffer)? {
+            0xFD => self.fill(buffer, 2)?,
+            0xFE => self.fill(buffer, 4)?,
+            _ => {}
+        }
+
+        DynamicLen::read(&mut Reader::new(&buffer[start..])).map(|len| len.0)
+    }
+
+    // Read a u16 length prefix and its bytes
+    fn fill_u16_prefixed(&mut self, buffer: &mut Vec<u8>) -> Result<(), ReaderError> {
+        let start = buffer.len();
+        self.fill(buffer, 2)?;
+        let len = u16::from_be_bytes([buffer[start], buffer[start + 1]]) as usize;
+        self.fill(buffer, len)
+    }
+
+    // Size of a primitive encoded with a fixed length
+    fn fixed_size(tag: u8) -> Option<usize> {
+        Some(match tag {
+            0 => 0,
+            1 | 7 => 1,
+            2 => 2,
+            3 => 4,
+            4 => 8,
+            5 => 16,
+            6 => 32,
+            _ => return None
+        })
+    }
+
+    // Opaque types have no length prefix,
+    // so each known type is read using its own layout
+    fn fill_opaque(&mut self, buffer: &mut Vec<u8>) -> Result<(), ReaderError> {
+        // amount or max value, commitment and its equality proof
+        const COMMITMENT_EQ_PROOF_SIZE: usize = RISTRETTO_COMPRESSED_SIZE * 3 + SCALAR_SIZE * 3;
+        const RANGE_PREFIX_SIZE: usize = 8 + RISTRETTO_COMPRESSED_SIZE + COMMITMENT_EQ_PROOF_SIZE;
+
+        match self.fill_u8(buffer)? {
+            HASH_OPAQUE_ID => self.fill(buffer, HASH_SIZE),
+            ADDRESS_OPAQUE_ID => {
+                // network flag and public key
+                self.fill(buffer, 1 + RISTRETTO_COMPRESSED_SIZE)?;
+                match self.fill_u8(buffer)? {
+                    0 => Ok(()),
+                    1 => self.fill_data_element(buffer),
+                    _ => Err(ReaderError::InvalidValue)
+                }
+            },
+            SIGNATURE_OPAQUE_ID => self.fill(buffer, SIGNATURE_SIZE),
+            CIPHERTEXT_OPAQUE_ID => self.fill(buffer, RISTRETTO_COMPRESSED_SIZE * 2),
+            CIPHERTEXT_VALIDITY_PROOF_OPAQUE_ID => {
+                let version = self.context.get_optional::<TxVersion>().copied();
+                self.fill(buffer, CiphertextValidityProof::size_for_version(version))
+            },
+            COMMITMENT_EQUALITY_PROOF_OPAQUE_ID => self.fill(buffer, COMMITMENT_EQ_PROOF_SIZE),
+            RANGE_PROOF_OPAQUE_ID => self.fill_u16_prefixed(buffer),
+            ARBITRARY_RANGE_PROOF_OPAQUE_ID | OWNERSHIP_PROOF_OPAQUE_ID => {
+                self.fill(buffer, RANGE_PREFIX_SIZE)?;
+                self.fill_u16_prefixed(buffer)
+            },
+            BALANCE_PROOF_OPAQUE_ID => self.fill(buffer, 8 + COMMITMENT_EQ_PROOF_SIZE),
+            RISTRETTO_OPAQUE_ID => self.fill(buffer, RISTRETTO_COMPRESSED_SIZE),
+            SCALAR_OPAQUE_ID => self.fill(buffer, SCALAR_SIZE),
+            _ => Err(ReaderError::InvalidValue)
+        }
+    }
+
+    // Extra data of an integrated address
+    // Iterative to not trust its depth, and bounded by the extra data limit
+    fn fill_data_element(&mut self, buffer: &mut Vec<u8>) -> Result<(), ReaderError> {
+        let start = buffer.len();
+        // true for a field key, false for an element
+        let mut pending = vec![false];
+        while let Some(is_key) = pending.pop() {
+            if buffer.len() - start > EXTRA_DATA_LIMIT_SIZE {
+                return Err(ReaderError::InvalidSize)
+            }
+
+            if is_key {
+                self.fill_data_value(buffer)?;
+                continue;
+            }
+
+            match self.fill_u8(buffer)? {
+                0 => self.fill_data_value(buffer)?,
+                1 => {
+                    let count = self.fill_u8(buffer)?;
+                    pending.resize(pending.len() + count as usize, false);
+                },
+                2 => {
+                    let count = self.fill_u8(buffer)?;
+                    for _ in 0..count {
+                        pending.push(false);
+                        pending.push(true);
+                    }
+                },
+                _ => return Err(ReaderError::InvalidValue)
+            }
+        }
+
+        Ok(())
+    }
+
+    // Typed value of an address extra data
+    fn fill_data_value(&mut self, buffer: &mut Vec<u8>) -> Result<(), ReaderError> {
+        match self.fill_u8(buffer)? {
+            // bool and u8
+            0 | 2 => self.fill(buffer, 1),
+            1 => {
+                let len = self.fill_u8(buffer)?;
+                self.fill(buffer, len as usize)
+            },
+            3 => self.fill(buffer, 2),
+            4 => self.fill(buffer, 4),
+            5 => self.fill(buffer, 8),
+            6 => self.fill(buffer, 16),
+            7 => self.fill(buffer, HASH_SIZE),
+            8 => self.fill_u16_prefixed(buffer),
+            _ => Err(ReaderError::InvalidValue)
+        }
+    }
+}
+
+impl<R: Read> CellSource for StreamSource<'_, R> {
+    fn next_tag(&mut self) -> Result<u8, ReaderError> {
+        let mut tag = [0u8; 1];
+        self.inner.read_exact(&mut tag)
+            .map_err(map_io_error)?;
+
+        Ok(tag[0])
+    }
+
+    fn next_len(&mut self) -> Result<usize, ReaderError> {
+        self.fill_len(&mut Vec::with_capacity(5))
+    }
+
+    fn next_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReaderError> {
+        // Don't trust the len for the allocation
+        let mut bytes = Vec::new();
+        self.inner.by_ref()
+            .take(len as u64)
+            .read_to_end(&mut bytes)
+            .map_err(map_io_error)?;
+
+        if bytes.len() != len {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        Ok(bytes)
+    }
+
+    // Only the bytes of the primitive are buffered
+    // so we can reuse its Serializer impl
+    fn next_primitive(&mut self) -> Result<Primitive, ReaderError> {
+        let mut buffer = Vec::new();
+        match self.fill_u8(&mut buffer)? {
+            8 => {
+                let len = self.fill_len(&mut buffer)?;
+                let bytes = self.next_bytes(len)?;
+                buffer.extend_from_slice(&bytes);
+            },
+            9 => {
+                for _ in 0..2 {
+                    let tag = self.fill_u8(&mut buffer)?;
+                    let size = Self::fixed_size(tag)
+                        .ok_or(ReaderError::InvalidValue)?;
+                    self.fill(&mut buffer, size)?;
+                }
+            },
+            10 => self.fill_opaque(&mut buffer)?,
+            tag => {
+                let size = Self::fixed_size(tag)
+                    .ok_or(ReaderError::InvalidValue)?;
+                self.fill(&mut buffer, size)?;
+            }
+        }
+
+        // The context is lent to the reader for the decoding
+        let mut reader = Reader::with_context(&buffer, mem::take(&mut self.context));
+        let primitive = Primitive::read(&mut reader);
+        self.context = mem::take(reader.context_mut());
+
+        primitive
+    }
+
+    fn offset(&self) -> Option<usize> {
+        None
+    }
+}
+
+fn map_io_error(e: io::Error) -> ReaderError {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => ReaderError::InvalidSize,
+        _ => ReaderError::Any(e.into())
+    }
+}
+
+// Decode a value cell from the source
+// Iterative approach to prevent stack overflow attacks
+fn read_value_cell<S: CellSource>(source: &mut S, max_depth: usize) -> Result<ValueCell, ReaderError> {
+    enum Pending {
+        Object { remaining: usize, values: Vec<ValuePointer> },
+        Map { remaining: usize, map: IndexMap<ValueCell, ValuePointer>, pending_key: Option<ValueCell> },
+    }
+
+    // Read the next value from the source
+    // Containers with values are pushed on the stack
+    fn read_next<S: CellSource>(source: &mut S, stack: &mut Vec<Pending>, max_depth: usize) -> Result<Option<ValueCell>, ReaderError> {
+        Ok(match source.next_tag()? {
+            0 => Some(ValueCell::Primitive(source.next_primitive()?)),
+            1 => {
+                let len = source.next_len()?;
+                Some(ValueCell::Bytes(source.next_bytes(len)?))
+            }
+            2 => {
+                let len = source.next_len()?;
+                if len == 0 {
+                    Some(ValueCell::Object(Vec::new()))
+                } else {
+                    if stack.len() >= max_depth {
+                        return Err(ReaderError::InvalidValue);
+                    }
+                    // Streams can't bound the len, so don't trust it for the allocation
+                    let capacity = len.min(MAX_PREALLOCATED_VALUES);
+                    stack.push(Pending::Object { remaining: len, values: Vec::with_capacity(capacity) });
+                    None
+                }
+            }
+            3 => {
+                let len = source.next_len()?;
+                if len == 0 {
+                    Some(ValueCell::Map(Box::new(IndexMap::new())))
+                } else {
+                    if stack.len() >= max_depth {
+                        return Err(ReaderError::InvalidValue);
+                    }
+                    stack.push(Pending::Map { remaining: len, map: IndexMap::new(), pending_key: None });
+                    None
+                }
+            }
+            _ => return Err(ReaderError::InvalidValue)
+        })
+    }
+
+    let mut stack: Vec<Pending> = Vec::new();
+    let mut result: Option<ValueCell> = None;
+
+    loop {
+        // If we have a result, process it
+        if let Some(value) = result.take() {
+            let Some(last) = stack.last_mut() else {
+                return Ok(value);
+            };
+
+            // Add the value to the parent container
+            match last {
+                Pending::Object { remaining, values } => {
+                    values.push(value.into());
+                    *remaining -= 1;
+                }
+                Pending::Map { remaining, map, pending_key } => {
+                    if let Some(key) = pending_key.take() {
+                        map.insert(key, value.into());
+                        *remaining -= 1;
+                    } else {
+                        *pending_key = Some(value);
+                    }
+                }
+            }
+
+            // Check if completed containers can be popped
+            while let Some(top) = stack.last() {
+                let is_complete = match top {
+                    Pending::Object { remaining, .. } => *remaining == 0,
+                    Pending::Map { remaining, pending_key, .. } => *remaining == 0 && pending_key.is_none(),
+                };
+
+                if is_complete {
+                    let completed = stack.pop().unwrap();
+                    result = Some(match completed {
+                        Pending::Object { values, .. } => ValueCell::Object(values),
+                        Pending::Map { map, .. } => ValueCell::Map(Box::new(map)),
+                    });
+                } else {
+                    break;
+                }
+            }
+
+            if result.is_some() {
+                continue;
+            }
+        }
+
+        // Read the next value
+        // Errors are reported at the position of its tag
+        let offset = source.offset();
+        match read_next(source, &mut stack, max_depth) {
+            Ok(value) => result = value,
+            Err(e) => return Err(match offset {
+                Some(pos) => e.at(pos),
+                None => e
+            })
+        }
+    }
+}
+
+impl ValueCellStream for ValueCell {
     // ValueCell with more than one value are serialized in reverse order
     // This help us to save a reverse operation when deserializing
-    fn write(&self, writer: &mut Writer) {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        // Reused buffer for the primitives encoding
+        let mut buffer = Vec::new();
         let mut stack = vec![self];
         while let Some(cell) = stack.pop() {
+            buffer.clear();
+            let mut writer = Writer::new(&mut buffer);
             match cell {
                 ValueCell::Primitive(value) => {
                     writer.write_u8(0);
-                    value.write(writer);
+                    value.write(&mut writer);
+                    w.write_all(&buffer)?;
                 },
                 ValueCell::Bytes(bytes) => {
                     writer.write_u8(1);
-                    DynamicLen(bytes.len()).write(writer);
-                    writer.write_bytes(bytes);
+                    DynamicLen(bytes.len()).write(&mut writer);
+                    w.write_all(&buffer)?;
+                    w.write_all(bytes)?;
                 }
                 ValueCell::Object(values) => {
                     writer.write_u8(2);
-                    DynamicLen(values.len()).write(writer);
+                    DynamicLen(values.len()).write(&mut writer);
+                    w.write_all(&buffer)?;
                     for value in values.iter().rev() {
                         stack.push(value.as_ref());
                     }
                 },
                 ValueCell::Map(map) => {
                     writer.write_u8(3);
-                    DynamicLen(map.len()).write(writer);
+                    DynamicLen(map.len()).write(&mut writer);
+                    w.write_all(&buffer)?;
                     for (key, value) in map.iter().rev() {
                         stack.push(value.as_ref());
                         stack.push(key);
@@ -255,100 +819,40 @@ impl Serializer for ValueCell {
                 }
             }
         }
-    }
-
-    // No deserialization can occurs here as we're missing context
-    fn read(reader: &mut Reader) -> Result<ValueCell, ReaderError> {
-        // Iterative approach to prevent stack overflow attacks
-        // Maximum nesting depth allowed
-        const MAX_DEPTH: usize = 16;
 
-        enum Pending {
-            Object { remaining: usize, values: Vec<ValuePointer> },
-            Map { remaining: usize, map: IndexMap<ValueCell, ValuePointer>, pending_key: Option<ValueCell> },
-        }
+        Ok(())
+    }
 
-        let mut stack: Vec<Pending> = Vec::new();
-        let mut result: Option<ValueCell> = None;
+    fn read_from_with_context<R: Read>(r: &mut R, context: Context) -> Result<Self, ReaderError> {
+        read_value_cell(&mut StreamSource { inner: r, context }, VALUE_CELL_MAX_DEPTH)
+    }
+}
 
-        loop {
-            // If we have a result, process it
-            if let Some(value) = result.take() {
-                let Some(last) = stack.last_mut() else {
-                    return Ok(value);
-                };
+impl ValueCellFastEq for SizedValueCell {
+    fn fast_eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.value == other.value
+    }
+}
 
-                // Add the value to the parent container
-                match last {
-                    Pending::Object { remaining, values } => {
-                        values.push(value.into());
-                        *remaining -= 1;
-                    }
-                    Pending::Map { remaining, map, pending_key } => {
-                        if let Some(key) = pending_key.take() {
-                            map.insert(key, value.into());
-                            *remaining -= 1;
-                        } else {
-                            *pending_key = Some(value);
-                        }
-                    }
-                }
+impl ValueCellDepth for ValueCell {
+    fn read_with_depth(reader: &mut Reader, max_depth: usize) -> Result<Self, ReaderError> {
+        read_value_cell(reader, max_depth)
+    }
+}
 
-                // Check if completed containers can be popped
-                while let Some(top) = stack.last() {
-                    let is_complete = match top {
-                        Pending::Object { remaining, .. } => *remaining == 0,
-                        Pending::Map { remaining, pending_key, .. } => *remaining == 0 && pending_key.is_none(),
-                    };
-
-                    if is_complete {
-                        let completed = stack.pop().unwrap();
-                        result = Some(match completed {
-                            Pending::Object { values, .. } => ValueCell::Object(values),
-                            Pending::Map { map, .. } => ValueCell::Map(Box::new(map)),
-                        });
-                    } else {
-                        break;
-                    }
-                }
+impl Serializer for ValueCell {
+    fn write(&self, writer: &mut Writer) {
+        self.write_to(writer.as_mut_bytes())
+            .expect("writing into a Vec can't fail");
+    }
 
-                if result.is_some() {
-                    continue;
-                }
-            }
+    // No deserialization can occurs here as we're missing context
+    fn read(reader: &mut Reader) -> Result<ValueCell, ReaderError> {
+        let max_depth = reader.context()
+            .get_optional::<ValueCellMaxDepth>()
+            .map_or(VALUE_CELL_MAX_DEPTH, |depth| depth.0);
 
-            // Read the next value
-            match reader.read_u8()? {
-                0 => result = Some(ValueCell::Primitive(Primitive::read(reader)?)),
-                1 => {
-                    let len = DynamicLen::read(reader)?.0;
-                    result = Some(ValueCell::Bytes(reader.read_bytes(len)?));
-                }
-                2 => {
-                    let len = DynamicLen::read(reader)?.0;
-                    if len == 0 {
-                        result = Some(ValueCell::Object(Vec::new()));
-                    } else {
-                        if stack.len() >= MAX_DEPTH {
-                            return Err(ReaderError::InvalidValue);
-                        }
-                        stack.push(Pending::Object { remaining: len, values: Vec::with_capacity(len) });
-                    }
-                }
-                3 => {
-                    let len = DynamicLen::read(reader)?.0;
-                    if len == 0 {
-                        result = Some(ValueCell::Map(Box::new(IndexMap::new())));
-                    } else {
-                        if stack.len() >= MAX_DEPTH {
-                            return Err(ReaderError::InvalidValue);
-                        }
-                        stack.push(Pending::Map { remaining: len, map: IndexMap::new(), pending_key: None });
-                    }
-                }
-                _ => return Err(ReaderError::InvalidValue)
-            }
-        }
+        read_value_cell(reader, max_depth)
     }
 
     fn size(&self) -> usize {
@@ -400,53 +904,64 @@ impl Serializer for Module {
             .cloned()
             .unwrap_or(ContractVersion::V0);
 
-        fn write_parameters(writer: &mut Writer, parameters: &Option<Vec<TypePacked>>, version: ContractVersion) {
-            if version >= ContractVersion::V1 {
-                if let Some(params) = parameters {
-                    writer.write_bool(true);                                
-                    writer.write_u8(params.len() as u8);
-                    for param in params {
-                        param.write(writer);
+        // Function helper to write the chunk access based on contract version
+        // Starting from V3, the access kind and the parameters presence
+        // are packed in the same byte
+        fn write_access(writer: &mut Writer, access: &Access, version: ContractVersion) {
+            let (kind, parameters) = match access {
+                Access::All { parameters } => (0, Some(parameters)),
+                Access::Internal => (1, None),
+                Access::Entry { parameters } => (2, Some(parameters)),
+                Access::Hook { .. } => (3, None),
+            };
+
+            if version >= ContractVersion::V3 {
+                let mut bits = BitWriter::new();
+                bits.write_bits(kind, 2);
+                bits.write_flag(parameters.is_some_and(Option::is_some));
+                bits.flush(writer);
+            } else {
+                writer.write_u8(kind);
+            }
+
+            match access {
+                Access::Hook { id } => writer.write_u8(*id),
+                Access::All { parameters } | Access::Entry { parameters } if version >= ContractVersion::V1 => {
+                    // V1 and V2 write the parameters presence after the access kind
+                    if version < ContractVersion::V3 {
+                        writer.write_bool(parameters.is_some());
                     }
-                } else {
-                    writer.write_bool(false);
-                }
+
+                    if let Some(params) = parameters {
+                        writer.write_u8(params.len() as u8);
+                        for param in params {
+                            param.write(writer);
+                        }
+                    }
+                },
+                _ => {}
             }
         }
 
-         // Function helper to write parameters based on contract version
         let chunks = self.chunks();
         writer.write_u16(chunks.len() as u16);
         for entry in chunks {
             let instructions = entry.chunk.get_instructions();
             DynamicLen(instructions.len()).write(writer);
             writer.write_bytes(instructions);
-            match &entry.access {
-                Access::All { parameters } => {
-                    writer.write_u8(0);
-                    write_parameters(writer, parameters, version);
-                },
-                Access::Internal => writer.write_u8(1),
-                Access::Entry { parameters } => {
-                    writer.write_u8(2);
-                    write_parameters(writer, parameters, version);
-                },
-                Access::Hook { id } => {
-                    writer.write_u8(3);
-                    writer.write_u8(*id);
-                }
-            }
+            write_access(writer, &entry.access, version);
         }
     }
 
     fn read(reader: &mut Reader) -> Result<Module, ReaderError> {
-        let constants_len = DynamicLen::read(reader)?.0;
+        let constants_len = DynamicLen::read_bounded(reader)?;
         let mut constants = IndexSet::new();
 
         for _ in 0..constants_len {
+            let pos = reader.position();
             let c = ValueCell::read(reader)?;
             if !constants.insert(c) {
-                return Err(ReaderError::InvalidValue);
+                return Err(ReaderError::InvalidValue.at(pos));
             }
         }
 
@@ -459,45 +974,86 @@ impl Serializer for Module {
             .cloned()
             .unwrap_or(ContractVersion::V0);
 
-        // Function helper to read parameters based on contract version
-        fn read_parameters(reader: &mut Reader, version: ContractVersion) -> Result<Option<Vec<TypePacked>>, ReaderError> {
-            if version >= ContractVersion::V1 {
-                let params_len = Option::<u8>::read(reader)?;
-                if let Some(len) = params_len {
-                    let mut params = Vec::with_capacity(len as usize);
-                    for _ in 0..len {
-                        params.push(TypePacked::read(reader)?);
-                    }
+        // Function helper to read the access based on contract version
+        fn read_access(reader: &mut Reader, version: ContractVersion) -> Result<(u8, Option<Vec<TypePacked>>), ReaderError> {
+            if version < ContractVersion::V1 {
+                return Ok((reader.read_u8()?, None))
+            }
 
-                    return Ok(Some(params))
-                }
+            let (kind, has_parameters) = if version >= ContractVersion::V3 {
+                let mut bits = BitReader::read(reader)?;
+                let kind = bits.read_bits(2)?;
+                let has_parameters = bits.read_flag()?;
+                bits.finish()?;
+
+                (kind, has_parameters)
+            } else {
+                let kind = reader.read_u8()?;
+                // Only entries & public chunks have the parameters presence flag
+                let has_parameters = matches!(kind, 0 | 2) && reader.read_bool()?;
+
+                (kind, has_parameters)
+            };
+
+            if !has_parameters {
+                return Ok((kind, None))
+            }
+
+            // Only entries & public chunks have parameters
+            if kind != 0 && kind != 2 {
+                return Err(ReaderError::InvalidValue)
             }
 
-            Ok(None)
+            let len = reader.read_u8()?;
+            let mut params = Vec::with_capacity(len as usize);
+            for _ in 0..len {
+                params.push(TypePacked::read(reader)?);
+            }
+
+            Ok((kind, Some(params)))
         }
 
-        for i in 0..chunks_len {
-            let instructions_len = DynamicLen::read(reader)?.0;
+        // Function helper to read a chunk with its access
+        fn read_chunk(reader: &mut Reader, version: ContractVersion) -> Result<(ModuleChunk, Option<u8>), ReaderError> {
+            let instructions_len = DynamicLen::read_bounded(reader)?;
             let instructions = reader.read_bytes(instructions_len)?;
             let chunk = Chunk::from_instructions(instructions);
 
-            let access = match reader.read_u8()? {
-                0 => Access::All { parameters: read_parameters(reader, version)? },
-                1 => Access::Internal,
-                2 => Access::Entry { parameters: read_parameters(reader, version)? },
+            let (kind, parameters) = read_access(reader, version)?;
+            let (access, hook) = match kind {
+                0 => (Access::All { parameters }, None),
+                1 => (Access::Internal, None),
+                2 => (Access::Entry { parameters }, None),
                 3 => {
                     let id = reader.read_u8()?;
-
-                    hooks.insert(id, i as _);
-                    Access::Hook { id }
+                    (Access::Hook { id }, Some(id))
                 }
                 _ => return Err(ReaderError::InvalidValue)
             };
 
-            chunks.push(ModuleChunk { chunk, access });
+            Ok((ModuleChunk { chunk, access }, hook))
+        }
+
+        for i in 0..chunks_len {
+            // Errors are reported at the start of the chunk
+            let pos = reader.position();
+            let (chunk, hook) = read_chunk(reader, version)
+                .map_err(|e| e.at(pos))?;
+
+            if let Some(id) = hook {
+                hooks.insert(id, i as _);
+            }
+
+            chunks.push(chunk);
+        }
+
+        let module = Module::with(constants, chunks, hooks);
+        // Starting from V1, only the canonical form is accepted
+        if version >= ContractVersion::V1 {
+            module.verify_canonical()?;
         }
 
-        Ok(Module::with(constants, chunks, hooks))
+        Ok(module)
     }
 
     fn size(&self) -> usize {
@@ -526,7 +1082,14 @@ impl Serializer for Module {
 
 #[cfg(test)]
 mod tests {
-    use crate::crypto::Hash;
+    use std::{io::Cursor, sync::Arc};
+    use merlin::Transcript;
+    use crate::{
+        api::{DataElement, DataValue},
+        context::Context,
+        contract::ContractModule,
+        crypto::{elgamal::{KeyPair, PedersenOpening}, Hash}
+    };
     use super::*;
 
     #[test]
@@ -538,6 +1101,180 @@ mod tests {
         assert_eq!(hex.len() / 2, module.size());
     }
 
+    #[test]
+    fn test_serde_module_v3_packed_access() {
+        let chunks = vec![
+            ModuleChunk { chunk: Chunk::new(), access: Access::Internal },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Entry { parameters: Some(vec![TypePacked::Bool, TypePacked::String]) } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::All { parameters: None } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Hook { id: 0 } },
+        ];
+        let module = Module::with(IndexSet::new(), chunks, [(0, 3)].into_iter().collect());
+
+        let contract = ContractModule::new(ContractVersion::V3, Arc::new(module));
+        let bytes = contract.to_bytes();
+        assert_eq!(bytes.len(), contract.size());
+
+        let decoded = ContractModule::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.module.chunks().len(), 4);
+        assert!(matches!(&decoded.module.chunks()[1].access, Access::Entry { parameters: Some(params) } if params.len() == 2));
+        assert!(matches!(&decoded.module.chunks()[2].access, Access::All { parameters: None }));
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_serde_module_v1_access_layout() {
+        let chunks = vec![
+            ModuleChunk { chunk: Chunk::new(), access: Access::All { parameters: None } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Entry { parameters: Some(vec![TypePacked::Bool]) } },
+        ];
+        let module = Module::with(IndexSet::new(), chunks, IndexMap::new());
+
+        let contract = ContractModule::new(ContractVersion::V1, Arc::new(module));
+        let bytes = contract.to_bytes();
+        assert_eq!(bytes.len(), contract.size());
+
+        // Access kind followed by the parameters presence flag
+        // then the dependencies count and the upgrade authority
+        let access = &bytes[bytes.len() - 9..];
+        assert_eq!(access, &[0, 0, 0, 2, 1, 1, TypePacked::Bool.to_bytes()[0], 0, 0]);
+
+        let decoded = ContractModule::from_bytes(&bytes).unwrap();
+        assert!(matches!(&decoded.module.chunks()[0].access, Access::All { parameters: None }));
+        assert!(matches!(&decoded.module.chunks()[1].access, Access::Entry { parameters: Some(params) } if params.len() == 1));
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_serde_module_v2_same_as_v1() {
+        let chunks = vec![
+            ModuleChunk { chunk: Chunk::new(), access: Access::Entry { parameters: Some(vec![TypePacked::Bool]) } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Hook { id: 0 } },
+        ];
+        let module = Arc::new(Module::with(IndexSet::new(), chunks, [(0, 1)].into_iter().collect()));
+
+        let mut v1 = ContractModule::new(ContractVersion::V1, module.clone());
+        v1.dependencies.push(Hash::zero());
+        let mut v2 = ContractModule::new(ContractVersion::V2, module);
+        v2.dependencies.push(Hash::zero());
+
+        let v1_bytes = v1.to_bytes();
+        let v2_bytes = v2.to_bytes();
+        // Only the version byte differs
+        assert_eq!(v1_bytes[1..], v2_bytes[1..]);
+        assert_eq!(v2_bytes.len(), v2.size());
+
+        let decoded = ContractModule::from_bytes(&v2_bytes).unwrap();
+        assert_eq!(decoded.version, ContractVersion::V2);
+        assert_eq!(decoded.dependencies, vec![Hash::zero()]);
+        assert_eq!(decoded.to_bytes(), v2_bytes);
+    }
+
+    #[test]
+    fn test_serde_module_v3_packs_v2_access() {
+        let chunks = vec![
+            ModuleChunk { chunk: Chunk::new(), access: Access::Entry { parameters: Some(vec![TypePacked::Bool]) } },
+            ModuleChunk { chunk: Chunk::new(), access: Access::Hook { id: 0 } },
+        ];
+        let module = Arc::new(Module::with(IndexSet::new(), chunks, [(0, 1)].into_iter().collect()));
+
+        let mut v2 = ContractModule::new(ContractVersion::V2, module.clone());
+        v2.dependencies.push(Hash::zero());
+        let mut v3 = ContractModule::new(ContractVersion::V3, module);
+        v3.dependencies.push(Hash::zero());
+
+        let v2_bytes = v2.to_bytes();
+        let v3_bytes = v3.to_bytes();
+        // The parameters presence flag of the entry is packed with its access kind
+        // and that byte is used by the source hash option instead
+        assert_eq!(v3_bytes.len(), v2_bytes.len());
+        assert_eq!(v3_bytes.len(), v3.size());
+
+        let decoded = ContractModule::from_bytes(&v3_bytes).unwrap();
+        assert_eq!(decoded.version, ContractVersion::V3);
+        assert_eq!(decoded.dependencies, vec![Hash::zero()]);
+        assert_eq!(decoded.to_bytes(), v3_bytes);
+    }
+
+    #[test]
+    fn test_serde_module_context_version_mismatch() {
+        let chunks = vec![ModuleChunk { chunk: Chunk::new(), access: Access::All { parameters: None } }];
+        let module = Arc::new(Module::with(IndexSet::new(), chunks, IndexMap::new()));
+        let v0_bytes = ContractModule::new(ContractVersion::V0, module.clone()).to_bytes();
+
+        // The module is always read using its own version
+        let mut context = Context::new();
+        context.store(ContractVersion::V1);
+        let mut reader = Reader::with_context(&v0_bytes, context);
+        assert_eq!(ContractModule::read(&mut reader).unwrap().version, ContractVersion::V0);
+        assert_eq!(reader.size(), 0);
+        assert_eq!(reader.context().get_optional::<ContractVersion>(), Some(&ContractVersion::V1));
+
+        // And written using it too
+        let mut context = Context::new();
+        context.store(ContractVersion::V1);
+        let mut bytes = Vec::new();
+        let mut writer = Writer::with_context(&mut bytes, context);
+        ContractModule::new(ContractVersion::V0, module.clone()).write(&mut writer);
+        assert_eq!(writer.context().get_optional::<ContractVersion>(), Some(&ContractVersion::V1));
+        assert_eq!(bytes, v0_bytes);
+
+        // The version is scoped to each module
+        let mut bytes = v0_bytes.clone();
+        bytes.extend(ContractModule::new(ContractVersion::V1, module).to_bytes());
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(ContractModule::read(&mut reader).unwrap().version, ContractVersion::V0);
+        assert_eq!(ContractModule::read(&mut reader).unwrap().version, ContractVersion::V1);
+        assert!(reader.context().get_optional::<ContractVersion>().is_none());
+    }
+
+    #[test]
+    fn test_serde_module_v3_non_canonical_access() {
+        let chunks = vec![ModuleChunk { chunk: Chunk::new(), access: Access::Internal }];
+        let module = Module::with(IndexSet::new(), chunks, IndexMap::new());
+
+        let mut bytes = ContractModule::new(ContractVersion::V3, Arc::new(module)).to_bytes();
+        // Access byte is followed by the dependencies count, the upgrade authority and the source hash
+        let index = bytes.len() - 4;
+        assert_eq!(bytes[index], 1);
+
+        // Internal access cannot have parameters
+        bytes[index] = 0b0000_0101;
+        assert!(ContractModule::from_bytes(&bytes).is_err());
+
+        // Unused bits must be zero
+        bytes[index] = 0b1000_0001;
+        assert!(ContractModule::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_serde_module_v1_canonical_constants() {
+        let chunks = || vec![ModuleChunk { chunk: Chunk::new(), access: Access::Internal }];
+        let sorted: IndexSet<ValueCell> = [
+            Primitive::U64(1).into(),
+            Primitive::U64(2).into(),
+        ].into_iter().collect();
+        let unsorted: IndexSet<ValueCell> = sorted.iter().rev().cloned().collect();
+
+        let module = Module::with(sorted, chunks(), IndexMap::new());
+        assert!(module.verify_canonical().is_ok());
+
+        let bytes = ContractModule::new(ContractVersion::V1, Arc::new(module)).to_bytes();
+        assert!(ContractModule::from_bytes(&bytes).is_ok());
+
+        let module = Arc::new(Module::with(unsorted, chunks(), IndexMap::new()));
+        assert!(module.verify_canonical().is_err());
+
+        // Not enforced before V1
+        let bytes = ContractModule::new(ContractVersion::V0, module.clone()).to_bytes();
+        assert!(ContractModule::from_bytes(&bytes).is_ok());
+
+        // The reordered encoding of the same constants is rejected
+        let bytes = ContractModule::new(ContractVersion::V1, module).to_bytes();
+        assert!(ContractModule::from_bytes(&bytes).is_err());
+    }
+
     #[track_caller]
     fn test_serde_cell(cell: ValueCell) {
         let bytes = cell.to_bytes();
@@ -546,6 +1283,269 @@ mod tests {
         assert_eq!(v, cell);
     }
 
+    #[track_caller]
+    fn test_stream_cell(cell: ValueCell) {
+        let mut cursor = Cursor::new(Vec::new());
+        cell.write_to(&mut cursor).unwrap();
+
+        // Same encoding as the Serializer impl
+        let bytes = cursor.into_inner();
+        assert_eq!(bytes, cell.to_bytes());
+
+        let mut cursor = Cursor::new(bytes);
+        let v = ValueCell::read_from(&mut cursor).unwrap();
+        assert_eq!(v, cell);
+        assert_eq!(cursor.position() as usize, cell.size());
+    }
+
+    #[test]
+    fn test_stream_value_cell() {
+        test_stream_cell(ValueCell::Bytes(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]));
+        test_stream_cell(ValueCell::Bytes(vec![0; u16::MAX as usize + 10]));
+        test_stream_cell(ValueCell::Object(vec![
+            Primitive::U64(42).into(),
+            Primitive::U64(23).into(),
+            Primitive::U64(42).into(),
+            Primitive::U64(57).into(),
+            Primitive::U64(10).into()
+        ]));
+        test_stream_cell(ValueCell::Map(Box::new([
+            (Primitive::U64(42).into(), Primitive::String("Hello World!".to_owned()).into())
+        ].into_iter().collect())));
+    }
+
+    #[test]
+    fn test_stream_primitives() {
+        test_stream_cell(ValueCell::Object(vec![
+            Primitive::Null.into(),
+            Primitive::Boolean(true).into(),
+            Primitive::U8(255).into(),
+            Primitive::U16(65535).into(),
+            Primitive::U32(u32::MAX).into(),
+            Primitive::U64(u64::MAX).into(),
+            Primitive::U128(u128::MAX).into(),
+            Primitive::U256(U256::MAX).into(),
+            Primitive::String("test string".to_owned()).into(),
+            Primitive::Range(Box::new((Primitive::U64(0), Primitive::U64(100)))).into(),
+            Primitive::Opaque(OpaqueWrapper::new(Hash::zero())).into(),
+            Primitive::U8(1).into(),
+        ]));
+    }
+
+    #[test]
+    fn test_stream_opaque_primitives() {
+        let keypair = KeyPair::new();
+        let data = DataElement::Fields([
+            (DataValue::U64(1), DataElement::from("memo")),
+            (DataValue::String("tags".to_owned()), DataElement::Array(vec![DataElement::Value(DataValue::Blob(vec![1, 2, 3]))])),
+        ].into_iter().collect());
+
+        // Opaque types may not implement the equality, so compare their encoding
+        let cell = ValueCell::Object(vec![
+            Primitive::Opaque(OpaqueWrapper::new(keypair.get_public_key().to_address_with(false, data))).into(),
+            Primitive::Opaque(OpaqueWrapper::new(keypair.get_public_key().to_address(true))).into(),
+            Primitive::Opaque(OpaqueWrapper::new(keypair.sign(b"message"))).into(),
+            Primitive::U8(1).into(),
+        ]);
+        let bytes = cell.to_bytes();
+        let mut cursor = Cursor::new(bytes.clone());
+        assert_eq!(ValueCell::read_from(&mut cursor).unwrap().to_bytes(), bytes);
+        assert_eq!(cursor.position() as usize, bytes.len());
+
+        // The proof layout depends on the tx version in the context
+        let proof = CiphertextValidityProof::new(
+            keypair.get_public_key(),
+            keypair.get_public_key(),
+            10,
+            &PedersenOpening::generate_new(),
+            TxVersion::V0,
+            &mut Transcript::new(b"test"),
+        );
+        let cell = ValueCell::Primitive(Primitive::Opaque(OpaqueWrapper::new(proof)));
+        let bytes = cell.to_bytes();
+
+        let mut context = Context::new();
+        context.store(TxVersion::V0);
+        let mut cursor = Cursor::new(bytes.clone());
+        assert_eq!(ValueCell::read_from_with_context(&mut cursor, context).unwrap().to_bytes(), bytes);
+        assert_eq!(cursor.position() as usize, bytes.len());
+
+        // Without it, the Y_2 point is expected
+        assert!(ValueCell::read_from(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_stream_deep_nesting_exceeds_limit() {
+        let mut cell = ValueCell::Primitive(Primitive::U64(42));
+        for _ in 0..17 {
+            cell = ValueCell::Object(vec![cell.into()]);
+        }
+
+        let bytes = cell.to_bytes();
+        assert!(ValueCell::read_from(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn test_stream_truncated() {
+        let cell = ValueCell::Object(vec![
+            Primitive::String("hello".to_owned()).into(),
+            ValueCell::Bytes(vec![1, 2, 3]).into(),
+        ]);
+
+        let bytes = cell.to_bytes();
+        for len in 0..bytes.len() {
+            assert!(ValueCell::read_from(&mut Cursor::new(&bytes[..len])).is_err());
+        }
+    }
+
+    #[test]
+    fn test_deposit_json_schema_is_tagged() {
+        let schema = schemars::schema_for!(ContractDeposit);
+        let variants = schema.as_value()
+            .get("oneOf")
+            .and_then(|v| v.as_array())
+            .expect("deposit variants");
+
+        // Each variant is externally tagged with its snake_case name
+        let variant = |tag: &str| variants.iter()
+            .find(|v| v["required"].as_array().is_some_and(|r| r.len() == 1 && r[0] == tag))
+            .unwrap_or_else(|| panic!("missing {} variant", tag));
+
+        assert_eq!(variants.len(), 2);
+        assert!(variant("public")["properties"]["public"].is_object());
+
+        let private = &variant("private")["properties"]["private"];
+        let mut required: Vec<&str> = private["required"].as_array()
+            .expect("private required fields")
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        required.sort();
+
+        assert_eq!(required, ["commitment", "ct_validity_proof", "receiver_handle", "sender_handle"]);
+    }
+
+    #[test]
+    fn test_deposit_read_header() {
+        let source = KeyPair::new();
+        let contract = KeyPair::new();
+        let inputs = [PrivateDepositInput {
+            asset: Hash::zero(),
+            amount: 100,
+            opening: PedersenOpening::generate_new(),
+        }];
+        let deposit = ContractDeposit::aggregate_private(
+            &inputs,
+            source.get_public_key(),
+            contract.get_public_key(),
+            TxVersion::V3,
+            &mut Transcript::new(b"test"),
+        ).unwrap().deposit;
+
+        // Prefix it to ensure the offset is relative to the buffer
+        let mut bytes = vec![0xFF; 3];
+        deposit.write(&mut Writer::new(&mut bytes));
+
+        let mut reader = Reader::new(&bytes);
+        reader.skip(3).unwrap();
+        let header = ContractDeposit::read_header(&mut reader).unwrap();
+        assert_eq!(reader.size(), 0);
+
+        let ContractDepositHeader::Private { ct_validity_proof, .. } = &header else {
+            panic!("expected a private deposit header");
+        };
+        assert_eq!(ct_validity_proof.offset, 3 + 1 + 32 * 3);
+
+        let decoded = header.into_deposit(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), deposit.to_bytes());
+
+        // Public deposits have no proof
+        let header = ContractDeposit::read_header(&mut Reader::new(&ContractDeposit::Public(42).to_bytes())).unwrap();
+        assert!(matches!(header.into_deposit(&[]), Ok(ContractDeposit::Public(42))));
+    }
+
+    // Build a truncated buffer declaring a u32::MAX length after the prefix
+    fn huge_len_bytes(prefix: &[u8]) -> Vec<u8> {
+        let mut bytes = prefix.to_vec();
+        DynamicLen(u32::MAX as usize).write(&mut Writer::new(&mut bytes));
+        bytes.extend_from_slice(&[0u8; 8]);
+        bytes
+    }
+
+    #[test]
+    fn test_serde_huge_len_rejected() {
+        // String primitive, bytes & object
+        for prefix in [&[0u8, 8][..], &[1], &[2], &[3]] {
+            let bytes = huge_len_bytes(prefix);
+            assert!(matches!(ValueCell::from_bytes(&bytes), Err(e) if matches!(e.kind(), ReaderError::InvalidSize)));
+            assert!(ValueCell::read_from(&mut Cursor::new(&bytes)).is_err());
+        }
+
+        // Module constants
+        let bytes = huge_len_bytes(&[]);
+        assert!(matches!(Module::from_bytes(&bytes), Err(e) if matches!(e.kind(), ReaderError::InvalidSize)));
+
+        // Module instructions
+        let bytes = huge_len_bytes(&[0, 0, 1]);
+        assert!(matches!(Module::from_bytes(&bytes), Err(e) if matches!(e.kind(), ReaderError::InvalidSize)));
+    }
+
+    #[test]
+    fn test_serde_module_error_position() {
+        let first = ValueCell::Primitive(Primitive::U64(1));
+        let second = ValueCell::Object(vec![Primitive::U8(2).into(), Primitive::U8(3).into()]);
+        let constants = IndexSet::from([first.clone(), second.clone()]);
+        let chunks = vec![ModuleChunk { chunk: Chunk::new(), access: Access::All { parameters: None } }];
+        let module = Module::with(constants, chunks, IndexMap::new());
+        let bytes = module.to_bytes();
+
+        // Corrupt the tag of the last value inside the second constant
+        // constants len + first constant + object tag & len + first value
+        let offset = 1 + first.size() + 2 + ValueCell::Primitive(Primitive::U8(2)).size();
+        let mut corrupted = bytes.clone();
+        corrupted[offset] = 0xFF;
+
+        let err = Module::from_bytes(&corrupted).unwrap_err();
+        assert_eq!(err.position(), Some(offset));
+        assert!(matches!(err.kind(), ReaderError::InvalidValue));
+
+        // Invalid access kind is reported at the start of the chunk
+        let chunk_offset = 1 + first.size() + second.size() + 2;
+        let mut corrupted = bytes.clone();
+        corrupted[chunk_offset + 1] = 0xFF;
+
+        let err = Module::from_bytes(&corrupted).unwrap_err();
+        assert_eq!(err.position(), Some(chunk_offset));
+        assert!(matches!(err.kind(), ReaderError::InvalidValue));
+    }
+
+    // Large object with a value deep inside
+    fn deep_cell(leaf: Primitive) -> SizedValueCell {
+        let mut cell = ValueCell::Object((0..64).map(|_| leaf.clone().into()).collect());
+        for _ in 0..8 {
+            cell = ValueCell::Object(vec![cell.into(), ValueCell::Bytes(vec![0; 32]).into()]);
+        }
+        SizedValueCell::new(cell)
+    }
+
+    #[test]
+    fn test_value_cell_fast_eq() {
+        let a = deep_cell(Primitive::U64(1));
+        let b = deep_cell(Primitive::U64(1));
+        assert!(a.fast_eq(&b));
+
+        // Different sizes
+        let c = deep_cell(Primitive::U8(1));
+        assert_ne!(a.size(), c.size());
+        assert_eq!(c.size(), c.get().size());
+        assert!(!a.fast_eq(&c));
+
+        // Same size, full comparison is required
+        let d = deep_cell(Primitive::U64(2));
+        assert_eq!(a.size(), d.size());
+        assert!(!a.fast_eq(&d));
+    }
+
     #[test]
     fn test_serde_primitive() {
         test_serde_cell(ValueCell::Primitive(Primitive::Null));
@@ -700,6 +1700,28 @@ mod tests {
         assert!(ValueCell::from_bytes(&bytes).is_err());
     }
 
+    #[test]
+    fn test_serde_deep_nesting_with_depth_override() {
+        let mut cell = ValueCell::Primitive(Primitive::U64(42));
+        for _ in 0..32 {
+            cell = ValueCell::Object(vec![cell.into()]);
+        }
+        let bytes = cell.to_bytes();
+
+        // Default limit is kept
+        assert!(ValueCell::from_bytes(&bytes).is_err());
+
+        let v = ValueCell::read_with_depth(&mut Reader::new(&bytes), 32).unwrap();
+        assert_eq!(v, cell);
+        assert!(ValueCell::read_with_depth(&mut Reader::new(&bytes), 31).is_err());
+
+        // Override through the reader context
+        let mut context = Context::new();
+        context.store(ValueCellMaxDepth(32));
+        let v = ValueCell::read(&mut Reader::with_context(&bytes, context)).unwrap();
+        assert_eq!(v, cell);
+    }
+
     #[test]
     fn test_serde_deep_map_nesting() {
         // Deeply nested maps (within 16 depth limit)
@@ -67,3 +67,95 @@ impl Serializer for MultiSigPayload {
         1 + 1 + self.participants.iter().map(|p| p.size()).sum::<usize>()
     }
 }
+
+// MultiSigUpdatePayload is a delta applied on the current multisig setup of an account
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+pub struct MultiSigUpdatePayload {
+    // Participants to add to the current setup
+    pub add: IndexSet<CompressedPublicKey>,
+    // Participants to remove from the current setup
+    pub remove: IndexSet<CompressedPublicKey>,
+    // New threshold to use, if not set, the current one is kept
+    pub new_threshold: Option<u8>,
+}
+
+impl MultiSigUpdatePayload {
+    // Apply the delta on the current multisig setup
+    // Returns None if a removed participant is not present
+    // or if an added participant is already present
+    pub fn apply_to(&self, current: &MultiSigPayload) -> Option<MultiSigPayload> {
+        let mut participants = current.participants.clone();
+        for key in &self.remove {
+            // Keep the order of the remaining participants
+            // as the signatures ids are based on it
+            if !participants.shift_remove(key) {
+                return None;
+            }
+        }
+
+        for key in &self.add {
+            if !participants.insert(key.clone()) {
+                return None;
+            }
+        }
+
+        Some(MultiSigPayload {
+            threshold: self.new_threshold.unwrap_or(current.threshold),
+            participants
+        })
+    }
+}
+
+fn write_keys(writer: &mut Writer, keys: &IndexSet<CompressedPublicKey>) {
+    writer.write_u8(keys.len() as u8);
+    for key in keys {
+        key.write(writer);
+    }
+}
+
+fn read_keys(reader: &mut Reader) -> Result<IndexSet<CompressedPublicKey>, ReaderError> {
+    let len = reader.read_u8()?;
+    if len > MAX_MULTISIG_PARTICIPANTS as u8 {
+        return Err(ReaderError::InvalidSize)
+    }
+
+    let mut keys = IndexSet::new();
+    for _ in 0..len {
+        if !keys.insert(CompressedPublicKey::read(reader)?) {
+            return Err(ReaderError::InvalidValue)
+        }
+    }
+
+    Ok(keys)
+}
+
+impl Serializer for MultiSigUpdatePayload {
+    fn write(&self, writer: &mut Writer) {
+        write_keys(writer, &self.add);
+        write_keys(writer, &self.remove);
+        self.new_threshold.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<MultiSigUpdatePayload, ReaderError> {
+        let add = read_keys(reader)?;
+        let remove = read_keys(reader)?;
+        let new_threshold = Option::read(reader)?;
+
+        // An empty update is useless
+        if add.is_empty() && remove.is_empty() && new_threshold.is_none() {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(MultiSigUpdatePayload {
+            add,
+            remove,
+            new_threshold
+        })
+    }
+
+    fn size(&self) -> usize {
+        1 + self.add.iter().map(|p| p.size()).sum::<usize>()
+        + 1 + self.remove.iter().map(|p| p.size()).sum::<usize>()
+        + self.new_threshold.size()
+    }
+}
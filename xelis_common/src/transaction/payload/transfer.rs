@@ -68,6 +68,13 @@ impl TransferPayload {
         &self.extra_data
     }
 
+    // Does this transfer carry extra data
+    // Only the presence is checked, nothing is decrypted
+    #[inline]
+    pub fn has_extra_data(&self) -> bool {
+        self.extra_data.is_some()
+    }
+
     // Get the ciphertext commitment
     #[inline]
     pub fn get_commitment(&self) -> &CompressedCommitment {
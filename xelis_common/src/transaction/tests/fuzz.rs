@@ -0,0 +1,216 @@
+use curve25519_dalek::Scalar;
+use indexmap::{IndexMap, IndexSet};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use xelis_vm::{Chunk, Module, Primitive, ValueCell};
+use crate::{
+    api::{DataElement, DataValue},
+    config::{COIN_VALUE, XELIS_ASSET},
+    contract::ContractVersion,
+    crypto::{Address, Hash, Hashable, KeyPair, PrivateKey},
+    serializer::Serializer,
+    transaction::{
+        builder::{
+            ContractDepositBuilder,
+            DeployContractBuilder,
+            DeployContractInvokeBuilder,
+            FeeBuilder,
+            InvokeContractBuilder,
+            MultiSigBuilder,
+            MultiSigUpdateBuilder,
+            TransactionBuilder,
+            TransactionTypeBuilder,
+            TransferBuilder
+        },
+        BurnPayload,
+        Reference,
+        Transaction,
+        TxVersion
+    }
+};
+use super::{Account, AccountStateImpl};
+
+// Fixed seed for the keys and payloads so a failing case can be replayed
+// Proof openings are still random and don't affect the encoded layout
+const SEED: u64 = 0x5845_4c49_53;
+// Transactions generated per transaction type
+const ROUNDS: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Transfers,
+    Burn,
+    MultiSig,
+    MultiSigUpdate,
+    InvokeContract,
+    DeployContract,
+}
+
+impl Kind {
+    // Lowest version able to build this kind of transaction
+    fn min_version(&self) -> TxVersion {
+        match self {
+            Self::Transfers | Self::Burn => TxVersion::V0,
+            Self::MultiSig => TxVersion::V1,
+            Self::InvokeContract | Self::DeployContract => TxVersion::V2,
+            Self::MultiSigUpdate => TxVersion::V3,
+        }
+    }
+}
+
+fn random_version(rng: &mut StdRng, min: TxVersion) -> TxVersion {
+    let min: u8 = min.into();
+    TxVersion::try_from(rng.gen_range(min..=3)).unwrap()
+}
+
+// Derive the account keys from the seeded rng
+fn random_account(rng: &mut StdRng) -> Account {
+    Account {
+        balances: Default::default(),
+        keypair: KeyPair::from_private_key(PrivateKey::from_scalar(Scalar::random(rng))),
+        nonce: 0,
+    }
+}
+
+fn random_addresses(rng: &mut StdRng, min: usize, max: usize) -> IndexSet<Address> {
+    (0..rng.gen_range(min..=max))
+        .map(|_| random_account(rng).address())
+        .collect()
+}
+
+fn random_deposits(rng: &mut StdRng, assets: &[Hash]) -> IndexMap<Hash, ContractDepositBuilder> {
+    let mut deposits = IndexMap::new();
+    for asset in assets {
+        if rng.gen_bool(0.5) {
+            deposits.insert(asset.clone(), ContractDepositBuilder {
+                amount: rng.gen_range(1..=COIN_VALUE),
+                private: false
+            });
+        }
+    }
+    deposits
+}
+
+fn random_module(rng: &mut StdRng) -> Module {
+    let mut module = Module::new();
+    for _ in 0..rng.gen_range(1..=3) {
+        module.add_entry_chunk(Chunk::new(), None);
+    }
+    module
+}
+
+// Generate a random valid builder data for the given kind
+fn random_builder_data(rng: &mut StdRng, kind: Kind, assets: &[Hash]) -> TransactionTypeBuilder {
+    match kind {
+        Kind::Transfers => TransactionTypeBuilder::Transfers(
+            (0..rng.gen_range(1..=4))
+                .map(|_| TransferBuilder {
+                    amount: rng.gen_range(1..=COIN_VALUE),
+                    destination: random_account(rng).address(),
+                    asset: assets[rng.gen_range(0..assets.len())].clone(),
+                    extra_data: rng.gen_bool(0.5)
+                        .then(|| DataElement::Value(DataValue::U64(rng.gen()))),
+                    encrypt_extra_data: true,
+                })
+                .collect()
+        ),
+        Kind::Burn => TransactionTypeBuilder::Burn(BurnPayload {
+            amount: rng.gen_range(1..=COIN_VALUE),
+            asset: assets[rng.gen_range(0..assets.len())].clone(),
+        }),
+        Kind::MultiSig => {
+            let participants = random_addresses(rng, 1, 4);
+            let threshold = rng.gen_range(1..=participants.len() as u8);
+            TransactionTypeBuilder::MultiSig(MultiSigBuilder {
+                participants,
+                threshold,
+            })
+        },
+        Kind::MultiSigUpdate => {
+            let add = random_addresses(rng, 0, 2);
+            let remove = random_addresses(rng, 0, 2);
+            // An update must change at least one thing
+            let new_threshold = if add.is_empty() && remove.is_empty() || rng.gen_bool(0.5) {
+                Some(rng.gen_range(1..=3))
+            } else {
+                None
+            };
+
+            TransactionTypeBuilder::MultiSigUpdate(MultiSigUpdateBuilder {
+                add,
+                remove,
+                new_threshold,
+            })
+        },
+        Kind::InvokeContract => TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+            contract: Hash::new(rng.gen()),
+            max_gas: rng.gen_range(1..=1000),
+            entry_id: rng.gen_range(0..4),
+            parameters: (0..rng.gen_range(0..=3))
+                .map(|_| ValueCell::Primitive(Primitive::U64(rng.gen())))
+                .collect(),
+            deposits: random_deposits(rng, assets),
+            permission: Default::default(),
+        }),
+        Kind::DeployContract => TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+            contract_version: ContractVersion::V1,
+            module: random_module(rng).to_hex(),
+            dependencies: (0..rng.gen_range(0..=2))
+                .map(|_| Hash::new(rng.gen()))
+                .collect(),
+            upgradeable: rng.gen_bool(0.5),
+            upgrade: None,
+            source_hash: None,
+            invoke: rng.gen_bool(0.5)
+                .then(|| DeployContractInvokeBuilder {
+                    max_gas: rng.gen_range(1..=1000),
+                    deposits: random_deposits(rng, assets),
+                }),
+        }),
+    }
+}
+
+// Check that the transaction survives a serialization roundtrip unchanged
+fn assert_roundtrip(tx: &Transaction) {
+    let bytes = tx.to_bytes();
+    assert_eq!(tx.size(), bytes.len());
+
+    let decoded = Transaction::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.size(), bytes.len());
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.hash(), tx.hash());
+}
+
+#[test]
+fn test_fuzz_transaction_serialization() {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let assets = [XELIS_ASSET, Hash::new([1u8; 32])];
+
+    for kind in [Kind::Transfers, Kind::Burn, Kind::MultiSig, Kind::MultiSigUpdate, Kind::InvokeContract, Kind::DeployContract] {
+        for _ in 0..ROUNDS {
+            let mut alice = random_account(&mut rng);
+            for asset in assets.iter() {
+                alice.set_balance(asset.clone(), 1000 * COIN_VALUE);
+            }
+
+            let mut state = AccountStateImpl {
+                balances: alice.balances.clone(),
+                nonce: alice.nonce,
+                last_balance_topoheight: None,
+                reference: Reference {
+                    topoheight: 0,
+                    hash: Hash::zero(),
+                },
+            };
+
+            let version = random_version(&mut rng, kind.min_version());
+            let data = random_builder_data(&mut rng, kind, &assets);
+            let builder = TransactionBuilder::new(version, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+            let estimated_size = builder.estimate_size();
+            let tx = builder.build(&mut state, &alice.keypair)
+                .unwrap_or_else(|e| panic!("failed to build {:?} at {:?}: {}", kind, version, e));
+
+            assert_eq!(tx.size(), estimated_size, "{:?} at {:?}", kind, version);
+            assert_roundtrip(&tx);
+        }
+    }
+}
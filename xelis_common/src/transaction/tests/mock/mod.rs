@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::{HashMap, VecDeque, hash_map::Entry}, sync::Arc};
+use std::{borrow::Cow, collections::{HashMap, HashSet, VecDeque, hash_map::Entry}, sync::Arc};
 use anyhow::Context;
 use async_trait::async_trait;
 use curve25519_dalek::{ristretto::CompressedRistretto, traits::Identity};
@@ -60,10 +60,13 @@ pub struct MockChainState {
     pub events: VecDeque<CallbackEvent>,
     pub events_listeners: HashMap<(Hash, u64), Vec<(Hash, EventCallbackRegistration)>>,
     pub accounts: HashMap<PublicKey, MockAccount>,
+    // Plaintext balances known for the early solvency check
+    pub plaintext_balances: HashMap<(PublicKey, Hash), u64>,
     pub multisig: HashMap<PublicKey, MultiSigPayload>,
     pub contracts: HashMap<Cow<'static, Hash>, Option<(VersionedState, Option<Cow<'static, ContractModule>>)>>,
     pub contract_logs: HashMap<Hash, Vec<ContractLog>>,
     pub burned_coins: HashMap<Hash, u64>,
+    pub applied_txs: HashSet<Hash>,
     pub gas_fee: u64,
     pub burned_fee: u64,
     pub env: Arc<EnvironmentBuilder<'static, ContractMetadata>>,
@@ -73,46 +76,69 @@ pub struct MockChainState {
     pub block: Block,
     pub contract_caches: HashMap<Hash, ContractCache>,
     pub executions: ExecutionsChanges,
+    // Contracts modules available in the storage but not loaded yet
+    pub stored_contracts: HashMap<Hash, ContractModule>,
+    // Count the modules loaded from the storage
+    pub module_loads: usize,
+}
+
+// Create an empty block with the requested version
+fn create_block(version: BlockVersion) -> Block {
+    let header = BlockHeader::new(
+        version,
+        0,
+        0,
+        IndexSet::new(),
+        [0u8; EXTRA_NONCE_SIZE],
+        CompressedPublicKey::new(CompressedRistretto::identity()),
+        IndexSet::new(),
+    );
+
+    Block::new(header, Vec::new())
 }
 
 impl MockChainState {
     pub fn new() -> Self {
-        let header = BlockHeader::new(
-            BlockVersion::V3,
-            0,
-            0,
-            IndexSet::new(),
-            [0u8; EXTRA_NONCE_SIZE],
-            CompressedPublicKey::new(CompressedRistretto::identity()),
-            IndexSet::new(),
-        );
-
         Self {
             assets: HashMap::new(),
             tracker: Default::default(),
             events: VecDeque::new(),
             events_listeners: HashMap::new(),
             accounts: HashMap::new(),
+            plaintext_balances: HashMap::new(),
             multisig: HashMap::new(),
             contracts: HashMap::new(),
             contract_logs: HashMap::new(),
             burned_coins: HashMap::new(),
+            applied_txs: HashSet::new(),
             gas_fee: 0,
             burned_fee: 0,
             env: Arc::new(build_environment::<MockStorageProvider>(ContractVersion::V1)),
             provider: MockStorageProvider::default(),
             mainnet: false,
             block_hash: Hash::zero(),
-            block: Block::new(header, Vec::new()),
+            block: create_block(BlockVersion::V3),
             contract_caches: HashMap::new(),
             executions: ExecutionsChanges::default(),
+            stored_contracts: HashMap::new(),
+            module_loads: 0,
         }
     }
 
+    // Change the version of the block in which the verification happens
+    pub fn set_block_version(&mut self, version: BlockVersion) {
+        self.block = create_block(version);
+    }
+
     pub async fn on_post_execution(&mut self, caller: &Hash) -> Result<(), anyhow::Error> {
         while let Some(event) = self.events.pop_front() {
             let contract_key = (event.contract.clone(), event.event_id);
-            if let Some(listeners) = self.events_listeners.remove(&contract_key) {
+            if let Some(mut listeners) = self.events_listeners.remove(&contract_key) {
+                // Same dispatch order as the daemon: by listener contract hash starting V6
+                if self.get_block_version() >= BlockVersion::V6 {
+                    listeners.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+
                 for (contract, callback) in listeners {
                     if !self.load_contract_module(Cow::Owned(contract.clone())).await? {
                         // for tests, we directly return an error
@@ -171,6 +197,13 @@ impl MockChainState {
             .unwrap_or(0)
     }
 
+    // Approximate memory used by all the contract caches
+    pub fn total_cache_memory(&self) -> usize {
+        self.contract_caches.values()
+            .map(ContractCache::approx_memory_bytes)
+            .sum()
+    }
+
     pub fn set_account_balance(&mut self, account: &PublicKey, asset: &Hash, balance: Ciphertext) {
         let acct_state = self.accounts.entry(account.clone())
             .or_insert_with(|| MockAccount {
@@ -199,6 +232,11 @@ impl MockChainState {
             .map(|m| m.as_ref())
     }
 
+    // Store the module without loading it in the cache
+    pub fn internal_store_contract_module(&mut self, hash: Hash, module: ContractModule) {
+        self.stored_contracts.insert(hash, module);
+    }
+
     pub fn internal_set_contract_module(&mut self, hash: Hash, module: ContractModule) {
         self.contracts.insert(
             Cow::Owned(hash),
@@ -246,6 +284,14 @@ impl<'a> BlockchainVerificationState<'a, anyhow::Error> for MockChainState {
             .context("Sender account or balance not found")
     }
 
+    async fn get_sender_plaintext_balance(
+        &mut self,
+        account: &'a PublicKey,
+        asset: &'a Hash,
+    ) -> Result<Option<u64>,  anyhow::Error> {
+        Ok(self.plaintext_balances.get(&(account.clone(), asset.clone())).copied())
+    }
+
     /// Apply new output to a sender account
     async fn add_sender_output(
         &mut self,
@@ -278,7 +324,7 @@ impl<'a> BlockchainVerificationState<'a, anyhow::Error> for MockChainState {
     }
 
     fn get_block_version(&self) -> BlockVersion {
-        BlockVersion::V0
+        self.block.get_header().get_version()
     }
 
     async fn set_multisig_state(
@@ -324,11 +370,42 @@ impl<'a> BlockchainVerificationState<'a, anyhow::Error> for MockChainState {
         Ok(())
     }
 
+    async fn upgrade_contract_module(
+        &mut self,
+        hash: &'a Hash,
+        module: &'a ContractModule,
+    ) -> Result<(),  anyhow::Error> {
+        let (state, m) = self.contracts.get_mut(hash)
+            .and_then(Option::as_mut)
+            .filter(|(_, m)| m.is_some())
+            .context("Contract module not found")?;
+
+        state.mark_updated();
+        *m = Some(Cow::Owned(module.clone()));
+
+        Ok(())
+    }
+
     async fn load_contract_module(
         &mut self,
         hash: Cow<'a, Hash>
     ) -> Result<bool,  anyhow::Error> {
-        Ok(self.contracts.contains_key(&hash))
+        if self.contracts.contains_key(&hash) {
+            return Ok(true);
+        }
+
+        match self.stored_contracts.get(hash.as_ref()) {
+            Some(module) => {
+                self.module_loads += 1;
+                self.contracts.insert(
+                    Cow::Owned(hash.into_owned()),
+                    Some((VersionedState::FetchedAt(0), Some(Cow::Owned(module.clone()))))
+                );
+
+                Ok(true)
+            },
+            None => Ok(false)
+        }
     }
 
     async fn get_contract_module_with_environment(
@@ -338,6 +415,16 @@ impl<'a> BlockchainVerificationState<'a, anyhow::Error> for MockChainState {
         let module = self.internal_load_contract_module(contract)?;
         Ok((&module.module, self.env.environment()))
     }
+
+    async fn get_contract_upgrade_authority(
+        &mut self,
+        hash: &'a Hash
+    ) -> Result<Option<CompressedPublicKey>,  anyhow::Error> {
+        Ok(self.contracts.get(hash)
+            .and_then(|v| v.as_ref())
+            .and_then(|(_, m)| m.as_ref())
+            .and_then(|m| m.upgrade_authority.clone()))
+    }
 }
 
 #[async_trait]
@@ -540,6 +627,10 @@ impl<'a> BlockchainApplyState<'a, MockStorageProvider,  anyhow::Error> for MockC
         Ok(())
     }
 
+    async fn get_burned_coins(&self, asset: &Hash) -> Result<u64,  anyhow::Error> {
+        Ok(self.burned_coins.get(asset).copied().unwrap_or(0))
+    }
+
     async fn add_gas_fee(&mut self, amount: u64) -> Result<(),  anyhow::Error> {
         self.gas_fee += amount;
         Ok(())
@@ -550,6 +641,15 @@ impl<'a> BlockchainApplyState<'a, MockStorageProvider,  anyhow::Error> for MockC
         Ok(())
     }
 
+    async fn is_tx_applied(&self, hash: &Hash) -> Result<bool,  anyhow::Error> {
+        Ok(self.applied_txs.contains(hash))
+    }
+
+    async fn mark_tx_applied(&mut self, hash: &Hash) -> Result<(),  anyhow::Error> {
+        self.applied_txs.insert(hash.clone());
+        Ok(())
+    }
+
     fn is_mainnet(&self) -> bool {
         self.mainnet
     }
@@ -13,7 +13,8 @@ use crate::{
         ContractProvider,
         ContractStorage,
     },
-    crypto::Hash
+    crypto::Hash,
+    versioned_type::Versioned
 };
 
 
@@ -35,6 +36,13 @@ impl ContractStorage for MockStorageProvider {
     async fn has_contract(&self, contract: &Hash, _: TopoHeight) -> Result<bool, anyhow::Error> {
         Ok(self.data.keys().any(|(c, _)| c == contract))
     }
+
+    async fn commit_data_batch(&mut self, contract: &Hash, topoheight: TopoHeight, writes: Vec<(ValueCell, Versioned<Option<ValueCell>>)>) -> Result<(), anyhow::Error> {
+        for (key, version) in writes {
+            self.data.insert((contract.clone(), key), (topoheight, version.take()));
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
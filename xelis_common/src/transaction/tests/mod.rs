@@ -7,11 +7,12 @@ use xelis_vm::{Chunk, Module};
 use crate::{
     account::{CiphertextCache, Nonce},
     api::{DataElement, DataValue},
-    config::{BURN_PER_CONTRACT, COIN_VALUE, XELIS_ASSET},
-    contract::ContractModule,
+    block::TopoHeight,
+    config::{BURN_PER_CONTRACT, COIN_VALUE, MAX_TRANSACTION_SIZE, XELIS_ASSET},
+    contract::{ContractModule, ContractVersion},
     crypto::{
         elgamal::{Ciphertext, PedersenOpening},
-        proofs::{G, ProofVerificationError},
+        proofs::{G, ProofGenerationError, ProofVerificationError},
         Address,
         Hash,
         Hashable,
@@ -27,17 +28,20 @@ use crate::{
             DeployContractInvokeBuilder,
             FeeBuilder,
             FeeHelper,
+            GenerationError,
             InvokeContractBuilder,
             MultiSigBuilder,
+            MultiSigUpdateBuilder,
             TransactionBuilder,
             TransactionTypeBuilder,
-            TransferBuilder
+            TransferBuilder,
+            SIZE_COMPUTATIONS
         },
         extra_data::{
             derive_shared_key_from_opening,
             PlaintextData
         },
-        verify::{NoZKPCache, VerificationError, ZKPCache},
+        verify::{BlockchainVerificationState, NoZKPCache, VerificationConfig, VerificationError, ZKPCache},
         BurnPayload,
         MultiSigPayload,
         Reference,
@@ -50,6 +54,7 @@ use crate::{
 };
 
 mod mock;
+mod fuzz;
 
 pub use mock::*;
 
@@ -88,16 +93,19 @@ impl Account {
     }
 }
 
+#[derive(Clone)]
 pub struct AccountStateImpl {
     pub balances: HashMap<Hash, Balance>,
     pub reference: Reference,
     pub nonce: Nonce,
+    pub last_balance_topoheight: Option<TopoHeight>,
 }
 
-fn create_tx_for(account: Account, destination: Address, amount: u64, extra_data: Option<DataElement>) -> Arc<Transaction> {
+pub fn create_tx_for(account: Account, destination: Address, amount: u64, extra_data: Option<DataElement>) -> Arc<Transaction> {
     let mut state = AccountStateImpl {
         balances: account.balances,
         nonce: account.nonce,
+        last_balance_topoheight: None,
         reference: Reference {
             topoheight: 0,
             hash: Hash::zero(),
@@ -180,6 +188,64 @@ fn test_encrypt_decrypt_two_parties() {
     }
 }
 
+#[tokio::test]
+async fn test_self_memo() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 0);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount: 50,
+        destination: bob.address(),
+        asset: XELIS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]);
+
+    let memo = DataElement::Value(DataValue::String("invoice #42".to_string()));
+
+    // Memo to self is rejected before V3
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data.clone(), FeeBuilder::default())
+        .with_self_memo(memo.clone());
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::SelfMemoNotSupported)));
+
+    let builder = TransactionBuilder::new(TxVersion::V3, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default())
+        .with_self_memo(memo.clone());
+    let estimated_size = builder.estimate_size();
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+    assert_eq!(estimated_size, tx.size());
+
+    // The memo is kept through the serialization
+    let tx = Transaction::from_bytes(&tx.to_bytes()).unwrap();
+    assert!(tx.get_self_memo().is_some());
+
+    // Only the source can decrypt it
+    let plaintext = tx.decrypt_self_memo(&alice.keypair.get_private_key()).unwrap();
+    assert_eq!(DataElement::from_bytes(&plaintext.0).unwrap(), memo);
+    assert!(tx.decrypt_self_memo(&bob.keypair.get_private_key()).is_none());
+
+    // The memo is covered by the signature
+    let mut chain_state = create_chain_state_for(&alice);
+    chain_state.accounts.insert(bob.keypair.get_public_key().compress(), MockAccount {
+        balances: HashMap::from([(XELIS_ASSET, bob.balances[&XELIS_ASSET].ciphertext.clone().take_ciphertext().unwrap())]),
+        nonce: bob.nonce,
+    });
+
+    let hash = tx.hash();
+    tx.verify(&hash, &mut chain_state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+}
+
 #[tokio::test]
 async fn test_tx_verify() {
     let mut alice = Account::new();
@@ -217,7 +283,7 @@ async fn test_tx_verify() {
     }
 
     let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
 
     // Check Bob balance
     let balance = bob.keypair.decrypt_to_point(&state.accounts[&bob.keypair.get_public_key().compress()].balances[&XELIS_ASSET]);    
@@ -228,6 +294,193 @@ async fn test_tx_verify() {
     assert_eq!(balance, Scalar::from((100u64 * COIN_VALUE) - (50 + tx.fee)) * (*G));
 }
 
+#[tokio::test]
+async fn test_tx_double_apply_rejected() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 0);
+
+    let tx = create_tx_for(alice.clone(), bob.address(), 50, None);
+
+    let mut state = MockChainState::new();
+    for account in [&alice, &bob] {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &account.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(account.keypair.get_public_key().compress(), MockAccount {
+            balances,
+            nonce: account.nonce,
+        });
+    }
+
+    let hash = tx.hash();
+    tx.apply_without_verify(&hash, &mut state).await.unwrap();
+    assert!(state.applied_txs.contains(&hash));
+
+    // Re-applying the same TX must be rejected
+    assert!(matches!(
+        tx.apply_without_verify(&hash, &mut state).await,
+        Err(VerificationError::AlreadyApplied(h)) if h == hash
+    ));
+    assert!(matches!(
+        tx.apply_with_partial_verify(&hash, &mut state).await,
+        Err(VerificationError::AlreadyApplied(_))
+    ));
+
+    // Bob only received the transfer once
+    let balance = bob.keypair.decrypt_to_point(&state.accounts[&bob.keypair.get_public_key().compress()].balances[&XELIS_ASSET]);
+    assert_eq!(balance, Scalar::from(50u64) * (*G));
+}
+
+#[tokio::test]
+async fn test_tx_self_transfer_rejected() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 0);
+
+    let create_state = || {
+        let mut state = create_chain_state_for(&alice);
+        let bob_state = create_chain_state_for(&bob);
+        state.accounts.extend(bob_state.accounts);
+        state
+    };
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount: 50,
+        destination: alice.address(),
+        asset: XELIS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]);
+    let builder = TransactionBuilder::new(TxVersion::V1, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::SenderIsReceiver)));
+
+    // Normal transfer is accepted
+    let tx = create_tx_for(alice.clone(), bob.address(), 50, None);
+    let mut state = create_state();
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    // Patch the destination to the sender, it must be rejected before the signature check
+    let source = alice.keypair.get_public_key().compress().to_bytes();
+    let destination = bob.keypair.get_public_key().compress().to_bytes();
+    let mut bytes = tx.to_bytes();
+    let index = bytes.windows(destination.len())
+        .position(|window| window == destination.as_slice())
+        .unwrap();
+    bytes[index..index + source.len()].copy_from_slice(&source);
+
+    let tx = Arc::new(Transaction::from_bytes(&bytes).unwrap());
+    let mut state = create_state();
+    let hash = tx.hash();
+    assert!(matches!(
+        tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::SenderIsReceiver)
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_tx_verify_serial_matches_parallel() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 0);
+
+    let tx = create_tx_for(alice.clone(), bob.address(), 50, None);
+
+    let mut state = MockChainState::new();
+    for account in [&alice, &bob] {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &account.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(account.keypair.get_public_key().compress(), MockAccount {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    let hash = tx.hash();
+    let serial = VerificationConfig::serial();
+    let parallel = VerificationConfig::new(4, 1);
+    assert!(!serial.is_parallel());
+
+    let mut serial_state = state.clone();
+    tx.verify(&hash, &mut serial_state, &NoZKPCache, &serial).await.unwrap();
+
+    let mut parallel_state = state.clone();
+    tx.verify(&hash, &mut parallel_state, &NoZKPCache, &parallel).await.unwrap();
+
+    // Same resulting balances for both paths
+    for account in [&alice, &bob] {
+        let key = account.keypair.get_public_key().compress();
+        let serial_balance = account.keypair.decrypt_to_point(&serial_state.accounts[&key].balances[&XELIS_ASSET]);
+        let parallel_balance = account.keypair.decrypt_to_point(&parallel_state.accounts[&key].balances[&XELIS_ASSET]);
+        assert_eq!(serial_balance, parallel_balance);
+    }
+
+    // Batch verification through both paths
+    let txs = [(tx.clone(), hash.clone())];
+    Transaction::verify_batch(txs.iter(), &mut state.clone(), &NoZKPCache, &serial).await.unwrap();
+    Transaction::verify_batch(txs.iter(), &mut state.clone(), &NoZKPCache, &parallel).await.unwrap();
+
+    // All the threads got released
+    assert_eq!(parallel.available_threads(), 3);
+}
+
+#[test]
+fn test_verification_config_shared_threads() {
+    // Sequential by default
+    assert!(!VerificationConfig::default().is_parallel());
+
+    // Limits are clamped and the pool is sized from them
+    let clamped = VerificationConfig::new(0, 0);
+    assert_eq!(clamped.max_parallel_proofs(), 1);
+    assert_eq!(clamped.batch_threshold(), 1);
+    assert_eq!(clamped.available_threads(), 0);
+
+    let config = VerificationConfig::new(4, 8);
+    assert_eq!(config.max_parallel_proofs(), 4);
+    assert_eq!(config.batch_threshold(), 8);
+    assert_eq!(config.available_threads(), 3);
+    // Below the threshold, proofs are verified on the current thread
+    assert!(!config.should_parallelize(7));
+    assert!(config.should_parallelize(8));
+
+    // Clones share the same pool
+    let shared = config.clone();
+    let first = config.acquire_threads(2);
+    assert_eq!(first.count(), 2);
+
+    let second = shared.acquire_threads(2);
+    assert_eq!(second.count(), 1);
+
+    let third = shared.acquire_threads(1);
+    assert_eq!(third.count(), 0);
+
+    drop(first);
+    assert_eq!(config.available_threads(), 2);
+
+    drop(second);
+    drop(third);
+    assert_eq!(shared.available_threads(), 3);
+}
+
 #[tokio::test]
 async fn test_tx_verify_with_zkp_cache() {
     let mut alice = Account::new();
@@ -268,7 +521,7 @@ async fn test_tx_verify_with_zkp_cache() {
     let hash = tx.hash();
     {
         // Ensure the TX is valid first
-        assert!(tx.verify(&hash, &mut state, &NoZKPCache).await.is_ok());    
+        assert!(tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.is_ok());    
     }
 
     struct DummyCache;
@@ -286,10 +539,10 @@ async fn test_tx_verify_with_zkp_cache() {
         .nonce = 0;
 
     // Now, the chain state balances has changed, it should error even if the TX is in cache
-    assert!(matches!(tx.verify(&hash, &mut state, &DummyCache).await, Err(VerificationError::Proof(ProofVerificationError::GenericProof))));
+    assert!(matches!(tx.verify(&hash, &mut state, &DummyCache, &VerificationConfig::default()).await, Err(VerificationError::Proof(ProofVerificationError::GenericProof))));
 
     // But should be fine for a clean state
-    assert!(tx.verify(&hash, &mut clean_state, &DummyCache).await.is_ok());
+    assert!(tx.verify(&hash, &mut clean_state, &DummyCache, &VerificationConfig::default()).await.is_ok());
 }
 
 #[tokio::test]
@@ -301,6 +554,7 @@ async fn test_burn_tx_verify() {
         let mut state = AccountStateImpl {
             balances: alice.balances.clone(),
             nonce: alice.nonce,
+            last_balance_topoheight: None,
             reference: Reference {
                 topoheight: 0,
                 hash: Hash::zero(),
@@ -335,13 +589,54 @@ async fn test_burn_tx_verify() {
     }
 
     let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
 
     // Check Alice balance
     let balance = alice.keypair.decrypt_to_point(&state.accounts[&alice.keypair.get_public_key().compress()].balances[&XELIS_ASSET]);
     assert_eq!(balance, Scalar::from((100u64 * COIN_VALUE) - (50 * COIN_VALUE + tx.fee)) * (*G));
 }
 
+#[test]
+fn test_block_weight() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let transfer = create_tx_for(alice.clone(), bob.address(), 50, None);
+    let invoke = {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce + 1,
+            last_balance_topoheight: None,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+            contract: Hash::zero(),
+            entry_id: 0,
+            max_gas: 1000,
+            parameters: Vec::new(),
+            deposits: Default::default(),
+            permission: Default::default(),
+        });
+        let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+        Arc::new(builder.build(&mut state, &alice.keypair).unwrap())
+    };
+
+    assert_eq!(transfer.get_max_gas(), 0);
+    assert_eq!(invoke.get_max_gas(), 1000);
+
+    let header = MockChainState::new().block.get_header().clone();
+    let block = crate::block::Block::new(header, vec![transfer.clone(), invoke.clone()]);
+    let weight = block.weight();
+    assert_eq!(weight.size, transfer.size() + invoke.size());
+    assert_eq!(weight.tx_count, 2);
+    assert_eq!(weight.total_gas, 1000);
+}
+
 #[tokio::test]
 async fn test_tx_invoke_contract() {
     let mut alice = Account::new();
@@ -352,6 +647,7 @@ async fn test_tx_invoke_contract() {
         let mut state = AccountStateImpl {
             balances: alice.balances.clone(),
             nonce: alice.nonce,
+            last_balance_topoheight: None,
             reference: Reference {
                 topoheight: 0,
                 hash: Hash::zero(),
@@ -388,7 +684,10 @@ async fn test_tx_invoke_contract() {
         Hash::zero(),
         ContractModule {
             version: Default::default(),
-            module: Arc::new(module)
+            module: Arc::new(module),
+            dependencies: Vec::new(),
+            upgrade_authority: None,
+            source_hash: None,
         },
     );
 
@@ -405,7 +704,7 @@ async fn test_tx_invoke_contract() {
     }
 
     let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
 
     // Check Alice balance
     let balance = alice.keypair.decrypt_to_point(&state.accounts[&alice.keypair.get_public_key().compress()].balances[&XELIS_ASSET]);
@@ -416,159 +715,918 @@ async fn test_tx_invoke_contract() {
 }
 
 #[tokio::test]
-async fn test_tx_deploy_contract() {
+async fn test_tx_invoke_contract_preloaded_modules() {
     let mut alice = Account::new();
 
     alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
 
-    let max_gas = 500;
-    let deposit = 10;
-
     let tx = {
         let mut state = AccountStateImpl {
             balances: alice.balances.clone(),
             nonce: alice.nonce,
+            last_balance_topoheight: None,
             reference: Reference {
                 topoheight: 0,
                 hash: Hash::zero(),
             },
         };
 
-        let mut module = Module::new();
-        module.add_entry_chunk(Chunk::new(), None);
-
-        // constructor
-        module.add_hook_chunk(0, Chunk::new());
-
-        assert!(module.size() == module.to_bytes().len());
-
-        let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
-            contract_version: Default::default(),
-            module: module.to_hex(),
-            invoke: Some(DeployContractInvokeBuilder {
-                deposits: [(XELIS_ASSET, ContractDepositBuilder {
-                    amount: deposit,
-                    private: false
-                })].into(),
-                max_gas,
-            }),
+        let data = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+            contract: Hash::zero(),
+            entry_id: 0,
+            max_gas: 1000,
+            parameters: Vec::new(),
+            deposits: Default::default(),
+            permission: Default::default(),
         });
         let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
-        let estimated_size = builder.estimate_size();
-        let tx = builder.build(&mut state, &alice.keypair).unwrap();
-        assert!(tx.to_bytes().len() == tx.size(), "expected {} bytes but estimated {} bytes", tx.to_bytes().len(), tx.size());
-        assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
-
-        Arc::new(tx)
+        Arc::new(builder.build(&mut state, &alice.keypair).unwrap())
     };
 
-    let mut state = MockChainState::new();
+    let mut state = create_chain_state_for(&alice);
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::new(), None);
 
-    // Create the chain state
-    {
-        let mut balances = HashMap::new();
-        for (asset, balance) in &alice.balances {
-            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
-        }
-        state.accounts.insert(alice.keypair.get_public_key().compress(), MockAccount {
-            balances,
-            nonce: alice.nonce,
-        });
-    }
+    state.internal_store_contract_module(
+        Hash::zero(),
+        ContractModule {
+            version: Default::default(),
+            module: Arc::new(module),
+            dependencies: Vec::new(),
+            upgrade_authority: None,
+            source_hash: None,
+        },
+    );
 
-    let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+    assert_eq!(tx.referenced_contracts(), IndexSet::from([&Hash::zero()]));
 
-    // Check Alice balance
-    let balance = alice.keypair.decrypt_to_point(&state.accounts[&alice.keypair.get_public_key().compress()].balances[&XELIS_ASSET]);
-    // 1 XEL for contract deploy, tx fee, max gas + deposit
-    let total_spend = BURN_PER_CONTRACT + tx.fee + max_gas + deposit;
+    state.preload_modules_for(&tx).await.unwrap();
+    assert_eq!(state.module_loads, 1);
 
-    assert_eq!(balance, Scalar::from((100 * COIN_VALUE) - total_spend) * (*G));
+    // Everything is served from the cache
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+    assert_eq!(state.module_loads, 1);
 }
 
 #[tokio::test]
-async fn test_max_transfers() {
+async fn test_tx_invoke_contract_private_deposit() {
     let mut alice = Account::new();
-    let mut bob = Account::new();
 
     alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
-    bob.set_balance(XELIS_ASSET, 0);
-
-    let tx = {
-        let mut transfers = Vec::new();
-        for _ in 0..MAX_TRANSFER_COUNT {
-            transfers.push(TransferBuilder {
-                amount: 1,
-                destination: bob.address(),
-                asset: XELIS_ASSET,
-                extra_data: None,
-                encrypt_extra_data: true,
-            });
-        }
-
-        let mut state = AccountStateImpl {
-            balances: alice.balances.clone(),
-            nonce: alice.nonce,
-            reference: Reference {
-                topoheight: 0,
-                hash: Hash::zero(),
-            },
-        };
-
-        let data = TransactionTypeBuilder::Transfers(transfers);
-        let builder = TransactionBuilder::new(TxVersion::V0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
-        let estimated_size = builder.estimate_size();
-        let tx = builder.build(&mut state, &alice.keypair).unwrap();
-        assert!(estimated_size == tx.size());
-        assert!(tx.to_bytes().len() == estimated_size);
 
-        Arc::new(tx)
+    let mut account_state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
     };
 
-    // Create the chain state
-    let mut state = MockChainState::new();
-
-    // Alice
-    {
-        let mut balances = HashMap::new();
-        for (asset, balance) in alice.balances {
-            balances.insert(asset, balance.ciphertext.take_ciphertext().unwrap());
-        }
-        state.accounts.insert(alice.keypair.get_public_key().compress(), MockAccount {
-            balances,
-            nonce: alice.nonce,
-        });
-    }
+    let data = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+        contract: Hash::zero(),
+        entry_id: 0,
+        max_gas: 1000,
+        parameters: Vec::new(),
+        deposits: [
+            (XELIS_ASSET, ContractDepositBuilder {
+                amount: 50 * COIN_VALUE,
+                private: true
+            })
+        ].into_iter().collect(),
+        permission: Default::default(),
+    });
 
-    // Bob
-    {
-        let mut balances = HashMap::new();
-        for (asset, balance) in bob.balances {
-            balances.insert(asset, balance.ciphertext.take_ciphertext().unwrap());
-        }
-        state.accounts.insert(bob.keypair.get_public_key().compress(), MockAccount {
-            balances,
-            nonce: alice.nonce,
-        });
+    // Private deposits are disabled until contracts can spend them
+    for version in [TxVersion::V2, TxVersion::V3] {
+        let builder = TransactionBuilder::new(version, alice.keypair.get_public_key().compress(), None, data.clone(), FeeBuilder::default());
+        assert!(matches!(builder.build(&mut account_state, &alice.keypair), Err(GenerationError::PrivateDepositsNotSupported)));
     }
-    let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
 }
 
-#[tokio::test]
-async fn test_multisig_setup() {
-    let mut alice = Account::new();
-    let mut bob = Account::new();
-    let charlie = Account::new();
+#[cfg(feature = "builder-assertions")]
+#[test]
+fn test_builder_size_assertions() {
+    use super::builder::verify_built_size;
 
+    let mut alice = Account::new();
+    let bob = Account::new();
     alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
-    bob.set_balance(XELIS_ASSET, 0);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount: 50,
+        destination: bob.address(),
+        asset: XELIS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]);
+
+    // A correct build passes the assertions
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+
+    // A drifted estimate is reported instead of panicking
+    assert!(matches!(
+        verify_built_size::<()>(&tx, tx.size() + 1),
+        Err(GenerationError::SizeMismatch(expected, got)) if expected == tx.size() + 1 && got == tx.size()
+    ));
+}
+
+#[test]
+fn test_transfers_with_extra_data() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    let charlie = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let extra_data = Some(DataElement::Value(DataValue::String("hello".to_owned())));
+    let data = TransactionTypeBuilder::Transfers(vec![
+        TransferBuilder {
+            amount: 50,
+            destination: bob.address(),
+            asset: XELIS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        },
+        TransferBuilder {
+            amount: 50,
+            destination: charlie.address(),
+            asset: XELIS_ASSET,
+            extra_data: extra_data.clone(),
+            encrypt_extra_data: true,
+        },
+        TransferBuilder {
+            amount: 50,
+            destination: bob.address(),
+            asset: XELIS_ASSET,
+            extra_data,
+            encrypt_extra_data: false,
+        },
+    ]);
+
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+
+    assert_eq!(tx.transfers_with_extra_data(), vec![1, 2]);
+}
+
+#[test]
+fn test_estimate_size_is_cached() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount: 50,
+        destination: bob.address(),
+        asset: XELIS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]);
+
+    SIZE_COMPUTATIONS.with(|count| count.set(0));
+
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let estimated_size = builder.estimate_size();
+    assert_eq!(builder.estimate_size(), estimated_size);
+
+    // Fees computation during the build reuses the cached size
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+    assert_eq!(SIZE_COMPUTATIONS.with(|count| count.get()), 1);
+    assert_eq!(tx.size(), estimated_size);
+}
+
+struct PanicZKPCache;
+
+#[async_trait]
+impl<E> ZKPCache<E> for PanicZKPCache {
+    async fn is_already_verified(&self, _: &Hash) -> Result<bool, E> {
+        panic!("proofs should not be checked");
+    }
+}
+
+#[tokio::test]
+async fn test_tx_invoke_contract_underfunded() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let tx = {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            last_balance_topoheight: None,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+            contract: Hash::zero(),
+            entry_id: 0,
+            max_gas: 1000,
+            parameters: Vec::new(),
+            deposits: [
+                (XELIS_ASSET, ContractDepositBuilder {
+                    amount: 50 * COIN_VALUE,
+                    private: false
+                })
+            ].into_iter().collect(),
+            permission: Default::default(),
+        });
+        let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+        Arc::new(builder.build(&mut state, &alice.keypair).unwrap())
+    };
+
+    let mut state = create_chain_state_for(&alice);
+    // Sender only owns 10 XEL while depositing 50 XEL
+    state.plaintext_balances.insert((alice.keypair.get_public_key().compress(), XELIS_ASSET), 10 * COIN_VALUE);
+
+    let hash = tx.hash();
+    assert!(matches!(
+        tx.verify(&hash, &mut state, &PanicZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::InsufficientFunds)
+    ));
+}
+
+#[tokio::test]
+async fn test_tx_deploy_contract() {
+    let mut alice = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let max_gas = 500;
+    let deposit = 10;
+
+    let tx = {
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            last_balance_topoheight: None,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let mut module = Module::new();
+        module.add_entry_chunk(Chunk::new(), None);
+
+        // constructor
+        module.add_hook_chunk(0, Chunk::new());
+
+        assert!(module.size() == module.to_bytes().len());
+
+        let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+            contract_version: Default::default(),
+            module: module.to_hex(),
+            dependencies: Vec::new(),
+            upgradeable: false,
+            upgrade: None,
+            source_hash: None,
+            invoke: Some(DeployContractInvokeBuilder {
+                deposits: [(XELIS_ASSET, ContractDepositBuilder {
+                    amount: deposit,
+                    private: false
+                })].into(),
+                max_gas,
+            }),
+        });
+        let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+        let estimated_size = builder.estimate_size();
+        let tx = builder.build(&mut state, &alice.keypair).unwrap();
+        assert!(tx.to_bytes().len() == tx.size(), "expected {} bytes but estimated {} bytes", tx.to_bytes().len(), tx.size());
+        assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
+
+        Arc::new(tx)
+    };
+
+    let mut state = MockChainState::new();
+
+    // Create the chain state
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &alice.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), MockAccount {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    // Check Alice balance
+    let balance = alice.keypair.decrypt_to_point(&state.accounts[&alice.keypair.get_public_key().compress()].balances[&XELIS_ASSET]);
+    // 1 XEL for contract deploy, tx fee, max gas + deposit
+    let total_spend = BURN_PER_CONTRACT + tx.fee + max_gas + deposit;
+
+    assert_eq!(balance, Scalar::from((100 * COIN_VALUE) - total_spend) * (*G));
+}
+
+fn create_deploy_tx_with_dependencies(alice: &Account, dependencies: Vec<Hash>) -> Arc<Transaction> {
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::new(), None);
+
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        contract_version: ContractVersion::V1,
+        module: module.to_hex(),
+        dependencies,
+        upgradeable: false,
+        upgrade: None,
+        source_hash: None,
+        invoke: None,
+    });
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let estimated_size = builder.estimate_size();
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+    assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
+    assert!(tx.to_bytes().len() == estimated_size);
+
+    Arc::new(tx)
+}
+
+fn create_chain_state_for(account: &Account) -> MockChainState {
+    let mut state = MockChainState::new();
+    let mut balances = HashMap::new();
+    for (asset, balance) in &account.balances {
+        balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+    }
+
+    state.accounts.insert(account.keypair.get_public_key().compress(), MockAccount {
+        balances,
+        nonce: account.nonce,
+    });
+
+    state
+}
+
+#[tokio::test]
+async fn test_tx_deploy_contract_with_existing_dependency() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let dependency = Hash::new([1u8; 32]);
+    let tx = create_deploy_tx_with_dependencies(&alice, vec![dependency.clone()]);
+
+    // Serialization must keep the declared dependencies
+    let tx2 = Transaction::from_bytes(&tx.to_bytes()).unwrap();
+    let TransactionType::DeployContract(payload) = tx2.get_data() else {
+        panic!("expected a deploy contract payload");
+    };
+    assert_eq!(payload.contract.dependencies, vec![dependency.clone()]);
+
+    let mut state = create_chain_state_for(&alice);
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::new(), None);
+    state.internal_set_contract_module(dependency, ContractModule::new(ContractVersion::V1, Arc::new(module)));
+
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tx_deploy_contract_with_missing_dependency() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let dependency = Hash::new([1u8; 32]);
+    let tx = create_deploy_tx_with_dependencies(&alice, vec![dependency.clone()]);
+
+    let mut state = create_chain_state_for(&alice);
+    let hash = tx.hash();
+    assert!(matches!(
+        tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::ContractDependencyNotFound(h)) if h == dependency
+    ));
+}
+
+fn create_account_state_for(account: &Account) -> AccountStateImpl {
+    AccountStateImpl {
+        balances: account.balances.clone(),
+        nonce: account.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    }
+}
+
+// Deploy a module with `entries` entry chunks
+// or replace the module of the `upgrade` contract with it
+fn create_upgrade_tx(account: &Account, state: &mut AccountStateImpl, entries: usize, upgradeable: bool, upgrade: Option<Hash>) -> Arc<Transaction> {
+    let mut module = Module::new();
+    for _ in 0..entries {
+        module.add_entry_chunk(Chunk::new(), None);
+    }
+
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        contract_version: ContractVersion::V1,
+        module: module.to_hex(),
+        dependencies: Vec::new(),
+        upgradeable,
+        upgrade,
+        source_hash: None,
+        invoke: None,
+    });
+    let builder = TransactionBuilder::new(TxVersion::V3, account.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    let estimated_size = builder.estimate_size();
+    let tx = builder.build(state, &account.keypair).unwrap();
+    assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
+
+    Arc::new(tx)
+}
+
+fn create_invoke_tx(account: &Account, state: &mut AccountStateImpl, contract: Hash, entry_id: u16) -> Arc<Transaction> {
+    let data = TransactionTypeBuilder::InvokeContract(InvokeContractBuilder {
+        contract,
+        entry_id,
+        max_gas: 1000,
+        parameters: Vec::new(),
+        deposits: Default::default(),
+        permission: Default::default(),
+    });
+    let builder = TransactionBuilder::new(TxVersion::V2, account.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+
+    Arc::new(builder.build(state, &account.keypair).unwrap())
+}
+
+#[tokio::test]
+async fn test_tx_upgrade_immutable_contract() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut account_state = create_account_state_for(&alice);
+    let mut state = create_chain_state_for(&alice);
+
+    let deploy = create_upgrade_tx(&alice, &mut account_state, 1, false, None);
+    let contract = deploy.hash();
+    deploy.verify(&contract, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    // Serialization must keep the missing upgrade authority
+    let tx = Transaction::from_bytes(&deploy.to_bytes()).unwrap();
+    let TransactionType::DeployContract(payload) = tx.get_data() else {
+        panic!("expected a deploy contract payload");
+    };
+    assert!(!payload.contract.is_upgradeable());
+
+    let upgrade = create_upgrade_tx(&alice, &mut account_state, 2, false, Some(contract.clone()));
+    let hash = upgrade.hash();
+    assert!(matches!(
+        upgrade.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::ContractImmutable(h)) if h == contract
+    ));
+}
+
+#[tokio::test]
+async fn test_tx_upgrade_upgradeable_contract() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut account_state = create_account_state_for(&alice);
+    let mut state = create_chain_state_for(&alice);
+
+    let deploy = create_upgrade_tx(&alice, &mut account_state, 1, true, None);
+    let contract = deploy.hash();
+    deploy.verify(&contract, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    let tx = Transaction::from_bytes(&deploy.to_bytes()).unwrap();
+    let TransactionType::DeployContract(payload) = tx.get_data() else {
+        panic!("expected a deploy contract payload");
+    };
+    assert_eq!(payload.contract.upgrade_authority.as_ref(), Some(alice.keypair.get_public_key().compress()).as_ref());
+
+    // Second entry doesn't exist before the upgrade
+    let invoke = create_invoke_tx(&alice, &mut account_state.clone(), contract.clone(), 1);
+    let hash = invoke.hash();
+    assert!(matches!(
+        invoke.verify(&hash, &mut state.clone(), &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::InvalidInvokeContract)
+    ));
+
+    let upgrade = create_upgrade_tx(&alice, &mut account_state, 2, true, Some(contract.clone()));
+    let tx = Transaction::from_bytes(&upgrade.to_bytes()).unwrap();
+    let TransactionType::DeployContract(payload) = tx.get_data() else {
+        panic!("expected a deploy contract payload");
+    };
+    assert_eq!(payload.upgrade.as_ref(), Some(&contract));
+
+    let hash = upgrade.hash();
+    upgrade.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    // The contract is still available at its original hash with the new module
+    let invoke = create_invoke_tx(&alice, &mut account_state, contract.clone(), 1);
+    let hash = invoke.hash();
+    invoke.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_tx_upgrade_contract_from_another_account() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = create_chain_state_for(&alice);
+    let mut balances = HashMap::new();
+    for (asset, balance) in &bob.balances {
+        balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+    }
+    state.accounts.insert(bob.keypair.get_public_key().compress(), MockAccount {
+        balances,
+        nonce: bob.nonce,
+    });
+
+    let deploy = create_upgrade_tx(&alice, &mut create_account_state_for(&alice), 1, true, None);
+    let contract = deploy.hash();
+    deploy.verify(&contract, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    // Only the upgrade authority can replace the module
+    let upgrade = create_upgrade_tx(&bob, &mut create_account_state_for(&bob), 2, true, Some(contract.clone()));
+    let hash = upgrade.hash();
+    assert!(matches!(
+        upgrade.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::ContractUpgradeNotAllowed(h)) if h == contract
+    ));
+}
+
+#[test]
+fn test_upgrade_requires_tx_v3() {
+    let alice = Account::new();
+    let mut state = create_account_state_for(&alice);
+
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::new(), None);
+
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        contract_version: ContractVersion::V1,
+        module: module.to_hex(),
+        dependencies: Vec::new(),
+        upgradeable: true,
+        upgrade: Some(Hash::zero()),
+        source_hash: None,
+        invoke: None,
+    });
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::ContractUpgradeNotSupported)));
+}
+
+// Deploy a module padded with raw instructions
+fn create_padded_deploy_tx(alice: &Account, padding: usize) -> Arc<Transaction> {
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::from_instructions(vec![0; padding]), None);
+
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        contract_version: ContractVersion::V1,
+        module: module.to_hex(),
+        dependencies: Vec::new(),
+        upgradeable: false,
+        upgrade: None,
+        source_hash: None,
+        invoke: None,
+    });
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+
+    Arc::new(builder.build(&mut state, &alice.keypair).unwrap())
+}
+
+#[tokio::test]
+async fn test_tx_max_size() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    // Find the padding required to reach exactly the limit
+    let padding = MAX_TRANSACTION_SIZE / 2;
+    let size = create_padded_deploy_tx(&alice, padding).size();
+    let padding = padding + MAX_TRANSACTION_SIZE - size;
+
+    let tx = create_padded_deploy_tx(&alice, padding);
+    assert_eq!(tx.size(), MAX_TRANSACTION_SIZE);
+
+    let mut state = create_chain_state_for(&alice);
+    let hash = tx.hash();
+    assert!(!matches!(
+        tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::TransactionTooLarge(..))
+    ));
+
+    let tx = create_padded_deploy_tx(&alice, padding + 1);
+    assert_eq!(tx.size(), MAX_TRANSACTION_SIZE + 1);
+
+    let mut state = create_chain_state_for(&alice);
+    let hash = tx.hash();
+    assert!(matches!(
+        tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::TransactionTooLarge(size, max)) if size == MAX_TRANSACTION_SIZE + 1 && max == MAX_TRANSACTION_SIZE
+    ));
+}
+
+#[test]
+fn test_upgradeable_requires_contract_v1() {
+    let alice = Account::new();
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let mut module = Module::new();
+    module.add_entry_chunk(Chunk::new(), None);
+
+    let data = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
+        contract_version: ContractVersion::V0,
+        module: module.to_hex(),
+        dependencies: Vec::new(),
+        upgradeable: true,
+        upgrade: None,
+        source_hash: None,
+        invoke: None,
+    });
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::UpgradeableNotSupported)));
+}
+
+#[test]
+fn test_min_supported_tx_version() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    // A plain transfer fits in V0 whatever its declared version
+    let tx = create_tx_for(alice.clone(), bob.address(), 50, None);
+    assert_eq!(tx.get_version(), TxVersion::V1);
+    assert_eq!(tx.min_supported_tx_version(), TxVersion::V0);
+    assert!(tx.can_encode_for(TxVersion::V0));
+    assert!(tx.can_encode_for(TxVersion::V2));
+
+    // Contracts require V2
+    let tx = create_deploy_tx_with_dependencies(&alice, Vec::new());
+    assert_eq!(tx.min_supported_tx_version(), TxVersion::V2);
+    assert!(!tx.can_encode_for(TxVersion::V0));
+    assert!(!tx.can_encode_for(TxVersion::V1));
+    assert!(tx.can_encode_for(TxVersion::V2));
+
+    // A V3 deploy without any V3 field only requires V2
+    let mut state = create_account_state_for(&alice);
+    let tx = create_upgrade_tx(&alice, &mut state, 1, false, None);
+    assert_eq!(tx.get_version(), TxVersion::V3);
+    assert_eq!(tx.min_supported_tx_version(), TxVersion::V2);
+    assert!(tx.can_encode_for(TxVersion::V2));
+
+    // A contract upgrade is a V3 only field
+    let tx = create_upgrade_tx(&alice, &mut state, 1, true, Some(Hash::zero()));
+    assert_eq!(tx.min_supported_tx_version(), TxVersion::V3);
+    assert!(!tx.can_encode_for(TxVersion::V2));
+}
+
+#[test]
+fn test_referenced_contracts_deploy() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let dependency = Hash::new([1; 32]);
+    let tx = create_deploy_tx_with_dependencies(&alice, vec![dependency.clone()]);
+    assert_eq!(tx.referenced_contracts(), IndexSet::from([&dependency]));
+
+    // The upgraded contract must be loaded too
+    let mut state = create_account_state_for(&alice);
+    let target = Hash::new([2; 32]);
+    let tx = create_upgrade_tx(&alice, &mut state, 1, true, Some(target.clone()));
+    assert_eq!(tx.referenced_contracts(), IndexSet::from([&target]));
+}
+
+#[test]
+fn test_dry_run_transfer() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let transfer = |amount| TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount,
+        destination: bob.address(),
+        asset: XELIS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]);
+
+    // Overspending is detected without the keypair
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, transfer(100 * COIN_VALUE), FeeBuilder::default());
+    assert!(matches!(
+        builder.dry_run(&state),
+        Err(GenerationError::Proof(ProofGenerationError::InsufficientFunds { .. }))
+    ));
+
+    // Valid transfer reports the same fees as the final transaction
+    let builder = TransactionBuilder::new(TxVersion::V2, alice.keypair.get_public_key().compress(), None, transfer(50 * COIN_VALUE), FeeBuilder::default());
+    let report = builder.dry_run(&state).unwrap();
+    assert_eq!(report.estimated_size, builder.estimate_size());
+    assert_eq!(report.fee_limit, report.fee * 2);
+
+    // Dry run didn't touch the state
+    assert_eq!(state.nonce, alice.nonce);
+    assert_eq!(state.balances[&XELIS_ASSET].balance, 100 * COIN_VALUE);
+
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+    assert_eq!(report.fee, tx.get_fee());
+    assert_eq!(report.fee_limit, tx.get_fee_limit());
+    assert_eq!(report.estimated_size, tx.size());
+}
+
+#[test]
+fn test_build_with_stale_reference() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        // Balance was updated at topoheight 10
+        last_balance_topoheight: Some(10),
+        // But the reference is older
+        reference: Reference {
+            topoheight: 5,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+        amount: 1,
+        destination: bob.address(),
+        asset: XELIS_ASSET,
+        extra_data: None,
+        encrypt_extra_data: true,
+    }]);
+
+    let builder = TransactionBuilder::new(TxVersion::V1, alice.keypair.get_public_key().compress(), None, data.clone(), FeeBuilder::default());
+    assert!(matches!(
+        builder.build(&mut state, &alice.keypair),
+        Err(GenerationError::InconsistentReference(5, 10))
+    ));
+
+    // Nothing was consumed from the state
+    assert_eq!(state.nonce, alice.nonce);
+    assert_eq!(state.balances[&XELIS_ASSET].balance, 100 * COIN_VALUE);
+
+    // Reference at the same topoheight is valid
+    state.reference.topoheight = 10;
+    let builder = TransactionBuilder::new(TxVersion::V1, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+    assert!(builder.build(&mut state, &alice.keypair).is_ok());
+}
+
+#[tokio::test]
+async fn test_max_transfers() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 0);
+
+    let tx = {
+        let mut transfers = Vec::new();
+        for _ in 0..MAX_TRANSFER_COUNT {
+            transfers.push(TransferBuilder {
+                amount: 1,
+                destination: bob.address(),
+                asset: XELIS_ASSET,
+                extra_data: None,
+                encrypt_extra_data: true,
+            });
+        }
+
+        let mut state = AccountStateImpl {
+            balances: alice.balances.clone(),
+            nonce: alice.nonce,
+            last_balance_topoheight: None,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::Transfers(transfers);
+        let builder = TransactionBuilder::new(TxVersion::V0, alice.keypair.get_public_key().compress(), None, data, FeeBuilder::default());
+        let estimated_size = builder.estimate_size();
+        let tx = builder.build(&mut state, &alice.keypair).unwrap();
+        assert!(estimated_size == tx.size());
+        assert!(tx.to_bytes().len() == estimated_size);
+
+        Arc::new(tx)
+    };
+
+    // Create the chain state
+    let mut state = MockChainState::new();
+
+    // Alice
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in alice.balances {
+            balances.insert(asset, balance.ciphertext.take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), MockAccount {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    // Bob
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in bob.balances {
+            balances.insert(asset, balance.ciphertext.take_ciphertext().unwrap());
+        }
+        state.accounts.insert(bob.keypair.get_public_key().compress(), MockAccount {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_multisig_setup() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+    let charlie = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 0);
 
     let tx = {
         let mut state = AccountStateImpl {
             balances: alice.balances.clone(),
             nonce: alice.nonce,
+            last_balance_topoheight: None,
             reference: Reference {
                 topoheight: 0,
                 hash: Hash::zero(),
@@ -614,7 +1672,7 @@ async fn test_multisig_setup() {
     }
 
     let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
 
     assert!(state.multisig.contains_key(&alice.keypair.get_public_key().compress()));
 }
@@ -635,6 +1693,7 @@ async fn test_multisig() {
         let mut state = AccountStateImpl {
             balances: alice.balances.clone(),
             nonce: alice.nonce,
+            last_balance_topoheight: None,
             reference: Reference {
                 topoheight: 0,
                 hash: Hash::zero(),
@@ -691,7 +1750,128 @@ async fn test_multisig() {
     });
 
     let hash = tx.hash();
-    tx.verify(&hash, &mut state, &NoZKPCache).await.unwrap();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+}
+
+fn create_multisig_update_tx(alice: &Account, signers: &[&Account], update: MultiSigUpdateBuilder) -> Arc<Transaction> {
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        last_balance_topoheight: None,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let data = TransactionTypeBuilder::MultiSigUpdate(update);
+    let builder = TransactionBuilder::new(TxVersion::V3, alice.keypair.get_public_key().compress(), Some(signers.len() as u8), data, FeeBuilder::default());
+    let estimated_size = builder.estimate_size();
+    let mut tx = builder.build_unsigned(&mut state, &alice.keypair).unwrap();
+    for (id, signer) in signers.iter().enumerate() {
+        tx.sign_multisig(&signer.keypair, id as u8);
+    }
+
+    let tx = tx.finalize(&alice.keypair);
+    assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
+    assert!(tx.to_bytes().len() == estimated_size);
+
+    Arc::new(tx)
+}
+
+#[tokio::test]
+async fn test_multisig_update_add_participant() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    let charlie = Account::new();
+    let dave = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let tx = create_multisig_update_tx(&alice, &[&bob], MultiSigUpdateBuilder {
+        add: IndexSet::from_iter(vec![dave.address()]),
+        remove: IndexSet::new(),
+        new_threshold: Some(2),
+    });
+
+    let mut state = create_chain_state_for(&alice);
+    state.multisig.insert(alice.keypair.get_public_key().compress(), MultiSigPayload {
+        threshold: 1,
+        participants: IndexSet::from_iter(vec![bob.keypair.get_public_key().compress(), charlie.keypair.get_public_key().compress()]),
+    });
+
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    let multisig = &state.multisig[&alice.keypair.get_public_key().compress()];
+    assert_eq!(multisig.threshold, 2);
+    assert_eq!(multisig.participants.len(), 3);
+    assert!(multisig.participants.contains(&dave.keypair.get_public_key().compress()));
+}
+
+#[tokio::test]
+async fn test_multisig_update_remove_participant() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    let charlie = Account::new();
+    let dave = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let tx = create_multisig_update_tx(&alice, &[&bob], MultiSigUpdateBuilder {
+        add: IndexSet::new(),
+        remove: IndexSet::from_iter(vec![charlie.address()]),
+        new_threshold: None,
+    });
+
+    let mut state = create_chain_state_for(&alice);
+    state.multisig.insert(alice.keypair.get_public_key().compress(), MultiSigPayload {
+        threshold: 1,
+        participants: IndexSet::from_iter(vec![
+            bob.keypair.get_public_key().compress(),
+            charlie.keypair.get_public_key().compress(),
+            dave.keypair.get_public_key().compress()
+        ]),
+    });
+
+    let hash = tx.hash();
+    tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await.unwrap();
+
+    // Order of the remaining participants is kept
+    let multisig = &state.multisig[&alice.keypair.get_public_key().compress()];
+    assert_eq!(multisig.threshold, 1);
+    assert_eq!(
+        multisig.participants,
+        IndexSet::from_iter(vec![bob.keypair.get_public_key().compress(), dave.keypair.get_public_key().compress()])
+    );
+}
+
+#[tokio::test]
+async fn test_multisig_update_threshold_above_participants() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    let charlie = Account::new();
+
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    // Removing charlie while requiring two signatures leaves a single participant
+    let tx = create_multisig_update_tx(&alice, &[&bob, &charlie], MultiSigUpdateBuilder {
+        add: IndexSet::new(),
+        remove: IndexSet::from_iter(vec![charlie.address()]),
+        new_threshold: None,
+    });
+
+    let mut state = create_chain_state_for(&alice);
+    state.multisig.insert(alice.keypair.get_public_key().compress(), MultiSigPayload {
+        threshold: 2,
+        participants: IndexSet::from_iter(vec![bob.keypair.get_public_key().compress(), charlie.keypair.get_public_key().compress()]),
+    });
+
+    let hash = tx.hash();
+    assert!(matches!(
+        tx.verify(&hash, &mut state, &NoZKPCache, &VerificationConfig::default()).await,
+        Err(VerificationError::MultiSigThreshold)
+    ));
 }
 
 impl FeeHelper for AccountStateImpl {
@@ -727,6 +1907,10 @@ impl AccountState for AccountStateImpl {
         self.reference.clone()
     }
 
+    fn get_last_balance_topoheight(&self) -> Result<Option<TopoHeight>, Self::Error> {
+        Ok(self.last_balance_topoheight)
+    }
+
     fn update_account_balance(&mut self, asset: &Hash, balance: u64, ciphertext: Ciphertext) -> Result<(), Self::Error> {
         self.balances.insert(asset.clone(), Balance {
             balance,
@@ -0,0 +1,133 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc
+};
+
+// Minimum range proofs per batch before splitting them across threads
+pub const DEFAULT_BATCH_THRESHOLD: usize = 8;
+
+// Limits applied during the ZK Proofs verification
+// This allow to bound the CPU usage when many blocks are verified concurrently
+// Clones share the same pool of threads, so the cap applies to all
+// the verifications running at the same time
+// Fields are private so the limits can't drift from the shared pool
+#[derive(Debug, Clone)]
+pub struct VerificationConfig {
+    // Maximum threads verifying proofs at the same time
+    // Set to 1 to verify everything on the current thread
+    max_parallel_proofs: usize,
+    // Minimum range proofs required in a batch
+    // before it get split and verified in parallel
+    batch_threshold: usize,
+    // Extra threads still available in the shared pool
+    available_threads: Arc<AtomicUsize>,
+}
+
+// Extra threads reserved from the shared pool
+// They are given back once dropped
+#[derive(Debug)]
+pub struct ThreadsPermit {
+    available_threads: Arc<AtomicUsize>,
+    count: usize,
+}
+
+impl ThreadsPermit {
+    // Number of extra threads reserved
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Drop for ThreadsPermit {
+    fn drop(&mut self) {
+        if self.count > 0 {
+            self.available_threads.fetch_add(self.count, Ordering::AcqRel);
+        }
+    }
+}
+
+impl VerificationConfig {
+    // Both limits are clamped to at least 1
+    // The shared pool is sized from the clamped `max_parallel_proofs`
+    pub fn new(max_parallel_proofs: usize, batch_threshold: usize) -> Self {
+        let max_parallel_proofs = max_parallel_proofs.max(1);
+        Self {
+            max_parallel_proofs,
+            batch_threshold: batch_threshold.max(1),
+            // The current thread is always used and isn't part of the pool
+            available_threads: Arc::new(AtomicUsize::new(max_parallel_proofs - 1)),
+        }
+    }
+
+    // Verify all the proofs on the current thread
+    pub fn serial() -> Self {
+        Self::new(1, DEFAULT_BATCH_THRESHOLD)
+    }
+
+    // Maximum threads verifying proofs at the same time
+    pub fn max_parallel_proofs(&self) -> usize {
+        self.max_parallel_proofs
+    }
+
+    // Minimum range proofs required in a batch to verify it in parallel
+    pub fn batch_threshold(&self) -> usize {
+        self.batch_threshold
+    }
+
+    // Is the parallel verification allowed
+    pub fn is_parallel(&self) -> bool {
+        self.max_parallel_proofs > 1
+    }
+
+    // Should `count` range proofs be verified in parallel
+    // Small batches are verified on the current thread
+    pub fn should_parallelize(&self, count: usize) -> bool {
+        self.is_parallel() && count >= self.batch_threshold
+    }
+
+    // Number of chunks to split the range proofs into
+    // One chunk is kept for the sigma proofs
+    pub fn range_proofs_chunks(&self, count: usize) -> usize {
+        let threads = self.max_parallel_proofs.saturating_sub(1);
+        (count / self.batch_threshold)
+            .min(threads)
+            .max(1)
+    }
+
+    // Reserve up to `wanted` extra threads from the shared pool
+    // The permit may hold less threads than requested, or none at all
+    // if other verifications are already using them
+    pub fn acquire_threads(&self, wanted: usize) -> ThreadsPermit {
+        let mut available = self.available_threads.load(Ordering::Acquire);
+        loop {
+            let count = available.min(wanted);
+            if count == 0 {
+                break ThreadsPermit {
+                    available_threads: Arc::clone(&self.available_threads),
+                    count: 0,
+                };
+            }
+
+            match self.available_threads.compare_exchange_weak(available, available - count, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => break ThreadsPermit {
+                    available_threads: Arc::clone(&self.available_threads),
+                    count,
+                },
+                Err(current) => available = current,
+            }
+        }
+    }
+
+    // Extra threads currently available in the shared pool
+    pub fn available_threads(&self) -> usize {
+        self.available_threads.load(Ordering::Acquire)
+    }
+}
+
+// Proofs are verified on the current thread by default
+// Use `VerificationConfig::new` to enable the parallel verification
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self::serial()
+    }
+}
@@ -27,6 +27,10 @@ pub enum VerificationError<T> {
     TransferExtraDataSize,
     #[error("Extra Data is too big in transaction")]
     TransactionExtraDataSize,
+    #[error("Memo to self is too big")]
+    SelfMemoSize,
+    #[error("Transaction size {} is above the limit of {}", _0, _1)]
+    TransactionTooLarge(usize, usize),
     #[error("Transfer count is invalid")]
     TransferCount,
     #[error("Deposit count is invalid")]
@@ -51,10 +55,58 @@ pub enum VerificationError<T> {
     InvalidInvokeContract,
     #[error("Contract not found")]
     ContractNotFound,
+    #[error("Contract dependency {} not found", _0)]
+    ContractDependencyNotFound(Hash),
+    #[error("Contract {} is immutable", _0)]
+    ContractImmutable(Hash),
     #[error("Deposit decompressed not found")]
     DepositNotFound,
     #[error("Configured max gas is above the network limit")]
     MaxGasReached,
+    #[error("Insufficient funds")]
+    InsufficientFunds,
+    #[error("Transaction {} was already applied", _0)]
+    AlreadyApplied(Hash),
     #[error(transparent)]
     Contract(#[from] ContractError<T>),
-}
\ No newline at end of file
+    #[error("Source is not allowed to upgrade contract {}", _0)]
+    ContractUpgradeNotAllowed(Hash),
+}
+
+impl<T> VerificationError<T> {
+    // Stable error code of the variant
+    // Exposed to the RPC clients, they must never change
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::State(_)
+            | Self::AnyError(_) => -33000,
+            Self::InvalidNonce(..) => -33001,
+            Self::SenderIsReceiver => -33002,
+            Self::InvalidSignature => -33003,
+            Self::Proof(_) => -33004,
+            Self::TransferExtraDataSize => -33005,
+            Self::TransactionExtraDataSize => -33006,
+            Self::SelfMemoSize => -33007,
+            Self::TransactionTooLarge(..) => -33008,
+            Self::TransferCount => -33009,
+            Self::DepositCount => -33010,
+            Self::Commitments => -33011,
+            Self::MultiSigParticipants => -33012,
+            Self::MultiSigThreshold => -33013,
+            Self::MultiSigNotConfigured => -33014,
+            Self::MultiSigNotFound => -33015,
+            Self::InvalidFormat => -33016,
+            Self::ModuleError(_) => -33017,
+            Self::InvalidInvokeContract => -33018,
+            Self::ContractNotFound => -33019,
+            Self::ContractDependencyNotFound(_) => -33020,
+            Self::ContractImmutable(_) => -33021,
+            Self::DepositNotFound => -33022,
+            Self::MaxGasReached => -33023,
+            Self::InsufficientFunds => -33024,
+            Self::AlreadyApplied(_) => -33025,
+            Self::Contract(_) => -33026,
+            Self::ContractUpgradeNotAllowed(_) => -33027,
+        }
+    }
+}
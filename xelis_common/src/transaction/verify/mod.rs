@@ -1,12 +1,15 @@
 mod state;
 mod error;
 mod zkp_cache;
+mod config;
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     iter,
+    panic,
     sync::Arc,
+    thread,
 };
 
 use anyhow::Context;
@@ -25,7 +28,7 @@ use xelis_vm::ModuleValidator;
 use crate::{
     account::Nonce,
     time::Instant,
-    config::{BURN_PER_CONTRACT, MAX_GAS_USAGE_PER_TX, XELIS_ASSET},
+    config::{BURN_PER_CONTRACT, MAX_CONTRACT_DEPENDENCIES, MAX_GAS_USAGE_PER_TX, XELIS_ASSET},
     contract::{
         vm::{
             self,
@@ -33,6 +36,7 @@ use crate::{
             InvokeContract,
             HOOK_CONSTRUCTOR_ID
         },
+        ContractModule,
         ContractProvider,
         InterContractPermission
     },
@@ -58,7 +62,7 @@ use crate::{
         SIGNATURE_SIZE
     },
     serializer::Serializer,
-    tokio::spawn_blocking_safe,
+    tokio::{is_multi_threads_supported, spawn_blocking_safe},
     transaction::{
         TxVersion,
         EXTRA_DATA_LIMIT_SIZE,
@@ -70,6 +74,8 @@ use crate::{
 };
 use super::{
     ContractDeposit,
+    MultiSigPayload,
+    MultiSigUpdatePayload,
     Role,
     Transaction,
     TransactionType,
@@ -79,6 +85,10 @@ use super::{
 pub use state::*;
 pub use error::*;
 pub use zkp_cache::*;
+pub use config::*;
+
+// Transaction prepared for the range proofs batch verification
+type PreparedRangeProof = (Arc<Transaction>, Transcript, Vec<(RistrettoPoint, CompressedRistretto)>);
 
 pub struct DecompressedTransferCt {
     pub commitment: PedersenCommitment,
@@ -123,6 +133,11 @@ impl Transaction {
             return false;
         }
 
+        // Memo to self is only supported since V3
+        if self.self_memo.is_some() && !self.version.has_self_memo() {
+            return false;
+        }
+
         match self.version {
             // V0 don't support MultiSig format
             TxVersion::V0 => {
@@ -143,8 +158,10 @@ impl Transaction {
                 | TransactionType::MultiSig(_) => true,
                 _ => false,
             }
+            // MultiSig updates are only supported in V3
+            TxVersion::V2 => !matches!(self.data, TransactionType::MultiSigUpdate(_)),
             // No restriction
-            TxVersion::V2 => true,
+            TxVersion::V3 => true,
         }
     }
 
@@ -176,7 +193,8 @@ impl Transaction {
                     output += Scalar::from(payload.amount)
                 }
             },
-            TransactionType::MultiSig(_) => {},
+            TransactionType::MultiSig(_)
+            | TransactionType::MultiSigUpdate(_) => {},
             TransactionType::InvokeContract(payload) => {
                 if *asset == XELIS_ASSET {
                     output += Scalar::from(payload.max_gas);
@@ -325,7 +343,8 @@ impl Transaction {
                 .deposits
                 .keys()
                 .all(|asset| has_commitment_for_asset(asset)),
-            TransactionType::DeployContract(_) => true,
+            TransactionType::DeployContract(_)
+            | TransactionType::MultiSigUpdate(_) => true,
         }
     }
 
@@ -551,6 +570,9 @@ impl Transaction {
                     return Err(VerificationError::MultiSigNotConfigured);
                 }
             },
+            TransactionType::MultiSigUpdate(payload) => {
+                self.get_updated_multisig(state, payload).await?;
+            },
             TransactionType::InvokeContract(payload) => {
                 self.verify_invoke_contract(
                     &mut deposits_decompressed,
@@ -586,6 +608,11 @@ impl Transaction {
                     )?;
                 }
 
+                self.verify_contract_dependencies(state, &payload.contract).await?;
+                if let Some(upgrade) = payload.upgrade.as_ref() {
+                    self.verify_contract_upgrade(state, upgrade, payload.invoke.is_some()).await?;
+                }
+
                 let environment = state.get_environment(payload.contract.version).await
                     .map_err(VerificationError::State)?;
 
@@ -636,6 +663,51 @@ impl Transaction {
         Ok(())
     }
 
+    // Early solvency check for contract calls
+    // If the state knows the plaintext balance of the sender,
+    // verify that it covers the public deposits, the max gas and the fee
+    // This is done before any proof verification to not waste CPU on underfunded TXs
+    async fn verify_sender_solvency<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B,
+    ) -> Result<(), VerificationError<E>> {
+        let (deposits, max_gas, burn) = match &self.data {
+            TransactionType::InvokeContract(payload) => (Some(&payload.deposits), payload.max_gas, 0),
+            TransactionType::DeployContract(payload) => match payload.invoke.as_ref() {
+                Some(invoke) => (Some(&invoke.deposits), invoke.max_gas, BURN_PER_CONTRACT),
+                None => (None, 0, BURN_PER_CONTRACT),
+            },
+            _ => return Ok(())
+        };
+
+        let mut required: IndexMap<&Hash, u64> = IndexMap::new();
+        let xelis = self.fee.checked_add(max_gas)
+            .and_then(|v| v.checked_add(burn))
+            .ok_or(VerificationError::InvalidFormat)?;
+        required.insert(&XELIS_ASSET, xelis);
+
+        for (asset, deposit) in deposits.into_iter().flat_map(|d| d.iter()) {
+            // Private deposits amounts are unknown
+            if let ContractDeposit::Public(amount) = deposit {
+                let total = required.entry(asset).or_insert(0);
+                *total = total.checked_add(*amount)
+                    .ok_or(VerificationError::InvalidFormat)?;
+            }
+        }
+
+        for (asset, amount) in required {
+            let balance = state.get_sender_plaintext_balance(&self.source, asset).await
+                .map_err(VerificationError::State)?;
+
+            if balance.is_some_and(|balance| balance < amount) {
+                debug!("sender has not enough funds for asset {}, required {}", asset, amount);
+                return Err(VerificationError::InsufficientFunds);
+            }
+        }
+
+        Ok(())
+    }
+
     // Load and check if a contract is available
     // This is needed in case a contract has been removed or wasn't deployed due to the constructor error
     pub(super) async fn is_contract_available<'a, E, B: BlockchainVerificationState<'a, E>>(
@@ -647,6 +719,92 @@ impl Transaction {
             .map_err(VerificationError::State)
     }
 
+    // Verify that every dependency declared by the module is unique
+    // and already deployed on chain
+    async fn verify_contract_dependencies<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B,
+        contract: &'a ContractModule,
+    ) -> Result<(), VerificationError<E>> {
+        if contract.dependencies.is_empty() {
+            return Ok(())
+        }
+
+        if !ContractModule::has_dependencies_support(contract.version) || contract.dependencies.len() > MAX_CONTRACT_DEPENDENCIES {
+            return Err(VerificationError::InvalidFormat);
+        }
+
+        let mut unique = HashSet::with_capacity(contract.dependencies.len());
+        for dependency in contract.dependencies.iter() {
+            if !unique.insert(dependency) {
+                return Err(VerificationError::InvalidFormat);
+            }
+
+            if !self.is_contract_available(state, dependency).await? {
+                return Err(VerificationError::ContractDependencyNotFound(dependency.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Verify that the source can replace the module of the targeted contract
+    // An upgrade can't run a constructor as the contract is already initialized
+    async fn verify_contract_upgrade<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B,
+        contract: &'a Hash,
+        has_invoke: bool,
+    ) -> Result<(), VerificationError<E>> {
+        if !self.version.has_contract_upgrade() || has_invoke {
+            return Err(VerificationError::InvalidFormat);
+        }
+
+        if !self.is_contract_available(state, contract).await? {
+            return Err(VerificationError::ContractNotFound);
+        }
+
+        let authority = state.get_contract_upgrade_authority(contract).await
+            .map_err(VerificationError::State)?
+            .ok_or_else(|| VerificationError::ContractImmutable(contract.clone()))?;
+
+        if authority != self.source {
+            return Err(VerificationError::ContractUpgradeNotAllowed(contract.clone()));
+        }
+
+        Ok(())
+    }
+
+    // Apply the multisig update on the current setup of the source
+    // and verify that the resulting setup is valid
+    async fn get_updated_multisig<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B,
+        payload: &MultiSigUpdatePayload,
+    ) -> Result<MultiSigPayload, VerificationError<E>> {
+        let current = state.get_multisig_state(&self.source).await
+            .map_err(VerificationError::State)?
+            .ok_or(VerificationError::MultiSigNotConfigured)?;
+
+        let updated = payload.apply_to(current)
+            .ok_or(VerificationError::MultiSigParticipants)?;
+
+        if updated.participants.is_empty() || updated.participants.len() > MAX_MULTISIG_PARTICIPANTS {
+            return Err(VerificationError::MultiSigParticipants);
+        }
+
+        // You can't contains yourself in the participants
+        if bool::from(self.get_source().ct_contained_in(&updated.participants)) {
+            return Err(VerificationError::MultiSigParticipants);
+        }
+
+        if updated.threshold == 0 || updated.threshold as usize > updated.participants.len() {
+            return Err(VerificationError::MultiSigThreshold);
+        }
+
+        Ok(updated)
+    }
+
     // internal, does not verify the range proof
     // returns (transcript, commitments for range proof)
     async fn pre_verify<'a, E, B: BlockchainVerificationState<'a, E>>(
@@ -661,6 +819,20 @@ impl Transaction {
             return Err(VerificationError::InvalidFormat);
         }
 
+        // Reject it before doing any work on it
+        let size = self.size();
+        let max_size = self.version.max_size();
+        if size > max_size {
+            debug!("transaction too large: {} > {}", size, max_size);
+            return Err(VerificationError::TransactionTooLarge(size, max_size));
+        }
+
+        if let Some(memo) = self.self_memo.as_ref() {
+            if memo.size() > EXTRA_DATA_LIMIT_SIZE {
+                return Err(VerificationError::SelfMemoSize);
+            }
+        }
+
         trace!("verify fee");
         // Verify the required fee, if fee_limit is not fully used, refund the left-over later
         let refund = state.handle_tx_fee(self, tx_hash).await
@@ -700,7 +872,7 @@ impl Transaction {
                 let mut extra_data_size = 0;
                 // Prevent sending to ourself
                 for transfer in transfers.iter() {
-                    if *transfer.get_destination() == self.source {
+                    if bool::from(transfer.get_destination().ct_eq(&self.source)) {
                         debug!("sender cannot be the receiver in the same TX");
                         return Err(VerificationError::SenderIsReceiver);
                     }
@@ -756,7 +928,7 @@ impl Transaction {
                 }
 
                 // You can't contains yourself in the participants
-                if payload.participants.contains(self.get_source()) {
+                if bool::from(self.get_source().ct_contained_in(&payload.participants)) {
                     return Err(VerificationError::MultiSigParticipants);
                 }
 
@@ -766,6 +938,12 @@ impl Transaction {
                     return Err(VerificationError::MultiSigNotConfigured);
                 }
             },
+            TransactionType::MultiSigUpdate(payload) => {
+                // Resulting setup is verified once the transcript is built
+                if payload.add.iter().any(|key| payload.remove.contains(key)) {
+                    return Err(VerificationError::MultiSigParticipants);
+                }
+            },
             TransactionType::InvokeContract(payload) => {
                 self.verify_invoke_contract(
                     &mut deposits_decompressed,
@@ -803,6 +981,11 @@ impl Transaction {
                     )?;
                 }
 
+                self.verify_contract_dependencies(state, &payload.contract).await?;
+                if let Some(upgrade) = payload.upgrade.as_ref() {
+                    self.verify_contract_upgrade(state, upgrade, payload.invoke.is_some()).await?;
+                }
+
                 let environment = state.get_environment(payload.contract.version).await
                     .map_err(VerificationError::State)?;
 
@@ -952,6 +1135,23 @@ impl Transaction {
                 state.set_multisig_state(&self.source, payload).await
                     .map_err(VerificationError::State)?;
             },
+            TransactionType::MultiSigUpdate(payload) => {
+                transcript.multisig_update_proof_domain_separator();
+                for key in &payload.add {
+                    transcript.append_public_key(b"multisig_add", key);
+                }
+                for key in &payload.remove {
+                    transcript.append_public_key(b"multisig_remove", key);
+                }
+                if let Some(threshold) = payload.new_threshold {
+                    transcript.append_u64(b"multisig_threshold", threshold as u64);
+                }
+
+                // Update the multisig
+                let updated = self.get_updated_multisig(state, payload).await?;
+                state.set_multisig_state(&self.source, &updated).await
+                    .map_err(VerificationError::State)?;
+            },
             TransactionType::InvokeContract(payload) => {                
                 let dest_pubkey = PublicKey::from_hash(&payload.contract);
                 self.verify_contract_deposits(
@@ -998,8 +1198,10 @@ impl Transaction {
                     transcript.deploy_contract_proof_domain_separator();
                 }
 
-                state.set_contract_module(tx_hash, &payload.contract).await
-                    .map_err(VerificationError::State)?;
+                match payload.upgrade.as_ref() {
+                    Some(upgrade) => state.upgrade_contract_module(upgrade, &payload.contract).await,
+                    None => state.set_contract_module(tx_hash, &payload.contract).await
+                }.map_err(VerificationError::State)?;
             }
         }
 
@@ -1051,10 +1253,29 @@ impl Transaction {
         Ok((transcript, final_commitments))
     }
 
+    // Verify the range proofs of the prepared transactions in one batch
+    fn verify_range_proofs_batch(prepared: &mut [PreparedRangeProof]) -> Result<(), ProofVerificationError> {
+        RangeProof::verify_batch(
+            prepared.iter_mut()
+                .map(|(tx, transcript, commitments)| {
+                    tx.range_proof
+                        .verification_view(
+                            transcript,
+                            commitments,
+                            BULLET_PROOF_SIZE
+                        )
+                }),
+            &BP_GENS,
+            &PC_GENS,
+        )
+        .map_err(ProofVerificationError::from)
+    }
+
     pub async fn verify_batch<'a, H, E, B, C>(
         txs: impl Iterator<Item = &'a (Arc<Transaction>, H)>,
         state: &mut B,
         cache: &C,
+        config: &VerificationConfig,
     ) -> Result<(), VerificationError<E>>
     where
         H: AsRef<Hash> + 'a,
@@ -1069,6 +1290,12 @@ impl Transaction {
         for (tx, hash) in txs {
             let hash = hash.as_ref();
 
+            tx.verify_sender_solvency(state).await?;
+
+            // Load the contracts modules before their verification
+            state.preload_modules_for(tx).await
+                .map_err(VerificationError::State)?;
+
             // In case the cache already know this TX
             // we don't need to spend time reverifying it again
             // because a TX is immutable, we can just verify the mutable parts
@@ -1088,9 +1315,46 @@ impl Transaction {
         // Pre-verification time
         histogram!("xelis_verify_batch_pre_ms").record(start.elapsed().as_millis() as f64);
 
+        // Small batches are verified on the current thread
+        // otherwise, reserve the threads from the shared pool
+        let permit = if config.should_parallelize(prepared.len()) && is_multi_threads_supported() {
+            Some(config.acquire_threads(config.range_proofs_chunks(prepared.len())))
+        } else {
+            None
+        };
+
         // Spawn a dedicated thread for the ZK Proofs verification
         // this prevent us from blocking the current thread
         spawn_blocking_safe(move || {
+            if let Some(permit) = permit.filter(|permit| permit.count() > 0) {
+                let chunks = permit.count();
+                trace!("verifying range proofs in {} chunks", chunks);
+                let start = Instant::now();
+                let chunk_size = prepared.len().div_ceil(chunks);
+
+                // Range proofs are verified in their own threads
+                // while the sigma proofs are verified on the current one
+                thread::scope(|scope| {
+                    let handles = prepared.chunks_mut(chunk_size)
+                        .map(|chunk| scope.spawn(move || Self::verify_range_proofs_batch(chunk)))
+                        .collect::<Vec<_>>();
+
+                    let sigma = sigma_batch_collector
+                        .verify()
+                        .map_err(|_| ProofVerificationError::GenericProof);
+
+                    for handle in handles {
+                        handle.join()
+                            .unwrap_or_else(|e| panic::resume_unwind(e))?;
+                    }
+
+                    sigma
+                })?;
+
+                histogram!("xelis_verify_batch_range_ms").record(start.elapsed().as_millis() as f64);
+                return Ok(());
+            }
+
             let start = Instant::now();
             sigma_batch_collector
                 .verify()
@@ -1100,20 +1364,7 @@ impl Transaction {
 
             if !prepared.is_empty() {
                 let start = Instant::now();
-                RangeProof::verify_batch(
-                    prepared.iter_mut()
-                        .map(|(tx, transcript, commitments)| {
-                            tx.range_proof
-                                .verification_view(
-                                    transcript,
-                                    commitments,
-                                    BULLET_PROOF_SIZE
-                                )
-                        }),
-                    &BP_GENS,
-                    &PC_GENS,
-                )
-                .map_err(ProofVerificationError::from)?;
+                Self::verify_range_proofs_batch(&mut prepared)?;
 
                 histogram!("xelis_verify_batch_range_ms").record(start.elapsed().as_millis() as f64);
             } else {
@@ -1134,11 +1385,18 @@ impl Transaction {
         tx_hash: &'a Hash,
         state: &mut B,
         cache: &C,
+        config: &VerificationConfig,
     ) -> Result<(), VerificationError<E>>
     where
         B: BlockchainVerificationState<'a, E>,
         C: ZKPCache<E>
     {
+        self.verify_sender_solvency(state).await?;
+
+        // Load the contracts modules before their verification
+        state.preload_modules_for(self).await
+            .map_err(VerificationError::State)?;
+
         let mut sigma_batch_collector = BatchCollector::default();
         let dynamic_parts_only = cache.is_already_verified(tx_hash).await
             .map_err(VerificationError::State)?;
@@ -1152,32 +1410,75 @@ impl Transaction {
             Some((res, Arc::clone(&self)))
         };
 
+        // A single transaction is verified on the current thread
+        // unless the batch threshold allows it and a thread is available
+        let permit = if res.is_some() && config.should_parallelize(1) && is_multi_threads_supported() {
+            Some(config.acquire_threads(1))
+        } else {
+            None
+        };
+
         // Block in place instead of spawning a dedicated thread to reduce overhead
         // verification is expected to be fast enough to not block anything
         spawn_blocking_safe(move || {
+            let verify_range_proof = |res: Option<((Transcript, Vec<(RistrettoPoint, CompressedRistretto)>), Arc<Transaction>)>| {
+                if let Some(((mut transcript, commitments), tx)) = res {
+                    trace!("Verifying range proof");
+                    RangeProof::verify_multiple(
+                        &tx.range_proof,
+                        &BP_GENS,
+                        &PC_GENS,
+                        &mut transcript,
+                        &commitments,
+                        BULLET_PROOF_SIZE,
+                    ).map_err(ProofVerificationError::from)
+                } else {
+                    Ok(())
+                }
+            };
+
+            if permit.as_ref().is_some_and(|permit| permit.count() > 0) {
+                // Verify the range proof in its own thread
+                return thread::scope(|scope| {
+                    let handle = scope.spawn(|| verify_range_proof(res));
+
+                    trace!("Verifying sigma proofs");
+                    let sigma = sigma_batch_collector
+                        .verify()
+                        .map_err(|_| ProofVerificationError::GenericProof);
+
+                    handle.join()
+                        .unwrap_or_else(|e| panic::resume_unwind(e))?;
+
+                    sigma
+                });
+            }
+
             trace!("Verifying sigma proofs");
             sigma_batch_collector
                 .verify()
                 .map_err(|_| ProofVerificationError::GenericProof)?;
 
-            if let Some(((mut transcript, commitments), tx)) = res {
-                trace!("Verifying range proof");
-                RangeProof::verify_multiple(
-                    &tx.range_proof,
-                    &BP_GENS,
-                    &PC_GENS,
-                    &mut transcript,
-                    &commitments,
-                    BULLET_PROOF_SIZE,
-                ).map_err(ProofVerificationError::from)
-            } else {
-                Ok(())
-            }
+            verify_range_proof(res)
         }).await.context("spawning blocking thread for ZK verification")??;
  
         Ok(())
     }
 
+    // Guard against applying the same transaction twice to the state
+    // It must be called before any output is applied
+    async fn mark_applied<'a, P: ContractProvider, E, B: BlockchainApplyState<'a, P, E>>(
+        tx_hash: &Hash,
+        state: &mut B,
+    ) -> Result<(), VerificationError<E>> {
+        if state.is_tx_applied(tx_hash).await.map_err(VerificationError::State)? {
+            return Err(VerificationError::AlreadyApplied(tx_hash.clone()));
+        }
+
+        state.mark_tx_applied(tx_hash).await
+            .map_err(VerificationError::State)
+    }
+
     // Apply the transaction to the state
     // Arc is required around Self to be shared easily into the VM if needed
     async fn apply<'a, P: ContractProvider, E, B: BlockchainApplyState<'a, P, E>>(
@@ -1223,6 +1524,10 @@ impl Transaction {
             TransactionType::MultiSig(payload) => {
                 state.set_multisig_state(&self.source, payload).await.map_err(VerificationError::State)?;
             },
+            TransactionType::MultiSigUpdate(payload) => {
+                let updated = self.get_updated_multisig(state, payload).await?;
+                state.set_multisig_state(&self.source, &updated).await.map_err(VerificationError::State)?;
+            },
             TransactionType::InvokeContract(payload) => {
                 if self.is_contract_available(state, &payload.contract).await? {
                     vm::invoke_contract(
@@ -1246,8 +1551,11 @@ impl Transaction {
                 }
             },
             TransactionType::DeployContract(payload) => {
-                state.set_contract_module(tx_hash, &payload.contract).await
-                    .map_err(VerificationError::State)?;
+                // An upgrade has no constructor to run
+                match payload.upgrade.as_ref() {
+                    Some(upgrade) => state.upgrade_contract_module(upgrade, &payload.contract).await,
+                    None => state.set_contract_module(tx_hash, &payload.contract).await
+                }.map_err(VerificationError::State)?;
 
                 if let Some(invoke) = payload.invoke.as_ref() {
                     let result = vm::invoke_contract(
@@ -1287,6 +1595,8 @@ impl Transaction {
         tx_hash: &'a Hash,
         state: &mut B,
     ) -> Result<(), VerificationError<E>> {
+        Self::mark_applied(tx_hash, state).await?;
+
         let mut transfers_decompressed = Vec::new();
         let mut deposits_decompressed = HashMap::new();
         match &self.data {
@@ -1355,6 +1665,8 @@ impl Transaction {
         state: &mut B
     ) -> Result<(), VerificationError<E>> {
         trace!("apply with partial verify");
+        Self::mark_applied(tx_hash, state).await?;
+
         let mut sigma_batch_collector = BatchCollector::default();
 
         let mut transfers_decompressed = Vec::new();
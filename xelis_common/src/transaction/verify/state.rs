@@ -69,6 +69,18 @@ pub trait BlockchainVerificationState<'a, E> {
         reference: &Reference,
     ) -> Result<&'b mut Ciphertext, E>;
 
+    /// Get the plaintext balance of the sender account, if known
+    /// This is used to reject underfunded transactions before
+    /// verifying any ZK Proof
+    /// By default, no plaintext balance is known
+    async fn get_sender_plaintext_balance(
+        &mut self,
+        _account: &'a CompressedPublicKey,
+        _asset: &'a Hash,
+    ) -> Result<Option<u64>, E> {
+        Ok(None)
+    }
+
     /// Apply new output to a sender account
     async fn add_sender_output(
         &mut self,
@@ -117,6 +129,13 @@ pub trait BlockchainVerificationState<'a, E> {
         module: &'a ContractModule,
     ) -> Result<(), E>;
 
+    /// Replace the module of an already deployed contract
+    async fn upgrade_contract_module(
+        &mut self,
+        hash: &'a Hash,
+        module: &'a ContractModule,
+    ) -> Result<(), E>;
+
     /// Load in the cache the contract module
     /// This is called before `get_contract_module_with_environment`
     /// Returns true if the module is available
@@ -125,12 +144,32 @@ pub trait BlockchainVerificationState<'a, E> {
         hash: Cow<'a, Hash>
     ) -> Result<bool, E>;
 
+    /// Load in the cache all the contract modules referenced by the transaction
+    /// This is called before the verification to reduce the storage calls
+    async fn preload_modules_for(
+        &mut self,
+        tx: &'a Transaction
+    ) -> Result<(), E> {
+        for contract in tx.referenced_contracts() {
+            self.load_contract_module(Cow::Borrowed(contract)).await?;
+        }
+
+        Ok(())
+    }
+
     /// Get the contract module with the environment
     /// This is used to verify that all parameters are correct
     async fn get_contract_module_with_environment(
         &self,
         hash: &'a Hash
     ) -> Result<(&Module, &Environment<ContractMetadata>), E>;
+
+    /// Get the account allowed to upgrade the contract deployed at this hash
+    /// Returns None if the contract is immutable or not deployed
+    async fn get_contract_upgrade_authority(
+        &mut self,
+        hash: &'a Hash
+    ) -> Result<Option<CompressedPublicKey>, E>;
 }
 
 pub struct ContractEnvironment<'a, P: ContractProvider> {
@@ -147,6 +186,8 @@ pub struct ContractEnvironment<'a, P: ContractProvider> {
 #[async_trait]
 pub trait BlockchainContractState<'a, P: ContractProvider, E> {
     /// Track the contract logs
+    /// Logs are provided in their emission order, including
+    /// the ones from nested calls, and must be stored as is
     async fn set_contract_logs(
         &mut self,
         caller: ContractCaller<'a>,
@@ -206,12 +247,21 @@ pub trait BlockchainApplyState<'a, P: ContractProvider, E>: BlockchainVerificati
     /// Add burned XELIS
     async fn add_burned_coins(&mut self, asset: &Hash, amount: u64) -> Result<(), E>;
 
+    /// Get the coins burned for an asset during this apply
+    async fn get_burned_coins(&self, asset: &Hash) -> Result<u64, E>;
+
     /// Add fee XELIS
     async fn add_gas_fee(&mut self, amount: u64) -> Result<(), E>;
 
     /// Add burned XELIS fee
     async fn add_burned_fee(&mut self, amount: u64) -> Result<(), E>;
 
+    /// Was the transaction already applied to this state
+    async fn is_tx_applied(&self, hash: &Hash) -> Result<bool, E>;
+
+    /// Mark the transaction as applied to this state
+    async fn mark_tx_applied(&mut self, hash: &Hash) -> Result<(), E>;
+
     /// Is mainnet network
     fn is_mainnet(&self) -> bool;
 }
\ No newline at end of file
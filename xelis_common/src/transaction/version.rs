@@ -1,5 +1,8 @@
 use schemars::JsonSchema;
-use crate::serializer::{Reader, ReaderError, Serializer, Writer};
+use crate::{
+    config::MAX_TRANSACTION_SIZE,
+    serializer::{Reader, ReaderError, Serializer, Writer}
+};
 use core::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
@@ -11,6 +14,33 @@ pub enum TxVersion {
     V1 = 1,
     // Smart Contracts
     V2 = 2,
+    // MultiSig updates, memo to self, contract upgrades
+    V3 = 3,
+}
+
+impl TxVersion {
+    // Check if the multisig setup can be updated using a delta
+    pub fn has_multisig_update(&self) -> bool {
+        *self >= TxVersion::V3
+    }
+
+    // Check if the transaction can carry an encrypted memo to self
+    pub fn has_self_memo(&self) -> bool {
+        *self >= TxVersion::V3
+    }
+
+    // Check if a deploy can replace the module of an upgradeable contract
+    pub fn has_contract_upgrade(&self) -> bool {
+        *self >= TxVersion::V3
+    }
+
+    // Maximum serialized size of a transaction
+    // Same limit for all versions for now
+    pub const fn max_size(&self) -> usize {
+        match self {
+            TxVersion::V0 | TxVersion::V1 | TxVersion::V2 | TxVersion::V3 => MAX_TRANSACTION_SIZE
+        }
+    }
 }
 
 impl Default for TxVersion {
@@ -27,6 +57,7 @@ impl TryFrom<u8> for TxVersion {
             0 => Ok(TxVersion::V0),
             1 => Ok(TxVersion::V1),
             2 => Ok(TxVersion::V2),
+            3 => Ok(TxVersion::V3),
             _ => Err(()),
         }
     }
@@ -38,6 +69,7 @@ impl Into<u8> for TxVersion {
             TxVersion::V0 => 0,
             TxVersion::V1 => 1,
             TxVersion::V2 => 2,
+            TxVersion::V3 => 3,
         }
     }
 }
@@ -55,6 +87,7 @@ impl Serializer for TxVersion {
             TxVersion::V0 => writer.write_u8(0),
             TxVersion::V1 => writer.write_u8(1),
             TxVersion::V2 => writer.write_u8(2),
+            TxVersion::V3 => writer.write_u8(3),
         }
     }
 
@@ -75,6 +108,7 @@ impl fmt::Display for TxVersion {
             TxVersion::V0 => write!(f, "V0"),
             TxVersion::V1 => write!(f, "V1"),
             TxVersion::V2 => write!(f, "V2"),
+            TxVersion::V3 => write!(f, "V3"),
         }
     }
 }
@@ -132,5 +166,12 @@ mod tests {
         assert!(version0 < version1);
         assert!(version1 < version2);
         assert!(version0 < version2);
+        assert!(version2 < TxVersion::V3);
+    }
+
+    #[test]
+    fn test_tx_version_v3() {
+        let read_version = TxVersion::from_bytes(&TxVersion::V3.to_bytes()).unwrap();
+        assert_eq!(read_version, TxVersion::V3);
     }
 }
\ No newline at end of file
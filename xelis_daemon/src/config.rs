@@ -24,6 +24,10 @@ pub const DEFAULT_RPC_BIND_ADDRESS: &str = "0.0.0.0:8080";
 
 // Default cache size for storage DB
 pub const DEFAULT_CACHE_SIZE: usize = 1024;
+// Minimum serialized size of a contract data to be shared between reads
+pub const SHARED_CONTRACT_DATA_MIN_SIZE: usize = 256;
+// Minimum count of block hashes the block hash filter is sized for
+pub const BLOCK_HASH_FILTER_MIN_CAPACITY: usize = 1 << 20;
 
 // Block rules
 // Millis per second, it is used to prevent having random 1000 values anywhere
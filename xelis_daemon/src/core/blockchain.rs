@@ -65,7 +65,7 @@ use xelis_common::{
         TimestampMillis
     },
     transaction::{
-        verify::BlockchainVerificationState,
+        verify::{BlockchainVerificationState, VerificationConfig},
         Transaction,
         TransactionType
     },
@@ -231,6 +231,8 @@ pub struct Blockchain<S: Storage> {
     // in differents groups and will verify them in parallel
     // If set to one, it will use the main thread directly
     txs_verification_threads_count: usize,
+    // Limits used during the ZK Proofs verification
+    verification_config: VerificationConfig,
     // Disable the ZKP Cache
     disable_zkp_cache: bool,
     // Max concurrency allowed for general tasks
@@ -273,6 +275,11 @@ impl<S: Storage> Blockchain<S> {
                 info!("Will use {} threads for TXs verification", config.txs_verification_threads_count);
             }
 
+            if config.max_parallel_proofs == 0 || config.proofs_batch_threshold == 0 {
+                error!("Max parallel proofs and proofs batch threshold must be above 0");
+                return Err(BlockchainError::InvalidConfig.into());
+            }
+
             if config.rpc.threads == 0 {
                 error!("RPC threads count must be above 0");
                 return Err(BlockchainError::InvalidConfig.into())
@@ -306,9 +313,12 @@ impl<S: Storage> Blockchain<S> {
             .map(|version| (version, Arc::new(build_environment::<S>(version).build())))
             .collect();
 
+        let verification_config = VerificationConfig::new(config.max_parallel_proofs, config.proofs_batch_threshold);
+        info!("Will verify proofs using up to {} threads with a batch threshold of {}", verification_config.max_parallel_proofs(), verification_config.batch_threshold());
+
         info!("Initializing chain...");
         let blockchain = Self {
-            mempool: RwLock::new(Mempool::new(network, config.disable_zkp_cache)),
+            mempool: RwLock::new(Mempool::new(network, config.disable_zkp_cache, verification_config.clone())),
             storage: RwLock::new(storage),
             storage_semaphore: Semaphore::new(1),
             pre_verify_block_semaphore: Semaphore::new(config.pre_verify_block_threads_count),
@@ -322,6 +332,7 @@ impl<S: Storage> Blockchain<S> {
             skip_block_template_txs_verification: config.skip_block_template_txs_verification,
             checkpoints: config.checkpoints.into_iter().collect(),
             txs_verification_threads_count: config.txs_verification_threads_count,
+            verification_config,
             flush_db_every_n_blocks: config.flush_db_every_n_blocks,
             disable_zkp_cache: config.disable_zkp_cache,
             concurrency: config.concurrency,
@@ -530,6 +541,12 @@ impl<S: Storage> Blockchain<S> {
         self.txs_verification_threads_count
     }
 
+    // Get the configured limits for the ZK Proofs verification
+    #[inline]
+    pub fn get_verification_config(&self) -> &VerificationConfig {
+        &self.verification_config
+    }
+
     // Get the storage semaphore
     // must be acquired before any storage modification
     #[inline]
@@ -1627,6 +1644,7 @@ impl<S: Storage> Blockchain<S> {
                             orphaned_txs.iter(),
                             &mut chain_state,
                             &tx_cache,
+                            &self.verification_config,
                         ).await {
                             warn!("Orphaned TXs for source {} are not valid anymore: {}, in orphaned txs list: {}, processed: {}", source.as_address(self.network.is_mainnet()), e, grouped_orphaned_txs.contains_key(&source), processed_sources.contains(&source));
                             failed_sources.insert(source);
@@ -1639,6 +1657,7 @@ impl<S: Storage> Blockchain<S> {
                         &hash,
                         &mut chain_state,
                         &tx_cache,
+                        &self.verification_config,
                     ).await {
                         warn!("TX {} ({}) is not valid for mining: {}", hash, source.as_address(self.network.is_mainnet()), e);
                         failed_sources.insert(source);
@@ -1700,6 +1719,8 @@ impl<S: Storage> Blockchain<S> {
             return Err(BlockchainError::InvalidBlockVersion)
         }
 
+        block.validate_miner(&self.network)?;
+
         if block.get_tips().len() > TIPS_LIMIT {
             let block_hash = block_hash.map(Immutable::into_owned)
                 .unwrap_or_else(|| block.hash());
@@ -1754,6 +1775,9 @@ impl<S: Storage> Blockchain<S> {
             return Err(BlockchainError::InvalidBlockVersion)
         }
 
+        // Verify that the miner key can receive the block rewards
+        block.validate_miner(&self.network)?;
+
         // Either check or use the precomputed one
         let (block_hash, pow_hash) = match pre_verify {
             PreVerifyBlock::Hash(hash) => (hash, None),
@@ -2110,6 +2134,7 @@ impl<S: Storage> Blockchain<S> {
                     let storage = &*storage;
                     let environments = &self.environments;
                     let cache = &tx_cache;
+                    let verification_config = &self.verification_config;
 
                     // We run the batches in concurrent tasks
                     // But, because Transaction#verify_batch is actually spawning a blocking thread
@@ -2117,14 +2142,14 @@ impl<S: Storage> Blockchain<S> {
                     stream::iter(batches.into_iter().map(Ok))
                         .try_for_each_concurrent(self.txs_verification_threads_count, async |txs| {
                             let mut chain_state = ChainState::new(storage, environments, stable_topoheight, current_topoheight, version, base_fee, base_height);
-                            Transaction::verify_batch(txs.iter(), &mut chain_state, cache).await
+                            Transaction::verify_batch(txs.iter(), &mut chain_state, cache, verification_config).await
                         }).await
                 } else {
                     // Verify all valid transactions in one batch
                     let mut chain_state = ChainState::new(&*storage, &self.environments, stable_topoheight, current_topoheight, version, base_fee, base_height);
                     let iter = txs_grouped.values()
                         .flatten();
-                    Transaction::verify_batch(iter, &mut chain_state, &tx_cache).await
+                    Transaction::verify_batch(iter, &mut chain_state, &tx_cache, &self.verification_config).await
                 }.context(format!("Failed to verify transactions in block {}", block_hash))?;
 
                 debug!("Verified {} transactions in {}ms", total_txs, start.elapsed().as_millis());
@@ -2510,10 +2535,11 @@ impl<S: Storage> Blockchain<S> {
                                     }
                                 }
                             },
-                            TransactionType::DeployContract(_) => {
+                            TransactionType::DeployContract(payload) => {
                                 if should_track_events.contains(&NotifyEvent::ContractDeploy) {
+                                    // An upgrade keeps the hash of the upgraded contract
                                     let value = json!(ContractDeployEvent {
-                                        contract: Cow::Borrowed(&tx_hash),
+                                        contract: Cow::Borrowed(payload.upgrade.as_ref().unwrap_or(&tx_hash)),
                                         block_hash: Cow::Borrowed(&hash),
                                         topoheight: highest_topo,
                                     });
@@ -2647,6 +2673,8 @@ impl<S: Storage> Blockchain<S> {
                     debug!("Processed contracts events in {}ms", start.elapsed().as_millis());
                 }
 
+                debug!("Contracts cache is using approximately {} bytes", chain_state.total_cache_memory());
+
                 // apply changes from Chain State
                 let finalizer = chain_state.finalize().await?;
 
@@ -3554,6 +3582,7 @@ pub fn get_block_dev_fee(height: u64) -> u64 {
 
 #[cfg(test)]
 mod tests {
+    use xelis_common::{block::MinerKeyError, crypto::elgamal::CompressedPublicKey};
     use super::*;
 
     #[test]
@@ -3589,4 +3618,14 @@ mod tests {
         // Max block size EMA is up to x11 the required base fee
         assert_eq!(calculate_required_base_fee(MAX_BLOCK_SIZE), FEE_PER_KB * 11);
     }
+
+    #[test]
+    fn test_block_identity_miner_rejected() {
+        // The identity point is encoded as zeroes
+        let miner = CompressedPublicKey::from_bytes(&[0u8; 32]).unwrap();
+        let header = BlockHeader::new(BlockVersion::V6, 0, 0, IndexSet::new(), [0u8; EXTRA_NONCE_SIZE], miner, IndexSet::new());
+
+        let result: Result<(), BlockchainError> = header.validate_miner(&Network::Mainnet).map_err(Into::into);
+        assert!(matches!(result, Err(BlockchainError::InvalidBlockMiner(MinerKeyError::Identity(_)))));
+    }
 }
\ No newline at end of file
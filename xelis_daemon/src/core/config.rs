@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use xelis_common::{
     crypto::Hash,
     prompt::LogLevel,
+    transaction::verify::DEFAULT_BATCH_THRESHOLD,
     utils::detect_available_parallelism
 };
 use crate::{
@@ -49,6 +50,10 @@ const fn default_p2p_concurrency_task_count_limit() -> usize {
     P2P_DEFAULT_CONCURRENCY_TASK_COUNT_LIMIT
 }
 
+const fn default_proofs_batch_threshold() -> usize {
+    DEFAULT_BATCH_THRESHOLD
+}
+
 const fn default_chain_sync_response_blocks() -> usize {
     CHAIN_SYNC_DEFAULT_RESPONSE_BLOCKS
 }
@@ -540,6 +545,18 @@ pub struct Config {
     #[clap(long, default_value_t = detect_available_parallelism())]
     #[serde(default = "detect_available_parallelism")]
     pub txs_verification_threads_count: usize,
+    /// Set the maximum threads verifying ZK Proofs at the same time.
+    /// This limit is shared by all the verifications running concurrently.
+    /// By default, will detect the best value.
+    /// If set to 1, proofs are verified on a single thread.
+    #[clap(long, default_value_t = detect_available_parallelism())]
+    #[serde(default = "detect_available_parallelism")]
+    pub max_parallel_proofs: usize,
+    /// Set the minimum range proofs count in a batch
+    /// before splitting its verification across several threads.
+    #[clap(long, default_value_t = default_proofs_batch_threshold())]
+    #[serde(default = "default_proofs_batch_threshold")]
+    pub proofs_batch_threshold: usize,
     /// Se the threads count to use during block pre verification.
     /// By default, will detect the best value.
     #[clap(long, default_value_t = detect_available_parallelism())]
@@ -15,7 +15,7 @@ use xelis_common::{
     contract::ContractVersion,
     tokio::sync::AcquireError,
     account::Nonce,
-    block::TopoHeight,
+    block::{MinerKeyError, TopoHeight},
     difficulty::DifficultyError,
     prompt::PromptError,
     rpc::InternalRpcError,
@@ -309,6 +309,8 @@ pub enum BlockchainError {
     InvalidTipsDifficulty(Hash, Hash),
     #[error("Invalid block version")]
     InvalidBlockVersion,
+    #[error("Invalid block miner key: {0}")]
+    InvalidBlockMiner(#[from] MinerKeyError),
     #[error("Invalid tx version")]
     InvalidTxVersion,
     #[error("Block is already in chain")]
@@ -426,6 +428,7 @@ impl From<VerificationError<BlockchainError>> for BlockchainError {
             VerificationError::TransferCount => BlockchainError::TransferCount,
             VerificationError::Commitments => BlockchainError::Commitments,
             VerificationError::TransactionExtraDataSize => BlockchainError::InvalidTransactionExtraData,
+            VerificationError::SelfMemoSize => BlockchainError::InvalidTransactionExtraData,
             VerificationError::TransferExtraDataSize => BlockchainError::InvalidTransferExtraData,
             VerificationError::MultiSigNotConfigured => BlockchainError::MultiSigNotConfigured,
             VerificationError::MultiSigParticipants => BlockchainError::MultiSigParticipants,
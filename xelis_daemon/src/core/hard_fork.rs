@@ -227,6 +227,10 @@ mod tests {
 
         assert!(is_tx_version_allowed_in_block_version(TxVersion::V2, BlockVersion::V4));
         assert!(is_tx_version_allowed_in_block_version(TxVersion::V2, BlockVersion::V5));
+
+        // TX V3 is not enabled yet
+        assert!(!is_tx_version_allowed_in_block_version(TxVersion::V3, BlockVersion::V5));
+        assert!(!is_tx_version_allowed_in_block_version(TxVersion::V3, BlockVersion::V6));
     }
 
     #[test]
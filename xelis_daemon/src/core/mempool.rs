@@ -29,6 +29,7 @@ use xelis_common::{
     serializer::Serializer,
     time::{get_current_time_in_seconds, TimestampSeconds},
     transaction::{
+        verify::VerificationConfig,
         MultiSigPayload,
         Transaction
     }
@@ -81,16 +82,19 @@ pub struct Mempool {
     // store all sender's nonce for faster finding
     caches: HashMap<PublicKey, AccountCache>,
     disable_zkp_cache: bool,
+    // Limits used during the TXs proofs verification
+    verification_config: VerificationConfig,
 }
 
 impl Mempool {
     // Create a new empty mempool
-    pub fn new(network: Network, disable_zkp_cache: bool) -> Self {
+    pub fn new(network: Network, disable_zkp_cache: bool, verification_config: VerificationConfig) -> Self {
         Mempool {
             mainnet: network.is_mainnet(),
             txs: LinkedHashMap::new(),
             caches: HashMap::new(),
             disable_zkp_cache,
+            verification_config,
         }
     }
 
@@ -147,7 +151,7 @@ impl Mempool {
     pub async fn add_tx<S: Storage>(&mut self, storage: &S, environments: &ContractEnvironments, stable_topoheight: TopoHeight, topoheight: TopoHeight, tx_base_fee: u64, base_height: u64, hash: Arc<Hash>, tx: Arc<Transaction>, size: usize, block_version: BlockVersion) -> Result<(), BlockchainError> {
         let mut state = MempoolState::new(&self, storage, environments, stable_topoheight, topoheight, block_version, self.mainnet, tx_base_fee, base_height);
         let tx_cache = TxCache::new(storage, self, self.disable_zkp_cache);
-        tx.verify(&hash, &mut state, &tx_cache).await?;
+        tx.verify(&hash, &mut state, &tx_cache, &self.verification_config).await?;
 
         let (balances, multisig) = state.get_sender_cache(tx.get_source())
             .ok_or_else(|| BlockchainError::AccountNotFound(tx.get_source().as_address(self.mainnet)))?;
@@ -533,7 +537,7 @@ impl Mempool {
                     let tx_cache = TxCache::new(storage, &self, self.disable_zkp_cache);
                     if let Some((next_tx, tx_hash)) = first_tx {
                         let mut state = MempoolState::new(&self, storage, environments, stable_topoheight, topoheight, block_version, self.mainnet, tx_base_fee, base_height);
-                        if let Err(e) = Transaction::verify(next_tx.get_tx(), &tx_hash, &mut state, &tx_cache).await {
+                        if let Err(e) = Transaction::verify(next_tx.get_tx(), &tx_hash, &mut state, &tx_cache, &self.verification_config).await {
                             warn!("Error while verifying TXs for source {}: {}", key.as_address(self.mainnet), e);
 
                             // We may have only one TX invalid, but because they are all linked to each others we delete the whole cache
@@ -23,6 +23,7 @@ use xelis_common::{
         ContractLog,
         ContractMetadata,
         ContractModule,
+        ContractStorage,
         ContractVersion,
         ExecutionsChanges,
         ExecutionsManager,
@@ -93,6 +94,8 @@ pub struct ApplicableChainState<'s, 'b, S: Storage> {
     contract_manager: ContractManager<'b>,
     total_fees: u64,
     total_fees_burned: u64,
+    // Coins burned per asset in this block
+    burned_coins: HashMap<Hash, u64>,
     // Transactions links to store: tx hash -> (blocks linked, executed in, contract)
     transactions_links: HashMap<&'b Hash, (IndexSet<&'b Hash>, Option<&'b Hash>, Option<&'b Hash>)>,
 }
@@ -291,14 +294,18 @@ impl<'a> FinalizedChainState<'a> {
         debug!("Storing contract storage changes");
         // Apply all the contract storage changes
         for (contract, cache) in self.contract_manager.caches {
-            // Apply all storage changes
-            for (key, value) in cache.storage {
-                if let Some((state, value)) = value {
-                    if state.should_be_stored() {
-                        trace!("Saving contract data {} key {} at topoheight {}", contract, key, self.topoheight);
-                        storage.set_last_contract_data_to(&contract, &key, self.topoheight, &VersionedContractData::new(value, state.get_topoheight())).await?;
-                    }
-                }
+            // Apply all storage changes at once
+            // linked to the version they were loaded from
+            let writes = cache.storage.into_iter()
+                .filter_map(|(key, value)| match value {
+                    Some((state, value)) if state.should_be_stored() => Some((key, VersionedContractData::new(value, state.get_topoheight()))),
+                    _ => None
+                })
+                .collect::<Vec<_>>();
+
+            if !writes.is_empty() {
+                trace!("Saving {} contract data for {} at topoheight {}", writes.len(), contract, self.topoheight);
+                storage.commit_data_batch(&contract, self.topoheight, writes).await?;
             }
 
             for (asset, data) in cache.balances {
@@ -559,6 +566,14 @@ impl<'s, 'b, S: Storage> BlockchainVerificationState<'b, BlockchainError> for Ap
         self.inner.set_contract_module(hash, module).await
     }
 
+    async fn upgrade_contract_module(
+        &mut self,
+        hash: &'b Hash,
+        module: &'b ContractModule
+    ) -> Result<(), BlockchainError> {
+        self.inner.upgrade_contract_module(hash, module).await
+    }
+
     async fn load_contract_module(
         &mut self,
         hash: Cow<'b, Hash>
@@ -572,6 +587,13 @@ impl<'s, 'b, S: Storage> BlockchainVerificationState<'b, BlockchainError> for Ap
     ) -> Result<(&xelis_vm::Module, &Environment<ContractMetadata>), BlockchainError> {
         self.inner.get_contract_module_with_environment(hash).await
     }
+
+    async fn get_contract_upgrade_authority(
+        &mut self,
+        hash: &'b Hash
+    ) -> Result<Option<PublicKey>, BlockchainError> {
+        self.inner.get_contract_upgrade_authority(hash).await
+    }
 }
 
 #[async_trait]
@@ -586,9 +608,18 @@ impl<'s, 'b, S: Storage> BlockchainApplyState<'b, S, BlockchainError> for Applic
         changes.circulating_supply.1 = new_supply;
         changes.circulating_supply.0.mark_updated();
 
+        let burned = self.burned_coins.entry(asset.clone()).or_insert(0);
+        *burned = burned.checked_add(amount)
+            .context("Burned coins overflow")?;
+
         Ok(())
     }
 
+    /// Burned supply tracked in this block
+    async fn get_burned_coins(&self, asset: &Hash) -> Result<u64, BlockchainError> {
+        Ok(self.burned_coins.get(asset).copied().unwrap_or(0))
+    }
+
     /// Track miner fees
     async fn add_gas_fee(&mut self, amount: u64) -> Result<(), BlockchainError> {
         self.gas_fee = self.gas_fee.checked_add(amount)
@@ -602,6 +633,23 @@ impl<'s, 'b, S: Storage> BlockchainApplyState<'b, S, BlockchainError> for Applic
         Ok(())
     }
 
+    /// Transaction executed in this state or in a previous block
+    async fn is_tx_applied(&self, hash: &Hash) -> Result<bool, BlockchainError> {
+        let executed = self.transactions_links.get(hash)
+            .is_some_and(|(_, executed_in, _)| executed_in.is_some());
+
+        if executed {
+            return Ok(true)
+        }
+
+        self.inner.storage.is_tx_executed_in_a_block(hash).await
+    }
+
+    // Already tracked by `mark_tx_as_executed_in_block` once the TX is applied
+    async fn mark_tx_applied(&mut self, _: &Hash) -> Result<(), BlockchainError> {
+        Ok(())
+    }
+
     fn is_mainnet(&self) -> bool {
         self.inner.storage.is_mainnet()
     }
@@ -633,7 +681,7 @@ impl<'s, 'b, S: Storage> BlockchainContractState<'b, S, BlockchainError> for App
         caller: ContractCaller<'c>,
         permission: Cow<'c, InterContractPermission>,
     ) -> Result<(ContractEnvironment<'c, S>, ContractChainState<'c>), BlockchainError> {
-        debug!("get contract environments for contract {} from caller {}", contract_hash, caller.get_hash());
+        debug!("get contract environments for contract {} from caller {}", contract_hash, caller);
 
         // Find the contract module in our cache
         // We don't use the function `get_contract_module_with_environment` because we need to return the mutable storage
@@ -838,7 +886,7 @@ impl<'s, 'b, S: Storage> BlockchainContractState<'b, S, BlockchainError> for App
         caller: &ContractCaller<'b>,
         contract: &Hash,
     ) -> Result<(), BlockchainError> {
-        trace!("post contract execution for caller {} on contract {}", caller.get_hash(), contract);
+        trace!("post contract execution for caller {} on contract {}", caller, contract);
         self.execute_callback_events(caller.get_hash().as_ref()).await
     }
 }
@@ -893,6 +941,7 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
             ),
             total_fees: 0,
             total_fees_burned: 0,
+            burned_coins: HashMap::new(),
             contract_manager: ContractManager::default(),
             block_hash,
             block,
@@ -976,6 +1025,13 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
         &self.contract_manager.caches
     }
 
+    // Approximate memory used by all the contracts cache
+    pub fn total_cache_memory(&self) -> usize {
+        self.contract_manager.caches.values()
+            .map(ContractCache::approx_memory_bytes)
+            .sum()
+    }
+
     // Get the contract tracker
     pub fn get_contract_tracker(&self) -> &ContractEventTracker {
         &self.contract_manager.tracker
@@ -1011,7 +1067,7 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
 
             // If we've already processed those from storage, we must handle those pending in memory
             let contract_key = (event.contract.clone(), event.event_id);
-            let callbacks = match self.contract_manager.events_processed.entry(contract_key.clone()) {
+            let mut callbacks = match self.contract_manager.events_processed.entry(contract_key.clone()) {
                 Entry::Occupied(_) => {
                     debug!("event {} for contract {} already processed from storage, getting pending callbacks from memory", event.event_id, event.contract);
                     // we don't need to include them into our processed list, because we just delete them from registrations
@@ -1021,12 +1077,9 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
                 Entry::Vacant(entry) => {
                     debug!("event {} for contract {} already processed, skipping", event.event_id, event.contract);
                     let topoheight = self.inner.topoheight;
-                    let mut callbacks = self.inner.storage.get_event_callbacks_available_at_maximum_topoheight(&event.contract, event.event_id, topoheight).await?
+                    let callbacks = self.inner.storage.get_event_callbacks_available_at_maximum_topoheight(&event.contract, event.event_id, topoheight).await?
                         .collect::<Result<Vec<_>, _>>()?;
 
-                    // Sort by contract hash to have a deterministic order
-                    callbacks.sort_by(|a, b| a.0.cmp(&b.0));
-
                     entry.insert(
                         callbacks.iter()
                             .map(|(contract, _)| contract.clone())
@@ -1037,6 +1090,12 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
                 }
             };
 
+            // Pending callbacks from memory are in registration order
+            // Starting V6, sort them by listener contract hash like the stored ones
+            if self.inner.block_version >= BlockVersion::V6 {
+                callbacks.sort_by(|a, b| a.0.cmp(&b.0));
+            }
+
             for (contract, callback) in callbacks {
                 debug!("processing event callback of {}", contract);
                 self.process_execution(
@@ -1067,10 +1126,10 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
         params: impl DoubleEndedIterator<Item = ValueCell> + ExactSizeIterator,
         post_hook: bool,
     ) -> Result<(), BlockchainError> {
-        debug!("processing scheduled execution of contract {} with caller {}", contract, caller.get_hash());
+        debug!("processing scheduled execution of contract {} with caller {}", contract, caller);
 
         if !self.load_contract_module(contract.clone()).await? {
-            warn!("failed to load contract module for scheduled execution of contract {} with caller {}", contract, caller.get_hash());
+            warn!("failed to load contract module for scheduled execution of contract {} with caller {}", contract, caller);
             return Ok(());
         }
 
@@ -1086,7 +1145,7 @@ impl<'s, 'b, S: Storage> ApplicableChainState<'s, 'b, S> {
             Cow::Owned(InterContractPermission::All),
             post_hook,
         ).await {
-            warn!("failed to process execution of contract {} with caller {}: {}", contract, caller.get_hash(), e);
+            warn!("failed to process execution of contract {} with caller {}: {}", contract, caller, e);
         }
 
         Ok(())
@@ -449,12 +449,39 @@ impl<'s, 'b, S: Storage> BlockchainVerificationState<'b, BlockchainError> for Ch
         Ok(())
     }
 
+    async fn upgrade_contract_module(
+        &mut self,
+        hash: &'b Hash,
+        module: &'b ContractModule
+    ) -> Result<(), BlockchainError> {
+        let (version, existing) = self.internal_get_versioned_contract(Cow::Borrowed(hash)).await?
+            .as_mut()
+            .filter(|(_, existing)| existing.is_some())
+            .ok_or_else(|| BlockchainError::ContractNotFound(hash.clone()))?;
+
+        version.mark_updated();
+        *existing = Some(Cow::Borrowed(module));
+
+        Ok(())
+    }
+
     async fn load_contract_module(
         &mut self,
         hash: Cow<'b, Hash>
     ) -> Result<bool, BlockchainError> {
-        self.internal_get_versioned_contract(hash).await
-            .map(|v| v.as_ref().is_some_and(|(_, m)| m.is_some()))
+        let dependencies = match self.internal_get_versioned_contract(hash).await? {
+            Some((_, Some(module))) => module.dependencies.clone(),
+            _ => return Ok(false)
+        };
+
+        // Prefetch the declared dependencies so
+        // inter contract calls are served from our cache
+        for dependency in dependencies {
+            trace!("Prefetching contract dependency {}", dependency);
+            self.internal_get_versioned_contract(Cow::Owned(dependency)).await?;
+        }
+
+        Ok(true)
     }
 
     /// Get the contract module with the environments
@@ -469,4 +496,16 @@ impl<'s, 'b, S: Storage> BlockchainVerificationState<'b, BlockchainError> for Ch
 
         Ok((&module.module, environment))
     }
+
+    async fn get_contract_upgrade_authority(
+        &mut self,
+        hash: &'b Hash
+    ) -> Result<Option<PublicKey>, BlockchainError> {
+        let authority = self.internal_get_versioned_contract(Cow::Borrowed(hash)).await?
+            .as_ref()
+            .and_then(|(_, module)| module.as_ref())
+            .and_then(|module| module.upgrade_authority.clone());
+
+        Ok(authority)
+    }
 }
\ No newline at end of file
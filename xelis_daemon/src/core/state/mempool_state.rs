@@ -325,6 +325,20 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for Mempoo
         Ok(())
     }
 
+    async fn upgrade_contract_module(
+        &mut self,
+        hash: &'a Hash,
+        module: &'a ContractModule,
+    ) -> Result<(), BlockchainError> {
+        if !self.load_contract_module(Cow::Borrowed(hash)).await? {
+            return Err(BlockchainError::ContractNotFound(hash.clone()));
+        }
+
+        self.contracts.insert(Cow::Borrowed(hash), Cow::Borrowed(module));
+
+        Ok(())
+    }
+
     async fn load_contract_module(
         &mut self,
         hash: Cow<'a, Hash>
@@ -344,6 +358,33 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for Mempoo
         }
     }
 
+    // Load all the missing modules in one storage call
+    // Missing contracts are skipped instead of failing
+    // so the verification can report them properly
+    async fn preload_modules_for(
+        &mut self,
+        tx: &'a Transaction
+    ) -> Result<(), BlockchainError> {
+        let missing = tx.referenced_contracts()
+            .into_iter()
+            .filter(|hash| !self.contracts.contains_key(*hash))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(())
+        }
+
+        let modules = self.storage.get_contracts_at_maximum_topoheight_for(&missing, self.topoheight).await?;
+        for (hash, module) in missing.into_iter().zip(modules) {
+            if let Some(module) = module.and_then(|(_, v)| v.take()) {
+                self.contracts.insert(Cow::Owned(hash), Cow::Owned(module.into_owned()));
+            }
+        }
+
+        Ok(())
+    }
+
     async fn get_contract_module_with_environment(
         &self,
         hash: &'a Hash
@@ -355,4 +396,18 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for Mempoo
             .ok_or(BlockchainError::ContractEnvironmentNotFound(module.version))?;
         Ok((&module.module, environment))
     }
+
+    async fn get_contract_upgrade_authority(
+        &mut self,
+        hash: &'a Hash
+    ) -> Result<Option<PublicKey>, BlockchainError> {
+        if let Some(module) = self.contracts.get(hash) {
+            return Ok(module.upgrade_authority.clone())
+        }
+
+        let authority = self.storage.get_contract_at_maximum_topoheight_for(hash, self.topoheight).await?
+            .and_then(|(_, v)| v.take().and_then(|m| m.upgrade_authority.clone()));
+
+        Ok(authority)
+    }
 }
\ No newline at end of file
@@ -282,4 +282,122 @@ pub (super) async fn search_versioned_balance_for_reference<S: DagOrderProvider
     };
 
     Ok((use_output_balance, new_version,  version))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use tempdir::TempDir;
+    use xelis_common::{
+        account::{CiphertextCache, Nonce},
+        config::COIN_VALUE,
+        crypto::{elgamal::Ciphertext, KeyPair},
+        network::Network,
+        transaction::{
+            builder::{
+                AccountState,
+                FeeBuilder,
+                FeeHelper,
+                TransactionBuilder,
+                TransactionTypeBuilder,
+                TransferBuilder
+            },
+            TxVersion
+        }
+    };
+    use crate::core::{config::RocksDBConfig, storage::RocksStorage};
+    use super::*;
+
+    struct State {
+        balance: u64,
+        ciphertext: CiphertextCache,
+        nonce: Nonce,
+    }
+
+    impl FeeHelper for State {
+        type Error = Infallible;
+
+        fn account_exists(&self, _: &PublicKey) -> Result<bool, Self::Error> {
+            Ok(true)
+        }
+    }
+
+    impl AccountState for State {
+        fn is_mainnet(&self) -> bool {
+            false
+        }
+
+        fn get_account_balance(&self, _: &Hash) -> Result<u64, Self::Error> {
+            Ok(self.balance)
+        }
+
+        fn get_reference(&self) -> Reference {
+            Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            }
+        }
+
+        fn get_account_ciphertext(&self, _: &Hash) -> Result<CiphertextCache, Self::Error> {
+            Ok(self.ciphertext.clone())
+        }
+
+        fn update_account_balance(&mut self, _: &Hash, new_balance: u64, ciphertext: Ciphertext) -> Result<(), Self::Error> {
+            self.balance = new_balance;
+            self.ciphertext = CiphertextCache::Decompressed(None, ciphertext);
+            Ok(())
+        }
+
+        fn get_nonce(&self) -> Result<Nonce, Self::Error> {
+            Ok(self.nonce)
+        }
+
+        fn update_nonce(&mut self, new_nonce: Nonce) -> Result<(), Self::Error> {
+            self.nonce = new_nonce;
+            Ok(())
+        }
+    }
+
+    fn create_tx(version: TxVersion) -> Transaction {
+        let keypair = KeyPair::new();
+        let destination = KeyPair::new().get_public_key().to_address(false);
+        let balance = 100 * COIN_VALUE;
+        let mut state = State {
+            balance,
+            ciphertext: CiphertextCache::Decompressed(None, keypair.get_public_key().encrypt(balance)),
+            nonce: 0,
+        };
+
+        let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+            amount: 50,
+            destination,
+            asset: XELIS_ASSET,
+            extra_data: None,
+            encrypt_extra_data: true,
+        }]);
+
+        TransactionBuilder::new(version, keypair.get_public_key().compress(), None, data, FeeBuilder::default())
+            .build(&mut state, &keypair)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pre_verify_tx_version() {
+        let tmp_dir = TempDir::new("pre-verify-tx-version").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        // TX V2 is accepted in the latest blocks
+        let tx = create_tx(TxVersion::V2);
+        assert!(pre_verify_tx(&storage, &tx, 0, 0, 0, BlockVersion::V6).await.is_ok());
+
+        // TX V3 isn't enabled yet and must be rejected before reaching the mempool or a block
+        let tx = create_tx(TxVersion::V3);
+        for version in [BlockVersion::V3, BlockVersion::V4, BlockVersion::V5, BlockVersion::V6] {
+            assert!(matches!(
+                pre_verify_tx(&storage, &tx, 0, 0, 0, version).await,
+                Err(BlockchainError::InvalidTxVersion)
+            ));
+        }
+    }
+}
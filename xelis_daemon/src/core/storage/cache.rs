@@ -2,7 +2,7 @@ use std::{
     collections::HashSet,
     num::NonZeroUsize,
     ops::{Deref, DerefMut},
-    sync::Arc
+    sync::{Arc, Mutex as StdMutex}
 };
 
 use indexmap::IndexSet;
@@ -12,12 +12,18 @@ use xelis_common::{
     block::{BlockHeader, TopoHeight},
     crypto::Hash,
     difficulty::{CumulativeDifficulty, Difficulty},
+    serializer::Serializer,
     transaction::Transaction
 };
 
-use crate::config::{DEFAULT_CACHE_SIZE, GENESIS_BLOCK_DIFFICULTY};
+use crate::config::{
+    BLOCK_HASH_FILTER_MIN_CAPACITY,
+    DEFAULT_CACHE_SIZE,
+    GENESIS_BLOCK_DIFFICULTY,
+    SHARED_CONTRACT_DATA_MIN_SIZE
+};
 
-use super::Tips;
+use super::{Tips, VersionedContractData};
 
 #[macro_export]
 macro_rules! init_cache {
@@ -215,4 +221,209 @@ impl DerefMut for StorageCache {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.counter
     }
+}
+
+// Share the large contract data between reads
+// Repeated reads of the same versioned data are served
+// from the same allocation instead of being decoded again
+// Key is the versioned data key on disk
+#[derive(Debug)]
+pub struct SharedContractDataCache {
+    values: StdMutex<LruCache<Vec<u8>, Arc<VersionedContractData>>>,
+}
+
+impl SharedContractDataCache {
+    pub fn new(cache_size: usize) -> Self {
+        Self {
+            values: StdMutex::new(LruCache::new(NonZeroUsize::new(cache_size).expect("Non zero value for cache")))
+        }
+    }
+
+    // Get the shared data for this key if present
+    pub fn get(&self, key: &[u8]) -> Option<Arc<VersionedContractData>> {
+        self.values.lock()
+            .expect("shared contract data lock")
+            .get(key)
+            .cloned()
+    }
+
+    // Wrap the data and share it if its large enough
+    pub fn intern(&self, key: Vec<u8>, data: VersionedContractData) -> Arc<VersionedContractData> {
+        let large = data.size() >= SHARED_CONTRACT_DATA_MIN_SIZE;
+        let data = Arc::new(data);
+        if large {
+            self.values.lock()
+                .expect("shared contract data lock")
+                .put(key, data.clone());
+        }
+
+        data
+    }
+
+    // Remove the data as it got overwritten
+    pub fn remove(&self, key: &[u8]) {
+        self.values.lock()
+            .expect("shared contract data lock")
+            .pop(key);
+    }
+
+    // Check if no data is shared
+    pub fn is_empty(&self) -> bool {
+        self.values.lock()
+            .expect("shared contract data lock")
+            .is_empty()
+    }
+
+    // Clear all the shared data
+    pub fn clear(&self) {
+        self.values.lock()
+            .expect("shared contract data lock")
+            .clear();
+    }
+}
+
+impl Default for SharedContractDataCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_SIZE)
+    }
+}
+
+// Counters used per expected block hash
+const BLOCK_HASH_FILTER_COUNTERS_PER_ITEM: usize = 8;
+// Counters updated per block hash
+const BLOCK_HASH_FILTER_HASHES: u64 = 4;
+// Each counter is stored on 4 bits
+const BLOCK_HASH_FILTER_COUNTER_MAX: u8 = 0x0F;
+
+// Counting bloom filter over the block hashes stored on disk
+// It is used to answer quickly for the blocks we don't have:
+// a negative answer is always right, a positive one must be checked on disk
+// It is not part of the snapshots: a block saved is always added,
+// but a block is only removed once deleted from the disk
+// so a discarded snapshot can't create a false negative
+#[derive(Debug)]
+pub struct BlockHashFilter {
+    // Two counters per byte
+    counters: Vec<u8>,
+    len: u64,
+}
+
+impl BlockHashFilter {
+    // Create a filter sized for the expected count of block hashes
+    pub fn new(expected: usize) -> Self {
+        let len = expected.saturating_mul(2).max(BLOCK_HASH_FILTER_MIN_CAPACITY) * BLOCK_HASH_FILTER_COUNTERS_PER_ITEM;
+        Self {
+            counters: vec![0; len.div_ceil(2)],
+            len: len as u64,
+        }
+    }
+
+    // Block hashes are uniformly distributed
+    // so we derive all the positions from the hash itself
+    fn positions(&self, hash: &Hash) -> impl Iterator<Item = usize> {
+        let bytes = hash.as_bytes();
+        let h1 = u64::from_le_bytes(bytes[0..8].try_into().expect("8 bytes"));
+        let h2 = u64::from_le_bytes(bytes[8..16].try_into().expect("8 bytes")) | 1;
+        let len = self.len;
+
+        (0..BLOCK_HASH_FILTER_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        (self.counters[index / 2] >> ((index % 2) * 4)) & BLOCK_HASH_FILTER_COUNTER_MAX
+    }
+
+    fn set(&mut self, index: usize, value: u8) {
+        let shift = (index % 2) * 4;
+        let byte = &mut self.counters[index / 2];
+        *byte = (*byte & !(BLOCK_HASH_FILTER_COUNTER_MAX << shift)) | (value << shift);
+    }
+
+    // Add a block hash to the filter
+    pub fn insert(&mut self, hash: &Hash) {
+        for index in self.positions(hash) {
+            let value = self.get(index);
+            if value < BLOCK_HASH_FILTER_COUNTER_MAX {
+                self.set(index, value + 1);
+            }
+        }
+    }
+
+    // Remove a block hash previously added
+    // Saturated counters are never decremented as their real count is lost
+    pub fn remove(&mut self, hash: &Hash) {
+        for index in self.positions(hash) {
+            let value = self.get(index);
+            if value > 0 && value < BLOCK_HASH_FILTER_COUNTER_MAX {
+                self.set(index, value - 1);
+            }
+        }
+    }
+
+    // Returns false only if the block hash was never added
+    pub fn may_contain(&self, hash: &Hash) -> bool {
+        self.positions(hash).all(|index| self.get(index) > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::crypto::hash;
+    use xelis_vm::{Primitive, ValueCell};
+    use super::*;
+
+    fn versioned_bytes(len: usize) -> VersionedContractData {
+        VersionedContractData::new(Some(ValueCell::Bytes(vec![1u8; len])), None)
+    }
+
+    #[test]
+    fn test_shared_contract_data() {
+        let cache = SharedContractDataCache::default();
+        let key = b"large".to_vec();
+        assert!(cache.get(&key).is_none());
+
+        let first = cache.intern(key.clone(), versioned_bytes(SHARED_CONTRACT_DATA_MIN_SIZE));
+        let second = cache.get(&key).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        // Small values are not shared
+        let small = VersionedContractData::new(Some(Primitive::U64(1).into()), None);
+        cache.intern(b"small".to_vec(), small);
+        assert!(cache.get(b"small").is_none());
+
+        // Overwritten data is not served anymore
+        cache.remove(&key);
+        assert!(cache.get(&key).is_none());
+    }
+    #[test]
+    fn test_block_hash_filter() {
+        let mut filter = BlockHashFilter::new(10_000);
+        let present = (0..10_000u64).map(|i| hash(&i.to_be_bytes())).collect::<Vec<_>>();
+        let absent = (10_000..20_000u64).map(|i| hash(&i.to_be_bytes())).collect::<Vec<_>>();
+
+        for hash in &present {
+            filter.insert(hash);
+        }
+
+        // No false negatives
+        assert!(present.iter().all(|hash| filter.may_contain(hash)));
+
+        // Only a few false positives
+        let false_positives = absent.iter().filter(|hash| filter.may_contain(hash)).count();
+        assert!(false_positives < absent.len() / 100, "{} false positives", false_positives);
+
+        // Removing half of the hashes keeps the other half
+        let (removed, kept) = present.split_at(5_000);
+        for hash in removed {
+            filter.remove(hash);
+        }
+        assert!(kept.iter().all(|hash| filter.may_contain(hash)));
+
+        // A hash added twice is kept until removed twice
+        let hash = &absent[0];
+        filter.insert(hash);
+        filter.insert(hash);
+        filter.remove(hash);
+        assert!(filter.may_contain(hash));
+    }
 }
\ No newline at end of file
@@ -43,7 +43,7 @@ pub trait Storage:
     + NonceProvider + AccountProvider + ClientProtocolProvider + BlockDagProvider
     + MerkleHashProvider + NetworkProvider + MultiSigProvider + TipsProvider
     + SnapshotProvider + ContractProvider + VersionedProvider + AssetCirculatingSupplyProvider
-    + CacheProvider + StateProvider
+    + CacheProvider + StateProvider + StateDiffProvider
     + Sync + Send + 'static {
     // delete block at topoheight, and all pointers (hash_at_topo, topo_by_hash, reward, supply, diff, cumulative diff...)
     async fn delete_block_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(Hash, Immutable<BlockHeader>, Vec<(Hash, Immutable<Transaction>)>), BlockchainError>;
@@ -20,6 +20,16 @@ pub trait AssetProvider {
     // Get the asset data from its hash and topoheight at which it got registered
     async fn get_asset_at_topoheight(&self, hash: &Hash, topoheight: TopoHeight) -> Result<VersionedAssetData, BlockchainError>;
 
+    // Get the asset data stored at exact topoheight
+    // None if the asset has no version at this topoheight
+    async fn get_asset_at_exact_topoheight(&self, hash: &Hash, topoheight: TopoHeight) -> Result<Option<VersionedAssetData>, BlockchainError> {
+        if !self.has_asset(hash).await? || !self.has_asset_at_exact_topoheight(hash, topoheight).await? {
+            return Ok(None)
+        }
+
+        self.get_asset_at_topoheight(hash, topoheight).await.map(Some)
+    }
+
     // Check that the asset has been registered <= maximum topoheight  
     async fn is_asset_registered_at_maximum_topoheight(&self, hash: &Hash, topoheight: TopoHeight) -> Result<bool, BlockchainError>;
 
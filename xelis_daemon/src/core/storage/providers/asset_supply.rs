@@ -6,6 +6,8 @@ use xelis_common::{
 };
 use crate::core::error::BlockchainError;
 
+use super::AssetProvider;
+
 pub type VersionedSupply = Versioned<u64>;
 
 // Circulating Supply Provider is used for non-native assets being tracked
@@ -14,7 +16,7 @@ pub type VersionedSupply = Versioned<u64>;
 // (in case of mint/burn mechanisms).
 // Only the circulating supply can be ensured to be valid in the 0..2^64 range
 #[async_trait]
-pub trait AssetCirculatingSupplyProvider {
+pub trait AssetCirculatingSupplyProvider: AssetProvider {
     // Verify if we have a supply already set for this asset
     async fn has_circulating_supply_for_asset(&self, asset: &Hash) -> Result<bool, BlockchainError>;
 
@@ -24,9 +26,37 @@ pub trait AssetCirculatingSupplyProvider {
     // Get the supply at exact topoheight
     async fn get_circulating_supply_for_asset_at_exact_topoheight(&self, asset: &Hash, topoheight: TopoHeight) -> Result<VersionedSupply, BlockchainError>;
 
+    // Get the supply stored at exact topoheight
+    // None if the asset has no supply version at this topoheight
+    async fn get_supply_at_exact_topoheight(&self, asset: &Hash, topoheight: TopoHeight) -> Result<Option<VersionedSupply>, BlockchainError> {
+        if !self.has_asset(asset).await? || !self.has_circulating_supply_for_asset_at_exact_topoheight(asset, topoheight).await? {
+            return Ok(None)
+        }
+
+        self.get_circulating_supply_for_asset_at_exact_topoheight(asset, topoheight).await.map(Some)
+    }
+
     // Get the supply at the maximum topoheight
     async fn get_circulating_supply_for_asset_at_maximum_topoheight(&self, asset: &Hash, topoheight: TopoHeight) -> Result<Option<(TopoHeight, VersionedSupply)>, BlockchainError>;
 
+    // Get all the assets with their supply at the maximum topoheight
+    // Assets without any supply at this topoheight are skipped
+    // Ordered by asset id
+    async fn get_assets_with_supply(&self, max_topoheight: TopoHeight) -> Result<Vec<(Hash, u64)>, BlockchainError> {
+        let mut assets = self.get_assets().await?
+            .collect::<Result<Vec<_>, _>>()?;
+        assets.sort();
+
+        let mut supplies = Vec::with_capacity(assets.len());
+        for asset in assets {
+            if let Some((_, supply)) = self.get_circulating_supply_for_asset_at_maximum_topoheight(&asset, max_topoheight).await? {
+                supplies.push((asset, supply.take()));
+            }
+        }
+
+        Ok(supplies)
+    }
+
     // Set the latest supply pointer for this asset and store the versioned data
     async fn set_last_circulating_supply_for_asset(&mut self, asset: &Hash, topoheight: TopoHeight, supply: &VersionedSupply) -> Result<(), BlockchainError>;
 }
\ No newline at end of file
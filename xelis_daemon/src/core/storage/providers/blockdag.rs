@@ -21,6 +21,10 @@ pub trait BlockDagProvider: DagOrderProvider + BlockProvider {
     // Set the metadata for topoheight
     async fn get_metadata_at_topoheight(&self, topoheight: TopoHeight) -> Result<TopoHeightMetadata, BlockchainError>;
 
+    // Get all the metadata stored between the two topoheights (inclusive)
+    // Entries are returned in ascending topoheight order
+    async fn get_topoheight_metadata_range(&self, from: TopoHeight, to: TopoHeight) -> Result<Vec<(TopoHeight, TopoHeightMetadata)>, BlockchainError>;
+
     // Set the metadata for topoheight
     async fn set_metadata_at_topoheight(&mut self, topoheight: TopoHeight, metadata: TopoHeightMetadata) -> Result<(), BlockchainError>;
 }
\ No newline at end of file
@@ -22,6 +22,19 @@ pub trait ContractBalanceProvider {
     // Get the balance under or equal topoheight requested for asset and contract
     async fn get_contract_balance_at_maximum_topoheight(&self, contract: &Hash, asset: &Hash, topoheight: TopoHeight) -> Result<Option<(TopoHeight, VersionedContractBalance)>, BlockchainError>;
 
+    // Get the balances under or equal topoheight requested for asset and several contracts
+    // Results are in the same order as the contracts, None if no balance was found
+    async fn get_contract_balances_at_maximum_topoheight(&self, contracts: &[Hash], asset: &Hash, topoheight: TopoHeight) -> Result<Vec<Option<(TopoHeight, u64)>>, BlockchainError> {
+        let mut balances = Vec::with_capacity(contracts.len());
+        for contract in contracts {
+            let balance = self.get_contract_balance_at_maximum_topoheight(contract, asset, topoheight).await?
+                .map(|(topo, version)| (topo, version.take()));
+            balances.push(balance);
+        }
+
+        Ok(balances)
+    }
+
     // Get the last topoheight that the contract has a balance
     async fn get_last_topoheight_for_contract_balance(&self, contract: &Hash, asset: &Hash) -> Result<Option<TopoHeight>, BlockchainError>;
 
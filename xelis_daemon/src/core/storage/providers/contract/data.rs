@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use futures::Stream;
 use xelis_vm::ValueCell;
@@ -26,9 +28,47 @@ pub trait ContractDataProvider {
     // Retrieve a contract data at maximum topoheight
     async fn get_contract_data_at_maximum_topoheight_for<'a>(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<(TopoHeight, VersionedContractData)>, BlockchainError>;
 
+    // Retrieve a contract data at a given topoheight
+    // The version may be shared with other reads, use it when the value is only read
+    async fn get_shared_contract_data_at_exact_topoheight_for(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Result<Arc<VersionedContractData>, BlockchainError> {
+        self.get_contract_data_at_exact_topoheight_for(contract, key, topoheight).await
+            .map(Arc::new)
+    }
+
+    // Retrieve a contract data at maximum topoheight
+    // The version may be shared with other reads, use it when the value is only read
+    async fn get_shared_contract_data_at_maximum_topoheight_for(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<(TopoHeight, Arc<VersionedContractData>)>, BlockchainError> {
+        self.get_contract_data_at_maximum_topoheight_for(contract, key, maximum_topoheight).await
+            .map(|res| res.map(|(topoheight, version)| (topoheight, Arc::new(version))))
+    }
+
     // Retrieve the topoheight of a contract data at maximum topoheight
     async fn get_contract_data_topoheight_at_maximum_topoheight_for<'a>(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<TopoHeight>, BlockchainError>;
 
+    // Retrieve several contract data at maximum topoheight
+    // The order of the keys is kept, None is returned for absent or deleted entries
+    async fn get_contract_data_batch(&self, contract: &Hash, keys: &[ValueCell], topoheight: TopoHeight) -> Result<Vec<Option<ValueCell>>, BlockchainError> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            let value = self.get_contract_data_at_maximum_topoheight_for(contract, key, topoheight).await?
+                .and_then(|(_, version)| version.take());
+            values.push(value);
+        }
+
+        Ok(values)
+    }
+
+    // Set several contract data at the same topoheight
+    // Each version already points to its previous topoheight
+    // A None value is stored as a deletion
+    async fn set_contract_data_batch(&mut self, contract: &Hash, topoheight: TopoHeight, writes: Vec<(ValueCell, VersionedContractData)>) -> Result<(), BlockchainError> {
+        for (key, version) in writes {
+            self.set_last_contract_data_to(contract, &key, topoheight, &version).await?;
+        }
+
+        Ok(())
+    }
+
     // Check if a contract data exists at a given topoheight
     // If the version is None, it returns false
     async fn has_contract_data_at_maximum_topoheight(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Result<bool, BlockchainError>;
@@ -34,6 +34,7 @@ pub trait ContractEventCallbackProvider {
 
     // Get all latest versions for a specific contract event 
     // Returns (listener_contract, version) for each latest version
+    // Listeners are ordered by their contract hash
     async fn get_event_callbacks_for_event_at_maximum_topoheight<'a>(
         &'a self,
         contract: &'a Hash,
@@ -43,6 +44,7 @@ pub trait ContractEventCallbackProvider {
 
     // Get all latest versions for a specific contract event 
     // Returns (listener_contract, version) for each latest version
+    // Listeners are ordered by their contract hash, this is the dispatch order
     async fn get_event_callbacks_available_at_maximum_topoheight<'a>(
         &'a self,
         contract: &'a Hash,
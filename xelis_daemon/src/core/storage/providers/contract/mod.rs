@@ -41,6 +41,17 @@ pub trait ContractProvider: ContractDataProvider + ContractLogsProvider + Contra
     // Retrieve a contract at maximum topoheight
     async fn get_contract_at_maximum_topoheight_for<'a>(&self, hash: &Hash, maximum_topoheight: TopoHeight) -> Result<Option<(TopoHeight, VersionedContractModule<'a>)>, BlockchainError>;
 
+    // Retrieve several contracts at maximum topoheight
+    // Results are in the same order as the hashes, None if no contract was found
+    async fn get_contracts_at_maximum_topoheight_for<'a>(&self, hashes: &[Hash], maximum_topoheight: TopoHeight) -> Result<Vec<Option<(TopoHeight, VersionedContractModule<'a>)>>, BlockchainError> {
+        let mut contracts = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            contracts.push(self.get_contract_at_maximum_topoheight_for(hash, maximum_topoheight).await?);
+        }
+
+        Ok(contracts)
+    }
+
     // Retrieve all the contracts hashes
     async fn get_contracts<'a>(&'a self, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight) -> Result<impl Iterator<Item = Result<Hash, BlockchainError>> + 'a, BlockchainError>;
 
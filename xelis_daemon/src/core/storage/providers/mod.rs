@@ -20,6 +20,7 @@ mod contract;
 mod versioned;
 mod cache;
 mod state;
+mod state_diff;
 mod asset_supply;
 
 pub use asset::*;
@@ -44,4 +45,5 @@ pub use contract::*;
 pub use versioned::*;
 pub use cache::*;
 pub use state::*;
+pub use state_diff::*;
 pub use asset_supply::*;
\ No newline at end of file
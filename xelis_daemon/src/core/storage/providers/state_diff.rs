@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use xelis_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use xelis_vm::ValueCell;
+
+use crate::core::error::BlockchainError;
+
+// All the state entries that changed between two topoheights
+// Each entry is mapped to the highest topoheight it changed at
+#[derive(Debug, Default)]
+pub struct StateDiff {
+    // Account balances per asset
+    pub balances: HashMap<(PublicKey, Hash), TopoHeight>,
+    // Account nonces
+    pub nonces: HashMap<PublicKey, TopoHeight>,
+    // Contract data entries per contract
+    pub contracts_data: HashMap<(Hash, ValueCell), TopoHeight>,
+}
+
+impl StateDiff {
+    // Returns true if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty()
+            && self.nonces.is_empty()
+            && self.contracts_data.is_empty()
+    }
+}
+
+#[async_trait]
+pub trait StateDiffProvider {
+    // Enumerate the balances, nonces and contract data entries
+    // that changed in the topoheight range (from, to]
+    async fn diff_between(&self, from: TopoHeight, to: TopoHeight) -> Result<StateDiff, BlockchainError>;
+}
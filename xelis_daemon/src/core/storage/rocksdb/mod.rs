@@ -45,9 +45,11 @@ use crate::core::{
             IteratorMode,
             Snapshot as InternalSnapshot
         },
+        BlockHashFilter,
         BlockProvider,
         ClientProtocolProvider,
         ContractLogsProvider,
+        SharedContractDataCache,
         StorageCache,
         TransactionProvider
     }
@@ -156,6 +158,10 @@ pub struct RocksStorage {
     network: Network,
     snapshot: Option<Snapshot>,
     cache: StorageCache,
+    // Large contract data shared between reads
+    shared_contract_data: SharedContractDataCache,
+    // Filter over the block hashes stored on disk
+    block_hash_filter: BlockHashFilter,
 }
 
 impl RocksStorage {
@@ -214,12 +220,31 @@ impl RocksStorage {
         let db  = DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(&opts, format!("{}{}", dir, network.to_string().to_lowercase()), cfs)
             .expect("Failed to open RocksDB");
 
+        let block_hash_filter = Self::load_block_hash_filter(&db)
+            .expect("Failed to load the block hash filter");
+
         Self {
             db: Arc::new(db),
             network,
             snapshot: None,
-            cache: StorageCache::new(None)
+            cache: StorageCache::new(None),
+            shared_contract_data: SharedContractDataCache::default(),
+            block_hash_filter,
+        }
+    }
+
+    // Build the block hash filter from all the blocks stored on disk
+    fn load_block_hash_filter(db: &InnerDB) -> Result<BlockHashFilter, BlockchainError> {
+        trace!("load block hash filter");
+        let count: u64 = Self::load_optional_from_disk_internal(db, None, Column::Common, BLOCKS_COUNT)?
+            .unwrap_or(0);
+
+        let mut filter = BlockHashFilter::new(count as usize);
+        for hash in Self::iter_keys_internal::<Hash>(db, None, IteratorMode::Start, Column::Blocks)? {
+            filter.insert(&hash?);
         }
+
+        Ok(filter)
     }
 
     #[inline(always)]
@@ -299,6 +324,38 @@ impl RocksStorage {
         Self::load_optional_from_disk_internal(&self.db, self.snapshot.as_ref(), column, key)
     }
 
+    // Load several keys in one batched read
+    // Snapshot entries take priority over the DB
+    pub fn load_many_optional_from_disk<K: AsRef<[u8]>, V: Serializer>(&self, column: Column, keys: &[K]) -> Result<Vec<Option<V>>, BlockchainError> {
+        trace!("load many {:?} from disk", column);
+
+        let mut values = Vec::with_capacity(keys.len());
+        // Indexes of the keys to read from the DB
+        let mut missing = Vec::new();
+        for (i, key) in keys.iter().enumerate() {
+            match self.snapshot.as_ref().map(|s| s.get(column, key.as_ref())) {
+                Some(EntryState::Stored(v)) => values.push(Some(V::from_bytes(&v)?)),
+                Some(EntryState::Deleted) => values.push(None),
+                _ => {
+                    values.push(None);
+                    missing.push(i);
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            let cf = cf_handle!(self.db, column);
+            let results = self.db.batched_multi_get_cf(&cf, missing.iter().map(|i| keys[*i].as_ref()), false);
+            for (i, res) in missing.into_iter().zip(results) {
+                if let Some(bytes) = res.with_context(|| format!("Internal error while reading column {:?}", column))? {
+                    values[i] = Some(V::from_bytes(&bytes)?);
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
     pub fn load_from_disk<K: AsRef<[u8]> + ?Sized, V: Serializer>(&self, column: Column, key: &K) -> Result<V, BlockchainError> {
         trace!("load from disk internal {:?}", column);
 
@@ -559,9 +616,41 @@ impl Storage for RocksStorage {
 
 #[cfg(test)]
 mod tests {
+    use std::{borrow::Cow, sync::Arc};
+    use futures::TryStreamExt;
     use itertools::Itertools;
     use rocksdb::{Direction, IteratorMode, Options, ReadOptions, SliceTransform, DB};
     use tempdir::TempDir;
+    use xelis_common::{
+        account::{VersionedBalance, VersionedNonce},
+        asset::{AssetData, AssetOwner, MaxSupplyMode},
+        contract::{ContractModule, ContractStorage},
+        crypto::{Hash, KeyPair},
+        network::Network,
+        serializer::Serializer,
+        versioned_type::Versioned
+    };
+    use crate::{
+        config::SHARED_CONTRACT_DATA_MIN_SIZE,
+        core::{
+            config::RocksDBConfig,
+            storage::{
+                AssetCirculatingSupplyProvider,
+                AssetProvider,
+                BalanceProvider,
+                BlockDagProvider,
+                ContractBalanceProvider,
+                ContractDataProvider,
+                ContractProvider,
+                NonceProvider,
+                StateDiffProvider,
+                VersionedContractProvider,
+                types::TopoHeightMetadata
+            }
+        }
+    };
+    use xelis_vm::{Chunk, Module, Primitive, ValueCell};
+    use super::RocksStorage;
 
     #[test]
     fn test_rocks_db_iterator_behavior() {
@@ -723,4 +812,341 @@ mod tests {
         // Bounds are inclusive, and 150 isn't included
         assert!(suffix == vec![69, 50, 30, 15, 0]);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_contract_balances_at_maximum_topoheight() {
+        let tmp_dir = TempDir::new("rocksdb-contract-balances").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let asset = Hash::new([1u8; 32]);
+        let data = AssetData::new(8, "Test".to_owned(), "TST".to_owned(), MaxSupplyMode::None, AssetOwner::None);
+        storage.add_asset(&asset, 0, Versioned::new(data, None)).await.unwrap();
+
+        let a = Hash::new([2u8; 32]);
+        let b = Hash::new([3u8; 32]);
+        let c = Hash::new([4u8; 32]);
+        for contract in [&a, &b, &c] {
+            storage.set_last_contract_to(contract, 0, &Versioned::new(None, None)).await.unwrap();
+        }
+
+        storage.set_last_contract_balance_to(&a, &asset, 5, Versioned::new(100, None)).await.unwrap();
+        storage.set_last_contract_balance_to(&a, &asset, 10, Versioned::new(200, Some(5))).await.unwrap();
+        storage.set_last_contract_balance_to(&b, &asset, 3, Versioned::new(50, None)).await.unwrap();
+
+        // c has no balance for the asset and the order is kept
+        let balances = storage.get_contract_balances_at_maximum_topoheight(&[b.clone(), c.clone(), a.clone()], &asset, 7).await.unwrap();
+        assert_eq!(balances, vec![Some((3, 50)), None, Some((5, 100))]);
+
+        let balances = storage.get_contract_balances_at_maximum_topoheight(&[a, b, c], &asset, 10).await.unwrap();
+        assert_eq!(balances, vec![Some((10, 200)), Some((3, 50)), None]);
+    }
+
+    #[tokio::test]
+    async fn test_asset_and_supply_at_exact_topoheight() {
+        let tmp_dir = TempDir::new("rocksdb-asset-exact-topoheight").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let asset = Hash::new([1u8; 32]);
+        let data = AssetData::new(8, "Test".to_owned(), "TST".to_owned(), MaxSupplyMode::None, AssetOwner::None);
+        storage.add_asset(&asset, 5, Versioned::new(data.clone(), None)).await.unwrap();
+        storage.set_last_circulating_supply_for_asset(&asset, 5, &Versioned::new(1000, None)).await.unwrap();
+
+        let stored = storage.get_asset_at_exact_topoheight(&asset, 5).await.unwrap().unwrap();
+        assert_eq!(stored.get().to_bytes(), data.to_bytes());
+        assert_eq!(storage.get_supply_at_exact_topoheight(&asset, 5).await.unwrap().map(|v| v.take()), Some(1000));
+
+        // Nothing was written at topoheight 6
+        assert!(storage.get_asset_at_exact_topoheight(&asset, 6).await.unwrap().is_none());
+        assert!(storage.get_supply_at_exact_topoheight(&asset, 6).await.unwrap().is_none());
+
+        // Unknown asset
+        let unknown = Hash::new([2u8; 32]);
+        assert!(storage.get_asset_at_exact_topoheight(&unknown, 5).await.unwrap().is_none());
+        assert!(storage.get_supply_at_exact_topoheight(&unknown, 5).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_assets_with_supply() {
+        let tmp_dir = TempDir::new("rocksdb-assets-with-supply").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let a = Hash::new([2u8; 32]);
+        let b = Hash::new([1u8; 32]);
+        for asset in [&a, &b] {
+            let data = AssetData::new(8, "Test".to_owned(), "TST".to_owned(), MaxSupplyMode::None, AssetOwner::None);
+            storage.add_asset(asset, 0, Versioned::new(data, None)).await.unwrap();
+        }
+
+        storage.set_last_circulating_supply_for_asset(&a, 0, &Versioned::new(500, None)).await.unwrap();
+        storage.set_last_circulating_supply_for_asset(&a, 10, &Versioned::new(700, Some(0))).await.unwrap();
+        storage.set_last_circulating_supply_for_asset(&b, 3, &Versioned::new(300, None)).await.unwrap();
+
+        // b has no supply yet
+        assert_eq!(storage.get_assets_with_supply(2).await.unwrap(), vec![(a.clone(), 500)]);
+
+        // Ordered by asset id
+        assert_eq!(storage.get_assets_with_supply(5).await.unwrap(), vec![(b.clone(), 300), (a.clone(), 500)]);
+        assert_eq!(storage.get_assets_with_supply(10).await.unwrap(), vec![(b, 300), (a, 700)]);
+    }
+
+    #[tokio::test]
+    async fn test_topoheight_metadata_range() {
+        let tmp_dir = TempDir::new("rocksdb-topoheight-metadata").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        for topoheight in [1, 3, 4, 8, 12] {
+            let metadata = TopoHeightMetadata {
+                block_reward: topoheight * 10,
+                emitted_supply: topoheight * 100,
+                total_fees: topoheight,
+                total_fees_burned: 0,
+            };
+            storage.set_metadata_at_topoheight(topoheight, metadata).await.unwrap();
+        }
+
+        let entries = storage.get_topoheight_metadata_range(3, 8).await.unwrap();
+        let topoheights = entries.iter().map(|(topoheight, _)| *topoheight).collect::<Vec<_>>();
+        assert_eq!(topoheights, vec![3, 4, 8]);
+        assert!(entries.iter().all(|(topoheight, metadata)| metadata.block_reward == topoheight * 10));
+
+        assert!(storage.get_topoheight_metadata_range(5, 7).await.unwrap().is_empty());
+        assert!(storage.get_topoheight_metadata_range(8, 3).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_contract_data_batch() {
+        let tmp_dir = TempDir::new("rocksdb-contract-data-batch").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let contract = Hash::new([1u8; 32]);
+        storage.set_last_contract_to(&contract, 0, &Versioned::new(None, None)).await.unwrap();
+
+        let key = |i: u64| ValueCell::Primitive(Primitive::U64(i));
+        let value = |i: u64| ValueCell::Primitive(Primitive::String(format!("value {}", i)));
+        for i in 0..3 {
+            storage.set_last_contract_data_to(&contract, &key(i), i, &Versioned::new(Some(value(i)), None)).await.unwrap();
+        }
+
+        // Key 3 was never written and the order is kept
+        let values = storage.get_contract_data_batch(&contract, &[key(2), key(3), key(0), key(1)], 5).await.unwrap();
+        assert_eq!(values, vec![Some(value(2)), None, Some(value(0)), Some(value(1))]);
+
+        // Key 2 doesn't exist yet at topoheight 1
+        let values = storage.get_contract_data_batch(&contract, &[key(0), key(2)], 1).await.unwrap();
+        assert_eq!(values, vec![Some(value(0)), None]);
+
+        // Unknown contract
+        let values = storage.get_contract_data_batch(&Hash::zero(), &[key(0)], 5).await.unwrap();
+        assert_eq!(values, vec![None]);
+    }
+
+    #[tokio::test]
+    async fn test_shared_contract_data() {
+        let tmp_dir = TempDir::new("rocksdb-shared-contract-data").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let contract = Hash::new([1u8; 32]);
+        storage.set_last_contract_to(&contract, 0, &Versioned::new(None, None)).await.unwrap();
+
+        let key = ValueCell::Primitive(Primitive::U64(0));
+        let value = ValueCell::Bytes(vec![1u8; SHARED_CONTRACT_DATA_MIN_SIZE]);
+        storage.set_last_contract_data_to(&contract, &key, 1, &Versioned::new(Some(value.clone()), None)).await.unwrap();
+
+        // Both reads are served from the same allocation
+        let first = storage.get_shared_contract_data_at_exact_topoheight_for(&contract, &key, 1).await.unwrap();
+        let (topoheight, second) = storage.get_shared_contract_data_at_maximum_topoheight_for(&contract, &key, 5).await.unwrap().unwrap();
+        assert_eq!(topoheight, 1);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.get().as_ref(), Some(&value));
+
+        // Contract reads share the loaded value too
+        storage.shared_contract_data.clear();
+        let (topoheight, loaded) = storage.load_data(&contract, &key, 5).await.unwrap().unwrap();
+        assert_eq!(topoheight, 1);
+        assert_eq!(loaded, Some(value.clone()));
+        assert!(!storage.shared_contract_data.is_empty());
+
+        let (_, shared) = storage.get_shared_contract_data_at_maximum_topoheight_for(&contract, &key, 5).await.unwrap().unwrap();
+        let (_, again) = storage.get_shared_contract_data_at_maximum_topoheight_for(&contract, &key, 5).await.unwrap().unwrap();
+        assert!(Arc::ptr_eq(&shared, &again));
+        assert!(!Arc::ptr_eq(&first, &shared));
+
+        // Overwriting the version doesn't serve the old value anymore
+        let updated = ValueCell::Bytes(vec![2u8; SHARED_CONTRACT_DATA_MIN_SIZE]);
+        storage.set_last_contract_data_to(&contract, &key, 1, &Versioned::new(Some(updated.clone()), None)).await.unwrap();
+        let third = storage.get_shared_contract_data_at_exact_topoheight_for(&contract, &key, 1).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(third.get().as_ref(), Some(&updated));
+    }
+
+    #[tokio::test]
+    async fn test_commit_contract_data_batch() {
+        let tmp_dir = TempDir::new("rocksdb-commit-contract-data-batch").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let contract = Hash::new([1u8; 32]);
+        storage.set_last_contract_to(&contract, 0, &Versioned::new(None, None)).await.unwrap();
+
+        let key = |i: u64| ValueCell::Primitive(Primitive::U64(i));
+        let value = |i: u64| ValueCell::Primitive(Primitive::U64(i * 10));
+        storage.set_last_contract_data_to(&contract, &key(3), 1, &Versioned::new(Some(value(3)), None)).await.unwrap();
+
+        // Three writes and the deletion of the key 3
+        let writes = vec![
+            (key(0), Versioned::new(Some(value(0)), None)),
+            (key(1), Versioned::new(Some(value(1)), None)),
+            (key(2), Versioned::new(Some(value(2)), None)),
+            (key(3), Versioned::new(None, Some(1))),
+        ];
+        storage.commit_data_batch(&contract, 2, writes).await.unwrap();
+
+        for i in 0..3 {
+            assert_eq!(storage.load_data(&contract, &key(i), 2).await.unwrap(), Some((2, Some(value(i)))));
+        }
+
+        // Deletion is stored as a new version linked to the previous one
+        assert_eq!(storage.load_data(&contract, &key(3), 2).await.unwrap(), Some((2, None)));
+        let version = storage.get_contract_data_at_exact_topoheight_for(&contract, &key(3), 2).await.unwrap();
+        assert_eq!(version.get_previous_topoheight(), Some(1));
+
+        // Previous state is still available
+        assert_eq!(storage.load_data(&contract, &key(3), 1).await.unwrap(), Some((1, Some(value(3)))));
+        assert_eq!(storage.load_data(&contract, &key(0), 1).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_diff_between_topoheights() {
+        let tmp_dir = TempDir::new("rocksdb-state-diff").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let asset = Hash::new([1u8; 32]);
+        let data = AssetData::new(8, "Test".to_owned(), "TST".to_owned(), MaxSupplyMode::None, AssetOwner::None);
+        storage.add_asset(&asset, 0, Versioned::new(data, None)).await.unwrap();
+
+        let contract = Hash::new([2u8; 32]);
+        storage.set_last_contract_to(&contract, 0, &Versioned::new(None, None)).await.unwrap();
+
+        let alice = KeyPair::new().get_public_key().compress();
+        let bob = KeyPair::new().get_public_key().compress();
+        let key = ValueCell::Primitive(Primitive::U64(0));
+
+        // Changes at topoheight 5
+        storage.set_last_nonce_to(&alice, 5, &VersionedNonce::new(1, None)).await.unwrap();
+        storage.set_last_balance_to(&alice, &asset, 5, &VersionedBalance::zero()).await.unwrap();
+        storage.set_last_contract_data_to(&contract, &key, 5, &Versioned::new(Some(ValueCell::Primitive(Primitive::U64(10))), None)).await.unwrap();
+
+        // Changes at topoheight 10
+        storage.set_last_nonce_to(&alice, 10, &VersionedNonce::new(2, Some(5))).await.unwrap();
+        storage.set_last_nonce_to(&bob, 10, &VersionedNonce::new(1, None)).await.unwrap();
+        storage.set_last_balance_to(&bob, &asset, 10, &VersionedBalance::zero()).await.unwrap();
+
+        let diff = storage.diff_between(4, 10).await.unwrap();
+        assert_eq!(diff.nonces.len(), 2);
+        assert_eq!(diff.nonces.get(&alice), Some(&10));
+        assert_eq!(diff.nonces.get(&bob), Some(&10));
+        assert_eq!(diff.balances.len(), 2);
+        assert_eq!(diff.balances.get(&(alice.clone(), asset.clone())), Some(&5));
+        assert_eq!(diff.balances.get(&(bob.clone(), asset.clone())), Some(&10));
+        assert_eq!(diff.contracts_data.len(), 1);
+        assert_eq!(diff.contracts_data.get(&(contract.clone(), key.clone())), Some(&5));
+
+        // Only the changes at topoheight 10 are included
+        let diff = storage.diff_between(5, 10).await.unwrap();
+        assert_eq!(diff.nonces.len(), 2);
+        assert_eq!(diff.balances.len(), 1);
+        assert!(diff.contracts_data.is_empty());
+
+        assert!(storage.diff_between(10, 10).await.unwrap().is_empty());
+
+        // The upper bound doesn't overflow
+        let diff = storage.diff_between(5, u64::MAX).await.unwrap();
+        assert_eq!(diff.nonces.len(), 2);
+        assert_eq!(diff.balances.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_contract_data_entries_at_maximum_topoheight() {
+        let tmp_dir = TempDir::new("rocksdb-contract-data-entries").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        let contract = Hash::new([1u8; 32]);
+        storage.set_last_contract_to(&contract, 0, &Versioned::new(None, None)).await.unwrap();
+
+        let key = |i: u64| ValueCell::Primitive(Primitive::U64(i));
+        let value = |i: u64| ValueCell::Primitive(Primitive::U64(i * 10));
+        for i in 0..3 {
+            storage.set_last_contract_data_to(&contract, &key(i), 1, &Versioned::new(Some(value(i)), None)).await.unwrap();
+        }
+
+        // Overwrite the key 1 and delete the key 2
+        storage.set_last_contract_data_to(&contract, &key(1), 2, &Versioned::new(Some(value(4)), Some(1))).await.unwrap();
+        storage.set_last_contract_data_to(&contract, &key(2), 3, &Versioned::new(None, Some(1))).await.unwrap();
+
+        let entries = |topoheight| {
+            let storage = &storage;
+            let contract = &contract;
+            async move {
+                let mut entries: Vec<(ValueCell, ValueCell)> = storage.get_contract_data_entries_at_maximum_topoheight(contract, topoheight).await.unwrap()
+                    .try_collect()
+                    .await
+                    .unwrap();
+                entries.sort_by_key(|(key, _)| key.to_bytes());
+                entries
+            }
+        };
+
+        assert_eq!(entries(2).await, vec![(key(0), value(0)), (key(1), value(4)), (key(2), value(2))]);
+
+        // Deleted entries are skipped
+        assert_eq!(entries(3).await, vec![(key(0), value(0)), (key(1), value(4))]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_versioned_contracts_below_topoheight_keep_last() {
+        let tmp_dir = TempDir::new("rocksdb-versioned-contracts").unwrap();
+        let config: RocksDBConfig = serde_json::from_str("{}").unwrap();
+        let mut storage = RocksStorage::new(tmp_dir.path().to_str().unwrap(), Network::Devnet, &config);
+
+        // Each version has a different number of entry chunks
+        let module = |chunks: usize| {
+            let mut module = Module::new();
+            for _ in 0..chunks {
+                module.add_entry_chunk(Chunk::new(), None);
+            }
+            ContractModule::new(Default::default(), Arc::new(module))
+        };
+
+        let contract = Hash::new([1u8; 32]);
+        let versions = [(1, None), (3, Some(1)), (6, Some(3)), (8, Some(6))];
+        for (i, (topoheight, previous)) in versions.into_iter().enumerate() {
+            let versioned = Versioned::new(Some(Cow::Owned(module(i + 1))), previous);
+            storage.set_last_contract_to(&contract, topoheight, &versioned).await.unwrap();
+        }
+
+        storage.delete_versioned_contracts_below_topoheight(5, true).await.unwrap();
+
+        // Latest version is untouched
+        let (topoheight, version) = storage.get_contract_at_maximum_topoheight_for(&contract, 10).await.unwrap().unwrap();
+        assert_eq!(topoheight, 8);
+        assert_eq!(version.get_previous_topoheight(), Some(6));
+        assert_eq!(version.get().as_ref().unwrap().to_bytes(), module(4).to_bytes());
+
+        // Newest version below the threshold is kept but no longer points to a pruned one
+        let (topoheight, version) = storage.get_contract_at_maximum_topoheight_for(&contract, 5).await.unwrap().unwrap();
+        assert_eq!(topoheight, 3);
+        assert_eq!(version.get_previous_topoheight(), None);
+        assert_eq!(version.get().as_ref().unwrap().to_bytes(), module(2).to_bytes());
+
+        assert!(!storage.has_contract_at_exact_topoheight(&contract, 1).await.unwrap());
+        assert!(storage.get_contract_at_maximum_topoheight_for(&contract, 2).await.unwrap().is_none());
+    }
+}
@@ -52,6 +52,11 @@ impl BlockProvider for RocksStorage {
     // Check if the block exists using its hash
     async fn has_block_with_hash(&self, hash: &Hash) -> Result<bool, BlockchainError> {
         trace!("has block with hash {}", hash);
+        // Every block saved, even in a snapshot, is in the filter
+        if !self.block_hash_filter.may_contain(hash) {
+            return Ok(false)
+        }
+
         self.contains_data(Column::Blocks, hash)
     }
 
@@ -98,6 +103,7 @@ impl BlockProvider for RocksStorage {
         }
 
         self.insert_into_disk(Column::Blocks, hash.as_bytes(), &block)?;
+        self.block_hash_filter.insert(&hash);
 
         let block_difficulty = BlockMetadata {
             covariance,
@@ -124,6 +130,11 @@ impl BlockProvider for RocksStorage {
         let block = self.get_block_header_by_hash(hash).await?;
 
         self.remove_from_disk(Column::Blocks, hash)?;
+        // The block may be restored if the snapshot is discarded
+        if self.snapshot.is_none() {
+            self.block_hash_filter.remove(hash);
+        }
+
         self.remove_block_hash_at_height(hash, block.get_height()).await?;
 
         for tx in block.get_transactions() {
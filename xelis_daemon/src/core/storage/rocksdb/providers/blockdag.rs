@@ -8,7 +8,8 @@ use xelis_common::{
 use crate::core::{
     error::BlockchainError,
     storage::{
-        rocksdb::Column,
+        rocksdb::{Column, IteratorMode},
+        snapshot::Direction,
         types::TopoHeightMetadata,
         BlockDagProvider,
         DagOrderProvider,
@@ -52,6 +53,24 @@ impl BlockDagProvider for RocksStorage {
         trace!("get metadata at topoheight {}", topoheight);
         self.load_from_disk(Column::TopoHeightMetadata, &topoheight.to_be_bytes())
     }
+
+    // Get all the metadata stored between the two topoheights (inclusive)
+    async fn get_topoheight_metadata_range(&self, from: TopoHeight, to: TopoHeight) -> Result<Vec<(TopoHeight, TopoHeightMetadata)>, BlockchainError> {
+        trace!("get metadata range from {} to {}", from, to);
+        if from > to {
+            return Ok(Vec::new())
+        }
+
+        let lower = from.to_be_bytes();
+        // Upper bound is never included
+        let upper = to.saturating_add(1).to_be_bytes();
+        self.iter::<TopoHeight, TopoHeightMetadata>(Column::TopoHeightMetadata, IteratorMode::Range {
+            lower_bound: &lower,
+            upper_bound: &upper,
+            direction: Direction::Forward
+        })?
+            .collect()
+    }
 }
 
 impl RocksStorage {
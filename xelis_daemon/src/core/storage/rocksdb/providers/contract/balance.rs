@@ -5,7 +5,7 @@ use xelis_common::{
     crypto::Hash, serializer::Skip
 };
 use crate::core::{
-    error::BlockchainError,
+    error::{BlockchainError, DiskContext},
     storage::{
         rocksdb::{AssetId, Column, ContractId, IteratorMode},
         snapshot::Direction,
@@ -73,6 +73,54 @@ impl ContractBalanceProvider for RocksStorage {
         Ok(None)
     }
 
+    // Get the balances under or equal topoheight requested for asset and several contracts
+    async fn get_contract_balances_at_maximum_topoheight(&self, contracts: &[Hash], asset: &Hash, maximum_topoheight: TopoHeight) -> Result<Vec<Option<(TopoHeight, u64)>>, BlockchainError> {
+        trace!("get {} contracts balances at maximum topoheight {} for {}", contracts.len(), maximum_topoheight, asset);
+        let mut balances = vec![None; contracts.len()];
+        let Some(asset_id) = self.get_optional_asset_id(asset)? else {
+            return Ok(balances)
+        };
+
+        // Unknown contracts have no balance
+        let mut ids = Vec::with_capacity(contracts.len());
+        for (i, contract) in contracts.iter().enumerate() {
+            if let Some(contract_id) = self.get_optional_contract_id(contract)? {
+                ids.push((i, contract_id));
+            }
+        }
+
+        let keys = ids.iter()
+            .map(|(_, contract_id)| Self::get_contract_balance_key(*contract_id, asset_id))
+            .collect::<Vec<_>>();
+        let pointers: Vec<Option<TopoHeight>> = self.load_many_optional_from_disk(Column::ContractsBalances, &keys)?;
+
+        // Find the version to read for each contract
+        let mut versions = Vec::with_capacity(ids.len());
+        for ((i, contract_id), pointer) in ids.into_iter().zip(pointers) {
+            let mut prev_topo = pointer;
+            while let Some(topo) = prev_topo {
+                let key = Self::get_versioned_contract_balance_key(contract_id, asset_id, topo);
+                if topo <= maximum_topoheight {
+                    versions.push((i, topo, key));
+                    break;
+                }
+
+                prev_topo = self.load_from_disk(Column::VersionedContractsBalances, &key)?;
+            }
+        }
+
+        let keys = versions.iter()
+            .map(|(_, _, key)| *key)
+            .collect::<Vec<_>>();
+        let values: Vec<Option<VersionedContractBalance>> = self.load_many_optional_from_disk(Column::VersionedContractsBalances, &keys)?;
+        for ((i, topo, _), value) in versions.into_iter().zip(values) {
+            let version = value.ok_or(BlockchainError::NotFoundOnDisk(DiskContext::LoadData))?;
+            balances[i] = Some((topo, version.take()));
+        }
+
+        Ok(balances)
+    }
+
     // Get the last topoheight that the contract has a balance
     async fn get_last_topoheight_for_contract_balance(&self, contract: &Hash, asset: &Hash) -> Result<Option<TopoHeight>, BlockchainError> {
         trace!("get last topoheight for contract {} balance {}", contract, asset);
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use futures::{stream, Stream, StreamExt};
 use log::trace;
@@ -28,6 +30,7 @@ impl ContractDataProvider for RocksStorage {
         let contract_data_id = self.get_or_create_contract_data_id(key)?;
         let versioned_key = Self::get_versioned_contract_data_key(contract_id, contract_data_id, topoheight);
 
+        self.shared_contract_data.remove(&versioned_key);
         self.insert_into_disk(Column::VersionedContractsData, &versioned_key, version)?;
         self.insert_into_disk(Column::ContractsData, &versioned_key[8..], &topoheight.to_be_bytes())
     }
@@ -75,6 +78,60 @@ impl ContractDataProvider for RocksStorage {
         Ok(None)
     }
 
+    // Retrieve a shared contract data at a given topoheight
+    async fn get_shared_contract_data_at_exact_topoheight_for(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Result<Arc<VersionedContractData>, BlockchainError> {
+        trace!("get shared contract {} data {} at exact topoheight {}", contract, key, topoheight);
+        let contract_id = self.get_contract_id(contract)?;
+        let contract_data_id = self.get_contract_data_id(key)?;
+        let key = Self::get_versioned_contract_data_key(contract_id, contract_data_id, topoheight);
+        self.load_shared_contract_data(&key)
+    }
+
+    // Retrieve a shared contract data at maximum topoheight
+    async fn get_shared_contract_data_at_maximum_topoheight_for(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<(TopoHeight, Arc<VersionedContractData>)>, BlockchainError> {
+        trace!("get shared contract {} data {} at maximum topoheight {}", contract, key, maximum_topoheight);
+        let Some(contract_id) = self.get_optional_contract_id(contract)? else {
+            return Ok(None)
+        };
+
+        let Some(contract_data_id) = self.get_optional_contract_data_id(key)? else {
+            return Ok(None)
+        };
+
+        if let Some(topo) = self.get_contract_data_topoheight_at_maximum_topoheight_for_internal(contract_id, contract_data_id, maximum_topoheight).await? {
+            let versioned_key = Self::get_versioned_contract_data_key(contract_id, contract_data_id, topo);
+            let version = self.load_shared_contract_data(&versioned_key)?;
+            return Ok(Some((topo, version)))
+        }
+
+        Ok(None)
+    }
+
+    // Retrieve several contract data at maximum topoheight
+    // The contract id is only resolved once
+    async fn get_contract_data_batch(&self, contract: &Hash, keys: &[ValueCell], maximum_topoheight: TopoHeight) -> Result<Vec<Option<ValueCell>>, BlockchainError> {
+        trace!("get contract {} {} data at maximum topoheight {}", contract, keys.len(), maximum_topoheight);
+        let mut values = vec![None; keys.len()];
+        let Some(contract_id) = self.get_optional_contract_id(contract)? else {
+            return Ok(values)
+        };
+
+        for (value, key) in values.iter_mut().zip(keys) {
+            let Some(contract_data_id) = self.get_optional_contract_data_id(key)? else {
+                continue
+            };
+
+            if let Some(topo) = self.get_contract_data_topoheight_at_maximum_topoheight_for_internal(contract_id, contract_data_id, maximum_topoheight).await? {
+                let versioned_key = Self::get_versioned_contract_data_key(contract_id, contract_data_id, topo);
+                *value = self.load_shared_contract_data(&versioned_key)?
+                    .get()
+                    .clone();
+            }
+        }
+
+        Ok(values)
+    }
+
     // Retrieve the topoheight of a contract data at maximum topoheight
     async fn get_contract_data_topoheight_at_maximum_topoheight_for<'a>(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<TopoHeight>, BlockchainError> {
         trace!("get contract {} data {} topoheight at maximum topoheight {}", contract, key, maximum_topoheight);
@@ -156,6 +213,22 @@ impl ContractDataProvider for RocksStorage {
 impl RocksStorage {
     const NEXT_CONTRACT_DATA_ID: &[u8] = b"NCDID";
 
+    // Load a versioned contract data, large values are shared between reads
+    // Snapshot reads are not shared as the changes may be discarded
+    fn load_shared_contract_data(&self, versioned_key: &[u8]) -> Result<Arc<VersionedContractData>, BlockchainError> {
+        if self.snapshot.is_some() {
+            return self.load_from_disk(Column::VersionedContractsData, versioned_key)
+                .map(Arc::new)
+        }
+
+        if let Some(data) = self.shared_contract_data.get(versioned_key) {
+            return Ok(data)
+        }
+
+        let data = self.load_from_disk(Column::VersionedContractsData, versioned_key)?;
+        Ok(self.shared_contract_data.intern(versioned_key.to_vec(), data))
+    }
+
     async fn get_contract_data_topoheight_at_maximum_topoheight_for_internal(&self, contract_id: ContractId, contract_key_id: ContractDataId, maximum_topoheight: TopoHeight) -> Result<Option<TopoHeight>, BlockchainError> {
         trace!("get contract {} data {} at maximum topoheight {}", contract_id, contract_key_id, maximum_topoheight);
 
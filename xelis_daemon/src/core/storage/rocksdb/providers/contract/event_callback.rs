@@ -86,8 +86,8 @@ impl ContractEventCallbackProvider for RocksStorage {
         let prefix = Self::get_event_callback_prefix(contract_id, event_id);
 
         // Iterate using the prefix to get all listeners for this event
-        self.iter::<ContractId, TopoHeight>(Column::ContractEventCallbacks, IteratorMode::WithPrefix(&prefix, Direction::Forward))
-            .map(move |iter| iter.map(move |res| {
+        let mut callbacks = self.iter::<ContractId, TopoHeight>(Column::ContractEventCallbacks, IteratorMode::WithPrefix(&prefix, Direction::Forward))?
+            .map(|res| {
                 let (listener_id, last_topoheight) = res?;
                 let versioned_key = Self::get_versioned_event_callback_key(last_topoheight, contract_id, event_id, listener_id);
 
@@ -103,7 +103,14 @@ impl ContractEventCallbackProvider for RocksStorage {
                 }
 
                 Ok(None)
-            }).filter_map(Result::transpose))
+            })
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>, BlockchainError>>()?;
+
+        // Listener ids follow the registration order, dispatch is done by listener hash
+        callbacks.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(callbacks.into_iter().map(Ok))
     }
 
     async fn get_event_callbacks_available_at_maximum_topoheight<'a>(
@@ -119,8 +126,8 @@ impl ContractEventCallbackProvider for RocksStorage {
         let prefix = Self::get_event_callback_prefix(contract_id, event_id);
 
         // Iterate using the prefix to get all listeners for this event
-        self.iter::<ContractId, TopoHeight>(Column::ContractEventCallbacks, IteratorMode::WithPrefix(&prefix, Direction::Forward))
-            .map(move |iter| iter.map(move |res| {
+        let mut callbacks = self.iter::<ContractId, TopoHeight>(Column::ContractEventCallbacks, IteratorMode::WithPrefix(&prefix, Direction::Forward))?
+            .map(|res| {
                 let (listener_id, last_topoheight) = res?;
                 let versioned_key = Self::get_versioned_event_callback_key(last_topoheight, contract_id, event_id, listener_id);
 
@@ -141,7 +148,14 @@ impl ContractEventCallbackProvider for RocksStorage {
                 }
 
                 Ok(None)
-            }).filter_map(Result::transpose))
+            })
+            .filter_map(Result::transpose)
+            .collect::<Result<Vec<_>, BlockchainError>>()?;
+
+        // Listener ids follow the registration order, dispatch is done by listener hash
+        callbacks.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(callbacks.into_iter().map(Ok))
     }
 }
 
@@ -6,7 +6,7 @@ use xelis_common::{
     serializer::Skip
 };
 use crate::core::{
-    error::BlockchainError,
+    error::{BlockchainError, DiskContext},
     storage::{
         ContractProvider,
         RocksStorage,
@@ -73,6 +73,45 @@ impl ContractProvider for RocksStorage {
         Ok(None)
     }
 
+    // Retrieve several contracts at maximum topoheight
+    async fn get_contracts_at_maximum_topoheight_for<'a>(&self, hashes: &[Hash], maximum_topoheight: TopoHeight) -> Result<Vec<Option<(TopoHeight, VersionedContractModule<'a>)>>, BlockchainError> {
+        trace!("get {} contracts at maximum topoheight {}", hashes.len(), maximum_topoheight);
+        let mut modules = Vec::with_capacity(hashes.len());
+        modules.resize_with(hashes.len(), || None);
+
+        let contracts: Vec<Option<Contract>> = self.load_many_optional_from_disk(Column::Contracts, hashes)?;
+
+        // Find the version to read for each contract
+        let mut versions = Vec::with_capacity(hashes.len());
+        for (i, contract) in contracts.into_iter().enumerate() {
+            let Some(contract) = contract else {
+                continue
+            };
+
+            let mut prev_topo = contract.module_pointer;
+            while let Some(topo) = prev_topo {
+                let key = Self::get_versioned_contract_key(contract.id, topo);
+                if topo <= maximum_topoheight {
+                    versions.push((i, topo, key));
+                    break;
+                }
+
+                prev_topo = self.load_from_disk(Column::VersionedContracts, &key)?;
+            }
+        }
+
+        let keys = versions.iter()
+            .map(|(_, _, key)| *key)
+            .collect::<Vec<_>>();
+        let values: Vec<Option<VersionedContractModule<'a>>> = self.load_many_optional_from_disk(Column::VersionedContracts, &keys)?;
+        for ((i, topo, _), value) in versions.into_iter().zip(values) {
+            let version = value.ok_or(BlockchainError::NotFoundOnDisk(DiskContext::LoadData))?;
+            modules[i] = Some((topo, version));
+        }
+
+        Ok(modules)
+    }
+
     // Retrieve all the contracts hashes
     async fn get_contracts<'a>(&'a self, minimum_topoheight: TopoHeight, maximum_topoheight: TopoHeight) -> Result<impl Iterator<Item = Result<Hash, BlockchainError>> + 'a, BlockchainError> {
         trace!("get contracts {}-{}", minimum_topoheight, maximum_topoheight);
@@ -18,6 +18,7 @@ mod account;
 mod balance;
 mod nonce;
 mod state;
+mod state_diff;
 mod multisig;
 mod contract;
 mod versioned;
\ No newline at end of file
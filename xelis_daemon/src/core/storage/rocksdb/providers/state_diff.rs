@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::block::TopoHeight;
+use xelis_vm::ValueCell;
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        rocksdb::{
+            AccountId,
+            AssetId,
+            Column,
+            ContractDataId,
+            ContractId,
+            IteratorMode,
+        },
+        snapshot::Direction,
+        RocksStorage,
+        StateDiff,
+        StateDiffProvider,
+    }
+};
+
+#[async_trait]
+impl StateDiffProvider for RocksStorage {
+    async fn diff_between(&self, from: TopoHeight, to: TopoHeight) -> Result<StateDiff, BlockchainError> {
+        trace!("diff between topoheight {} and {}", from, to);
+        let mut diff = StateDiff::default();
+        if from >= to {
+            return Ok(diff);
+        }
+
+        // Versioned keys are prefixed by their topoheight
+        // so a single range scan covers (from, to]
+        let lower_bound = from.saturating_add(1).to_be_bytes();
+        let upper_bound = to.saturating_add(1).to_be_bytes();
+
+        for res in self.iter_keys::<(TopoHeight, AccountId, AssetId)>(Column::VersionedBalances, IteratorMode::Range { lower_bound: &lower_bound, upper_bound: &upper_bound, direction: Direction::Forward })? {
+            let (topoheight, account_id, asset_id) = res?;
+            let key = self.get_account_key_from_id(account_id)?;
+            let asset = self.get_asset_hash_from_id(asset_id)?;
+            diff.balances.entry((key, asset))
+                .and_modify(|current| *current = topoheight.max(*current))
+                .or_insert(topoheight);
+        }
+
+        for res in self.iter_keys::<(TopoHeight, AccountId)>(Column::VersionedNonces, IteratorMode::Range { lower_bound: &lower_bound, upper_bound: &upper_bound, direction: Direction::Forward })? {
+            let (topoheight, account_id) = res?;
+            let key = self.get_account_key_from_id(account_id)?;
+            diff.nonces.entry(key)
+                .and_modify(|current| *current = topoheight.max(*current))
+                .or_insert(topoheight);
+        }
+
+        for res in self.iter_keys::<(TopoHeight, ContractId, ContractDataId)>(Column::VersionedContractsData, IteratorMode::Range { lower_bound: &lower_bound, upper_bound: &upper_bound, direction: Direction::Forward })? {
+            let (topoheight, contract_id, data_id) = res?;
+            let contract = self.get_contract_from_id(contract_id)?;
+            let key: ValueCell = self.load_from_disk(Column::ContractDataTableById, &data_id.to_be_bytes())?;
+            diff.contracts_data.entry((contract, key))
+                .and_modify(|current| *current = topoheight.max(*current))
+                .or_insert(topoheight);
+        }
+
+        Ok(diff)
+    }
+}
@@ -14,16 +14,19 @@ use crate::core::{
 impl VersionedContractDataProvider for RocksStorage {
     async fn delete_versioned_contract_data_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
         trace!("delete versioned contract data at topoheight {}", topoheight);
+        self.shared_contract_data.clear();
         self.delete_versioned_at_topoheight(Column::ContractsData, Column::VersionedContractsData, topoheight)
     }
 
     async fn delete_versioned_contract_data_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
         trace!("delete versioned contract data above topoheight {}", topoheight);
+        self.shared_contract_data.clear();
         self.delete_versioned_above_topoheight(Column::ContractsData, Column::VersionedContractsData, topoheight)
     }
 
     async fn delete_versioned_contract_data_below_topoheight(&mut self, topoheight: TopoHeight, keep_last: bool) -> Result<(), BlockchainError> {
         trace!("delete versioned contract data below topoheight {}", topoheight);
+        self.shared_contract_data.clear();
         self.delete_versioned_below_topoheight_default(Column::ContractsData, Column::VersionedContractsData, topoheight, keep_last)
     }
 }
\ No newline at end of file
@@ -28,7 +28,7 @@ use sled::{IVec, Tree};
 use log::{debug, trace, info, error};
 
 use super::{
-    cache::StorageCache,
+    cache::{BlockHashFilter, SharedContractDataCache, StorageCache},
     providers::*,
     Storage,
     snapshot::{
@@ -194,6 +194,10 @@ pub struct SledStorage {
 
     // Cache
     pub(super) cache: StorageCache,
+    // Large contract data shared between reads
+    pub(super) shared_contract_data: SharedContractDataCache,
+    // Filter over the block hashes stored on disk
+    pub(super) block_hash_filter: BlockHashFilter,
 
     // If we have a snapshot, we can use it to rollback
     pub(super) snapshot: Option<Snapshot>,
@@ -244,12 +248,15 @@ impl SledStorage {
 
         let sled = config.open()?;
 
+        let blocks = sled.open_tree("blocks")?;
+        let block_hash_filter = Self::load_block_hash_filter(&blocks)?;
+
         let mut storage = Self {
             network,
             transactions: sled.open_tree("transactions")?,
             txs_executed: sled.open_tree("txs_executed")?,
             blocks_execution_order: sled.open_tree("blocks_execution_order")?,
-            blocks: sled.open_tree("blocks")?,
+            blocks,
             blocks_at_height: sled.open_tree("blocks_at_height")?,
             extra: sled.open_tree("extra")?,
             topo_by_hash: sled.open_tree("topo_at_hash")?,
@@ -287,6 +294,8 @@ impl SledStorage {
             contracts_transactions: sled.open_tree("contracts_transactions")?,
             db: sled,
             cache: StorageCache::new(cache_size),
+            shared_contract_data: SharedContractDataCache::default(),
+            block_hash_filter,
             snapshot: None,
         };
 
@@ -310,6 +319,17 @@ impl SledStorage {
         Ok(storage)
     }
 
+    // Build the block hash filter from all the blocks stored on disk
+    fn load_block_hash_filter(blocks: &Tree) -> Result<BlockHashFilter, BlockchainError> {
+        trace!("load block hash filter");
+        let mut filter = BlockHashFilter::new(blocks.len());
+        for key in blocks.iter().keys() {
+            filter.insert(&Hash::from_bytes(&key?)?);
+        }
+
+        Ok(filter)
+    }
+
     pub fn cache_mut(&mut self) -> &mut StorageCache {
         match self.snapshot.as_mut() {
             Some(snapshot) => &mut snapshot.cache,
@@ -423,6 +443,33 @@ impl SledStorage {
         }
     }
 
+    // Scan the keys in the range [lower_bound, upper_bound)
+    pub(super) fn scan_range_keys<'a, K: Serializer + 'a>(snapshot: Option<&'a Snapshot>, tree: &Tree, lower_bound: &[u8], upper_bound: &[u8]) -> impl Iterator<Item = Result<K, BlockchainError>> + 'a {
+        let range = tree.range(lower_bound..upper_bound);
+        match snapshot {
+            Some(snapshot) => Either::Left(snapshot.lazy_iter_keys(tree.into(), IteratorMode::Range { lower_bound, upper_bound, direction: Direction::Forward }, range)),
+            None => Either::Right(range.keys().map(|res| {
+                let bytes = res?;
+                let k = K::from_bytes(&bytes)?;
+                Ok(k)
+            }))
+        }
+    }
+
+    // Scan the entries in the range [lower_bound, upper_bound)
+    pub(super) fn scan_range<'a, K: Serializer + 'a, V: Serializer + 'a>(snapshot: Option<&'a Snapshot>, tree: &Tree, lower_bound: &[u8], upper_bound: &[u8]) -> impl Iterator<Item = Result<(K, V), BlockchainError>> + 'a {
+        let range = tree.range(lower_bound..upper_bound);
+        match snapshot {
+            Some(snapshot) => Either::Left(snapshot.lazy_iter(tree.into(), IteratorMode::Range { lower_bound, upper_bound, direction: Direction::Forward }, range)),
+            None => Either::Right(range.map(|res| {
+                let (k_bytes, v_bytes) = res?;
+                let k = K::from_bytes(&k_bytes)?;
+                let v = V::from_bytes(&v_bytes)?;
+                Ok((k, v))
+            }))
+        }
+    }
+
     // Scan prefix raw
     pub(super) fn scan_prefix_raw<'a>(snapshot: Option<&'a Snapshot>, tree: &Tree, prefix: &[u8]) -> impl Iterator<Item = Result<(BytesView<'a>, BytesView<'a>), BlockchainError>> + 'a {
         match snapshot {
@@ -53,6 +53,11 @@ impl BlockProvider for SledStorage {
 
     async fn has_block_with_hash(&self, hash: &Hash) -> Result<bool, BlockchainError> {
         trace!("has block {}", hash);
+        // Every block saved, even in a snapshot, is in the filter
+        if !self.block_hash_filter.may_contain(hash) {
+            return Ok(false)
+        }
+
         self.contains_data_cached(&self.blocks, self.cache.objects.as_ref().map(|o| &o.blocks_cache), hash).await
     }
 
@@ -95,6 +100,7 @@ impl BlockProvider for SledStorage {
 
         // Store block header and increase blocks count if it's a new block
         Self::insert_into_disk(self.snapshot.as_mut(), &self.blocks, hash.as_bytes(), block.to_bytes())?;
+        self.block_hash_filter.insert(&hash);
 
         // Store difficulty
         Self::insert_into_disk(self.snapshot.as_mut(), &self.difficulty, hash.as_bytes(), difficulty.to_bytes())?;
@@ -136,6 +142,10 @@ impl BlockProvider for SledStorage {
 
         // Delete block header
         let header = Self::delete_arc_cacheable_data(self.snapshot.as_mut(), &self.blocks, self.cache.objects.as_mut().map(|o| &mut o.blocks_cache), &hash).await?;
+        // The block may be restored if the snapshot is discarded
+        if self.snapshot.is_none() {
+            self.block_hash_filter.remove(hash);
+        }
 
         // Decrease blocks count
         self.store_blocks_count(self.count_blocks().await? - 1)?;
@@ -45,6 +45,24 @@ impl BlockDagProvider for SledStorage {
         self.load_from_disk(&self.topoheight_metadata, &topoheight.to_be_bytes(), DiskContext::MetadataAtTopoHeight(topoheight))
     }
 
+    async fn get_topoheight_metadata_range(&self, from: TopoHeight, to: TopoHeight) -> Result<Vec<(TopoHeight, TopoHeightMetadata)>, BlockchainError> {
+        trace!("get topoheight metadata range from {} to {}", from, to);
+        if from > to {
+            return Ok(Vec::new())
+        }
+
+        let lower = from.to_be_bytes();
+        // Upper bound is never included
+        let upper = to.saturating_add(1).to_be_bytes();
+        let mut entries = Self::scan_range::<TopoHeight, TopoHeightMetadata>(self.snapshot.as_ref(), &self.topoheight_metadata, &lower, &upper)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Pending snapshot writes are returned after the disk entries
+        entries.sort_unstable_by_key(|(topoheight, _)| *topoheight);
+
+        Ok(entries)
+    }
+
     // Set the metadata for topoheight
     async fn set_metadata_at_topoheight(&mut self, topoheight: TopoHeight, metadata: TopoHeightMetadata) -> Result<(), BlockchainError> {
         trace!("set topoheight metadata at {}", topoheight);
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use futures::{stream, Stream, StreamExt};
 use log::trace;
@@ -17,6 +19,7 @@ impl ContractDataProvider for SledStorage {
     async fn set_last_contract_data_to(&mut self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight, data: &VersionedContractData) -> Result<(), BlockchainError> {
         trace!("set last contract data to topoheight {}", topoheight);
         let versioned_key = self.get_versioned_contract_data_key(contract, key, topoheight);
+        self.shared_contract_data.remove(&versioned_key);
         Self::insert_into_disk(self.snapshot.as_mut(), &self.versioned_contracts_data, &versioned_key, data.to_bytes())?;
 
         let key = self.get_contract_data_key(contract, key);
@@ -47,6 +50,23 @@ impl ContractDataProvider for SledStorage {
         }
     }
 
+    async fn get_shared_contract_data_at_exact_topoheight_for(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Result<Arc<VersionedContractData>, BlockchainError> {
+        trace!("get shared contract data at topoheight {}", topoheight);
+        let versioned_key = self.get_versioned_contract_data_key(contract, key, topoheight);
+        self.load_shared_contract_data(versioned_key)
+    }
+
+    async fn get_shared_contract_data_at_maximum_topoheight_for(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<(TopoHeight, Arc<VersionedContractData>)>, BlockchainError> {
+        trace!("get shared contract data at maximum topoheight {}", maximum_topoheight);
+        match self.get_contract_data_topoheight_at_maximum_topoheight_for(contract, key, maximum_topoheight).await? {
+            Some(topoheight) => {
+                let contract = self.get_shared_contract_data_at_exact_topoheight_for(&contract, key, topoheight).await?;
+                Ok(Some((topoheight, contract)))
+            },
+            None => Ok(None)
+        }
+    }
+
     async fn get_contract_data_topoheight_at_maximum_topoheight_for<'a>(&self, contract: &Hash, key: &ValueCell, maximum_topoheight: TopoHeight) -> Result<Option<TopoHeight>, BlockchainError> {
         trace!("get contract data topoheight at maximum topoheight {}", maximum_topoheight);
         let Some(pointer) = self.get_last_topoheight_for_contract_data(contract, key).await? else {
@@ -101,6 +121,22 @@ impl ContractDataProvider for SledStorage {
 }
 
 impl SledStorage {
+    // Load a versioned contract data, large values are shared between reads
+    // Snapshot reads are not shared as the changes may be discarded
+    fn load_shared_contract_data(&self, versioned_key: Vec<u8>) -> Result<Arc<VersionedContractData>, BlockchainError> {
+        if self.snapshot.is_some() {
+            return self.load_from_disk(&self.versioned_contracts_data, &versioned_key, DiskContext::ContractData)
+                .map(Arc::new)
+        }
+
+        if let Some(data) = self.shared_contract_data.get(&versioned_key) {
+            return Ok(data)
+        }
+
+        let data = self.load_from_disk(&self.versioned_contracts_data, &versioned_key, DiskContext::ContractData)?;
+        Ok(self.shared_contract_data.intern(versioned_key, data))
+    }
+
     pub fn get_versioned_contract_data_key(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Vec<u8> {
         let mut buffer = Vec::new();
         buffer.extend_from_slice(&topoheight.to_be_bytes());
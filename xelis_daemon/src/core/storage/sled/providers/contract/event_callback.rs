@@ -76,6 +76,7 @@ impl ContractEventCallbackProvider for SledStorage {
         let prefix = Self::get_event_callback_prefix(contract, event_id);
         
         // Iterate using the prefix to get all listeners for this event
+        // Listener hash is the key suffix, so they are already ordered by hash
         Ok(Self::scan_prefix::<Skip<40, Hash>, TopoHeight>(
             self.snapshot.as_ref(),
             &self.contracts_event_callbacks,
@@ -118,6 +119,7 @@ impl ContractEventCallbackProvider for SledStorage {
         let prefix = Self::get_event_callback_prefix(contract, event_id);
         
         // Iterate using the prefix to get all listeners for this event
+        // Listener hash is the key suffix, so they are already ordered by hash
         Ok(Self::scan_prefix::<Skip<40, Hash>, TopoHeight>(
             self.snapshot.as_ref(),
             &self.contracts_event_callbacks,
@@ -15,27 +15,23 @@ use crate::core::storage::{
     BalanceProvider,
     ContractBalanceProvider,
     ContractDataProvider,
+    VersionedContractData,
     ContractProvider as _,
     NetworkProvider,
     SledStorage,
     AssetCirculatingSupplyProvider,
     ContractScheduledExecutionProvider,
     ContractEventCallbackProvider,
+    SnapshotProvider,
 };
 
 #[async_trait]
 impl ContractStorage for SledStorage {
     async fn load_data(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Result<Option<(TopoHeight, Option<ValueCell>)>, anyhow::Error> {
         trace!("load contract {} key {} data at topoheight {}", contract, key, topoheight);
-        let res = self.get_contract_data_at_maximum_topoheight_for(contract, &key, topoheight).await?;
-
-        match res {
-            Some((topoheight, data)) => match data.take() {
-                Some(data) => Ok(Some((topoheight, Some(data)))),
-                None => Ok(Some((topoheight, None))),
-            },
-            None => Ok(None),
-        }
+        // Large values are shared with the other reads
+        let res = self.get_shared_contract_data_at_maximum_topoheight_for(contract, &key, topoheight).await?;
+        Ok(res.map(|(topoheight, data)| (topoheight, data.get().clone())))
     }
 
     async fn load_data_latest_topoheight(&self, contract: &Hash, key: &ValueCell, topoheight: TopoHeight) -> Result<Option<TopoHeight>, anyhow::Error> {
@@ -49,6 +45,23 @@ impl ContractStorage for SledStorage {
         let res = self.has_contract_at_maximum_topoheight(contract, topoheight).await?;
         Ok(res)
     }
+
+    async fn commit_data_batch(&mut self, contract: &Hash, topoheight: TopoHeight, writes: Vec<(ValueCell, VersionedContractData)>) -> Result<(), anyhow::Error> {
+        trace!("commit {} writes for contract {} at topoheight {}", writes.len(), contract, topoheight);
+        // Without a snapshot already active, use our own
+        // so the writes are only applied all together
+        let owned = !self.has_snapshot().await?;
+        if owned {
+            self.start_snapshot().await?;
+        }
+
+        let res = self.set_contract_data_batch(contract, topoheight, writes).await;
+        if owned {
+            self.end_snapshot(res.is_ok())?;
+        }
+
+        Ok(res?)
+    }
 }
 
 #[async_trait]
@@ -20,4 +20,5 @@ mod tips_provider;
 mod contract;
 mod versioned;
 mod cache;
-mod state;
\ No newline at end of file
+mod state;
+mod state_diff;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::{
+    block::TopoHeight,
+    crypto::{Hash, PublicKey}
+};
+use xelis_vm::ValueCell;
+use crate::core::{
+    error::BlockchainError,
+    storage::{
+        SledStorage,
+        StateDiff,
+        StateDiffProvider
+    }
+};
+
+#[async_trait]
+impl StateDiffProvider for SledStorage {
+    async fn diff_between(&self, from: TopoHeight, to: TopoHeight) -> Result<StateDiff, BlockchainError> {
+        trace!("diff between topoheight {} and {}", from, to);
+        let mut diff = StateDiff::default();
+        if from >= to {
+            return Ok(diff);
+        }
+
+        // Versioned keys are prefixed by their topoheight
+        // so a single range scan covers (from, to]
+        let lower_bound = from.saturating_add(1).to_be_bytes();
+        let upper_bound = to.saturating_add(1).to_be_bytes();
+
+        for res in Self::scan_range_keys::<(TopoHeight, PublicKey, Hash)>(self.snapshot.as_ref(), &self.versioned_balances, &lower_bound, &upper_bound) {
+            let (topoheight, key, asset) = res?;
+            diff.balances.entry((key, asset))
+                .and_modify(|current| *current = topoheight.max(*current))
+                .or_insert(topoheight);
+        }
+
+        for res in Self::scan_range_keys::<(TopoHeight, PublicKey)>(self.snapshot.as_ref(), &self.versioned_nonces, &lower_bound, &upper_bound) {
+            let (topoheight, key) = res?;
+            diff.nonces.entry(key)
+                .and_modify(|current| *current = topoheight.max(*current))
+                .or_insert(topoheight);
+        }
+
+        for res in Self::scan_range_keys::<(TopoHeight, Hash, ValueCell)>(self.snapshot.as_ref(), &self.versioned_contracts_data, &lower_bound, &upper_bound) {
+            let (topoheight, contract, key) = res?;
+            diff.contracts_data.entry((contract, key))
+                .and_modify(|current| *current = topoheight.max(*current))
+                .or_insert(topoheight);
+        }
+
+        Ok(diff)
+    }
+}
@@ -10,16 +10,19 @@ use crate::core::{
 impl VersionedContractDataProvider for SledStorage {
     async fn delete_versioned_contract_data_at_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
         trace!("delete versioned contracts data at topoheight {}", topoheight);
+        self.shared_contract_data.clear();
         Self::delete_versioned_tree_at_topoheight(&mut self.snapshot, &self.contracts_data, &self.versioned_contracts_data, topoheight)
     }
 
     async fn delete_versioned_contract_data_above_topoheight(&mut self, topoheight: TopoHeight) -> Result<(), BlockchainError> {
         trace!("delete versioned contracts data above topoheight {}", topoheight);
+        self.shared_contract_data.clear();
         Self::delete_versioned_tree_above_topoheight(&mut self.snapshot, &self.contracts_data, &self.versioned_contracts_data, topoheight, DiskContext::VersionedContractData)
     }
 
     async fn delete_versioned_contract_data_below_topoheight(&mut self, topoheight: TopoHeight, keep_last: bool) -> Result<(), BlockchainError> {
         trace!("delete versioned contracts data below topoheight {}", topoheight);
+        self.shared_contract_data.clear();
         Self::delete_versioned_tree_below_topoheight(&mut self.snapshot, &self.contracts_data, &self.versioned_contracts_data, topoheight, keep_last, DiskContext::VersionedContractData)
     }
 }
\ No newline at end of file
@@ -1483,6 +1483,20 @@ async fn get_account_history<S: Storage>(context: &Context<'_, '_>, params: GetA
                         });
                     }
                 },
+                TransactionType::MultiSigUpdate(payload) => {
+                    if is_sender && params.outgoing_flow {
+                        history.push(AccountHistoryEntry {
+                            topoheight: topo,
+                            hash: tx_hash.clone(),
+                            history_type: AccountHistoryType::MultiSigUpdate {
+                                add: payload.add.iter().map(|p| p.as_address(is_mainnet)).collect(),
+                                remove: payload.remove.iter().map(|p| p.as_address(is_mainnet)).collect(),
+                                new_threshold: payload.new_threshold,
+                            },
+                            block_timestamp: block_header.get_timestamp()
+                        });
+                    }
+                },
                 TransactionType::InvokeContract(payload) => {
                     if is_sender && params.outgoing_flow {
                         history.push(AccountHistoryEntry {
@@ -2119,14 +2133,14 @@ async fn get_contract_module<S: Storage>(context: &Context<'_, '_>, params: GetC
     }))
 }
 
-async fn get_contract_data<S: Storage>(context: &Context<'_, '_>, params: GetContractDataParams<'_>) -> Result<RPCVersioned<Versioned<Option<ValueCell>>>, InternalRpcError> {
+async fn get_contract_data<S: Storage>(context: &Context<'_, '_>, params: GetContractDataParams<'_>) -> Result<RPCVersioned<Arc<Versioned<Option<ValueCell>>>>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let storage = blockchain.get_storage().read().await;
 
     let topoheight = storage.get_last_topoheight_for_contract_data(&params.contract, &params.key).await?
         .context("No data found with requested key")?;
 
-    let version = storage.get_contract_data_at_exact_topoheight_for(&params.contract, &params.key, topoheight).await?;
+    let version = storage.get_shared_contract_data_at_exact_topoheight_for(&params.contract, &params.key, topoheight).await?;
 
     Ok(RPCVersioned {
         topoheight,
@@ -2135,11 +2149,11 @@ async fn get_contract_data<S: Storage>(context: &Context<'_, '_>, params: GetCon
 }
 
 
-async fn get_contract_data_at_topoheight<S: Storage>(context: &Context<'_, '_>, params: GetContractDataAtTopoHeightParams<'_>) -> Result<Versioned<Option<ValueCell>>, InternalRpcError> {
+async fn get_contract_data_at_topoheight<S: Storage>(context: &Context<'_, '_>, params: GetContractDataAtTopoHeightParams<'_>) -> Result<Arc<Versioned<Option<ValueCell>>>, InternalRpcError> {
     let blockchain = chain_from_context::<S>(context)?;
     let storage = blockchain.get_storage().read().await;
 
-    let version = storage.get_contract_data_at_exact_topoheight_for(&params.contract, &params.key, params.topoheight).await?;
+    let version = storage.get_shared_contract_data_at_exact_topoheight_for(&params.contract, &params.key, params.topoheight).await?;
 
     Ok(version)
 }
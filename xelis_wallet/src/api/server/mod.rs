@@ -1,11 +1,17 @@
 mod rpc_server;
 mod xswd_server;
 
+use std::time::Duration;
+
+use actix_web::dev::ServerHandle;
+use async_trait::async_trait;
+use log::warn;
 use serde::Serialize;
 use serde_json::json;
 use xelis_common::{
     api::wallet::NotifyEvent,
-    rpc::{ShareableTid, server::WebSocketServerHandler}
+    rpc::{ShareableTid, server::WebSocketServerHandler},
+    tokio::time::timeout as with_timeout
 };
 use crate::api::XSWDHandler;
 pub use xswd_server::{
@@ -18,17 +24,18 @@ pub use rpc_server::{
     AuthConfig
 };
 
-pub enum APIServer<W>
+pub enum APIServer<W, H = ServerHandle>
 where
     W: ShareableTid<'static> + XSWDHandler
 {
-    RPCServer(WalletRpcServerShared<W>),
-    XSWD(XSWDServer<W>)
+    RPCServer(WalletRpcServerShared<W, H>),
+    XSWD(XSWDServer<W, H>)
 }
 
-impl<W> APIServer<W>
+impl<W, H> APIServer<W, H>
 where
-    W: ShareableTid<'static> + XSWDHandler
+    W: ShareableTid<'static> + XSWDHandler,
+    H: ServerStopHandle
 {
     pub async fn notify_event<V: Serialize>(&self, event: &NotifyEvent, value: &V) {
         let json = json!(value);
@@ -42,14 +49,144 @@ where
         }
     }
 
-    pub async fn stop(self) {
+    // Stop the server, if graceful, in-flight requests
+    // are awaited up to the timeout
+    pub async fn stop(self, graceful: bool, timeout: Duration) {
         match self {
             APIServer::RPCServer(server) => {
-                server.stop().await;
+                server.stop(graceful, timeout).await;
             },
             APIServer::XSWD(xswd) => {
-                xswd.stop().await;
+                xswd.stop(graceful, timeout).await;
             }
         }
     }
+}
+
+// Handle to stop a running HTTP server
+#[async_trait]
+pub trait ServerStopHandle: Send + Sync {
+    async fn stop(&self, graceful: bool);
+}
+
+#[async_trait]
+impl ServerStopHandle for ServerHandle {
+    async fn stop(&self, graceful: bool) {
+        ServerHandle::stop(self, graceful).await
+    }
+}
+
+// Stop the server behind the handle
+// A graceful stop lets the in-flight requests complete
+// but we don't wait for them longer than the timeout:
+// the server is then stopped without waiting anymore
+pub(crate) async fn stop_server<H: ServerStopHandle>(handle: &H, graceful: bool, timeout: Duration) {
+    if !graceful {
+        handle.stop(false).await;
+        return;
+    }
+
+    if with_timeout(timeout, handle.stop(true)).await.is_err() {
+        warn!("Server didn't stop within {:?}, aborting the in-flight requests", timeout);
+        handle.stop(false).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use anyhow::Error;
+    use xelis_common::{
+        crypto::{elgamal::PublicKey as DecompressedPublicKey, KeyPair},
+        rpc::{tid, InternalRpcError, RPCHandler, RpcRequest, RpcResponseError}
+    };
+    use crate::api::{AppStateShared, PermissionRequest, PermissionResult, XSWDResponse};
+    use super::*;
+
+    struct MockHandler {
+        keypair: KeyPair
+    }
+
+    tid!(MockHandler);
+
+    #[async_trait]
+    impl XSWDHandler for MockHandler {
+        async fn request_permission(&self, _: &AppStateShared, _: PermissionRequest<'_>) -> Result<PermissionResult, Error> {
+            Ok(PermissionResult::Reject)
+        }
+
+        async fn cancel_request_permission(&self, _: &AppStateShared) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error> {
+            Ok(self.keypair.get_public_key())
+        }
+
+        async fn call_node_with(&self, _: &AppStateShared, request: RpcRequest) -> Result<XSWDResponse, RpcResponseError> {
+            Err(RpcResponseError::new(request.id, InternalRpcError::InternalError("Node calls are not supported")))
+        }
+
+        async fn on_app_disconnect(&self, _: AppStateShared) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    // Record the graceful flags used to stop it
+    // A graceful stop waits for the in-flight requests during the delay
+    struct MockHandle {
+        calls: Arc<Mutex<Vec<bool>>>,
+        delay: Duration
+    }
+
+    #[async_trait]
+    impl ServerStopHandle for MockHandle {
+        async fn stop(&self, graceful: bool) {
+            self.calls.lock().unwrap().push(graceful);
+            if graceful {
+                xelis_common::tokio::time::sleep(self.delay).await;
+            }
+        }
+    }
+
+    fn create_handle(delay: Duration) -> (MockHandle, Arc<Mutex<Vec<bool>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let handle = MockHandle {
+            calls: calls.clone(),
+            delay
+        };
+
+        (handle, calls)
+    }
+
+    fn create_servers(delay: Duration) -> Vec<(APIServer<MockHandler, MockHandle>, Arc<Mutex<Vec<bool>>>)> {
+        let handler = || RPCHandler::new(MockHandler { keypair: KeyPair::new() }, None);
+
+        let (handle, rpc_calls) = create_handle(delay);
+        let rpc = APIServer::RPCServer(Arc::new(WalletRpcServer::with_handle(handle, handler())));
+
+        let (handle, xswd_calls) = create_handle(delay);
+        let xswd = APIServer::XSWD(XSWDServer::with_handle(handle, handler()));
+
+        vec![(rpc, rpc_calls), (xswd, xswd_calls)]
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_stop_graceful_flag() {
+        for graceful in [true, false] {
+            for (server, calls) in create_servers(Duration::ZERO) {
+                server.stop(graceful, Duration::from_secs(5)).await;
+                assert_eq!(*calls.lock().unwrap(), vec![graceful]);
+            }
+        }
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_stop_graceful_timeout() {
+        // In-flight requests never complete, the server is then stopped without waiting
+        for (server, calls) in create_servers(Duration::from_secs(60)) {
+            server.stop(true, Duration::from_millis(10)).await;
+            assert_eq!(*calls.lock().unwrap(), vec![true, false]);
+        }
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use actix_web_httpauth::{
     middleware::HttpAuthentication,
@@ -13,6 +13,7 @@ use xelis_common::{
     },
     api::wallet::NotifyEvent,
     config,
+    crypto::hash,
     rpc::{
         server::{
             json_rpc,
@@ -29,6 +30,7 @@ use xelis_common::{
         ShareableTid,
     }
 };
+use crate::api::server::{stop_server, ServerStopHandle};
 use actix_web::{
     get,
     HttpResponse,
@@ -41,18 +43,18 @@ use actix_web::{
     error::{ErrorUnauthorized, ErrorBadGateway, ErrorBadRequest}
 };
 
-pub type WalletRpcServerShared<W> = Arc<WalletRpcServer<W>>;
+pub type WalletRpcServerShared<W, H = ServerHandle> = Arc<WalletRpcServer<W, H>>;
 
 pub struct AuthConfig {
     pub username: String,
     pub password: String
 }
 
-pub struct WalletRpcServer<W>
+pub struct WalletRpcServer<W, H = ServerHandle>
 where
     W: ShareableTid<'static>,
 {
-    handle: Mutex<Option<ServerHandle>>,
+    handle: Mutex<Option<H>>,
     websocket: WebSocketServerShared<EventWebSocketHandler<W, NotifyEvent>>,
     auth_config: Option<AuthConfig>
 }
@@ -112,19 +114,40 @@ where
             let user = credentials.user_id();
             let password = credentials.password().ok_or(ErrorBadRequest("Missing password"))?;
 
-            if *config.username != *user || *config.password != *password {
+            // Compare the hashes in constant time to not leak the credentials
+            let valid_user = hash(config.username.as_bytes()).ct_eq(&hash(user.as_bytes()));
+            let valid_password = hash(config.password.as_bytes()).ct_eq(&hash(password.as_bytes()));
+            if !bool::from(valid_user & valid_password) {
                 return Err(ErrorUnauthorized("Username/password are invalid"))
             }
         }
 
         Ok(())
     }
+}
+
+impl<W, H> WalletRpcServer<W, H>
+where
+    W: ShareableTid<'static>,
+    H: ServerStopHandle
+{
+    // Create the server around an already running handle
+    #[cfg(test)]
+    pub(super) fn with_handle(handle: H, rpc_handler: RPCHandler<W>) -> Self {
+        Self {
+            handle: Mutex::new(Some(handle)),
+            websocket: WebSocketServer::new(EventWebSocketHandler::new(rpc_handler, 0)),
+            auth_config: None
+        }
+    }
 
-    pub async fn stop(&self) {
+    // Stop the server, if graceful, in-flight requests
+    // are awaited up to the timeout
+    pub async fn stop(&self, graceful: bool, timeout: Duration) {
         info!("Stopping RPC Server...");
         let mut handle = self.handle.lock().await;
         if let Some(handle) = handle.take() {
-            handle.stop(false).await;
+            stop_server(&handle, graceful, timeout).await;
             info!("RPC Server is now stopped!");
         } else {
             warn!("RPC Server is not running!");
@@ -132,7 +155,7 @@ where
     }
 }
 
-impl<W> WebSocketServerHandler<EventWebSocketHandler<W, NotifyEvent>> for WalletRpcServer<W>
+impl<W, H> WebSocketServerHandler<EventWebSocketHandler<W, NotifyEvent>> for WalletRpcServer<W, H>
 where
     W: ShareableTid<'static>,
 {
@@ -1,7 +1,8 @@
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
-    sync::Arc
+    sync::Arc,
+    time::Duration
 };
 
 use actix_web::{
@@ -21,7 +22,6 @@ use serde_json::{json, Value};
 use xelis_common::{
     api::{
         EventResult,
-        daemon::NotifyEvent as DaemonNotifyEvent,
         wallet::NotifyEvent
     },
     rpc::{
@@ -33,16 +33,18 @@ use xelis_common::{
     },
     tokio::{
         spawn_task,
-        sync::RwLock,
-        task
+        sync::RwLock
     }
 };
 
 use crate::{
     api::{
+        server::{stop_server, ServerStopHandle},
         AppState,
         AppStateShared,
         ApplicationData,
+        ConnectionContext,
+        ConnectionContextShared,
         XSWDError,
         XSWDProvider,
         XSWDHandler,
@@ -52,12 +54,12 @@ use crate::{
     config::XSWD_BIND_ADDRESS
 };
 
-pub struct XSWDServer<W>
+pub struct XSWDServer<W, H = ServerHandle>
 where
     W: ShareableTid<'static> + XSWDHandler
 {
     websocket: Arc<WebSocketServer<XSWDWebSocketHandler<W>>>,
-    handle: ServerHandle
+    handle: H
 }
 
 impl<W> XSWDServer<W>
@@ -88,14 +90,31 @@ where
             handle
         })
     }
+}
+
+impl<W, H> XSWDServer<W, H>
+where
+    W: ShareableTid<'static> + XSWDHandler,
+    H: ServerStopHandle
+{
+    // Create the server around an already running handle
+    #[cfg(test)]
+    pub(super) fn with_handle(handle: H, handler: RPCHandler<W>) -> Self {
+        Self {
+            websocket: WebSocketServer::new(XSWDWebSocketHandler::new(handler)),
+            handle
+        }
+    }
 
     pub fn get_handler(&self) -> &XSWDWebSocketHandler<W> {
         self.websocket.get_handler()
     }
 
-    pub async fn stop(&self) {
+    // Stop the server, if graceful, in-flight requests
+    // are awaited up to the timeout
+    pub async fn stop(&self, graceful: bool, timeout: Duration) {
         info!("Stopping XSWD...");
-        self.handle.stop(false).await;
+        stop_server(&self.handle, graceful, timeout).await;
         info!("XSWD has been stopped !");
     }
 }
@@ -106,7 +125,8 @@ where
 {
     // All applications connected to the wallet
     applications: RwLock<HashMap<WebSocketSessionShared<Self>, AppStateShared>>,
-    node_events: RwLock<HashMap<DaemonNotifyEvent, HashMap<AppStateShared, task::JoinHandle<()>>>>,
+    // Context of each connection, holding its node events subscriptions
+    connections: RwLock<HashMap<WebSocketSessionShared<Self>, ConnectionContextShared>>,
     xswd: XSWD<W>,
 }
 
@@ -120,7 +140,7 @@ where
         Self {
             applications: RwLock::new(HashMap::new()),
             xswd: XSWD::new(handler),
-            node_events: RwLock::new(HashMap::new()),
+            connections: RwLock::new(HashMap::new()),
         }
     }
 
@@ -200,35 +220,28 @@ where
             match self.xswd.on_request(self, &app, message).await? {
                 XSWDResponse::Request(v) => Ok(v),
                 XSWDResponse::Event(event, stream, response) => {
-                    let mut events = self.node_events.write().await;
-                    let apps = events.entry(event)
-                        .or_insert_with(HashMap::new);
-
-                    match stream {
-                        Some((mut stream, id), ) => {
-                            if !apps.contains_key(&app) {
-                                let session = session.clone();
-                                let handle = spawn_task("xswd-event-listener", async move {
-                                    while let Ok(value) = stream.recv().await {
-                                        // we need to map the result to the requested id
-                                        let response = RpcResponse::new(Cow::Borrowed(&id), Cow::Borrowed(&value));
-                                        if let Err(e) = session.send_json(response).await {
-                                            error!("Error while sending event notification to session: {}", e);
-                                            break;
-                                        }
-                                    }
-                                });
-
-                                apps.insert(app.clone(), handle);
-                            }
-                        },
-                        None => {
-                            if let Some(handle) = apps.remove(&app) {
-                                handle.abort();
-                            }
-                        },
+                    let connection = {
+                        let connections = self.connections.read().await;
+                        connections.get(session).cloned()
+                    };
+
+                    // Connection is already closed, its subscriptions are gone
+                    let Some(connection) = connection else {
+                        return Ok(response);
                     };
 
+                    let session = session.clone();
+                    connection.on_event(event, stream, move |response| {
+                        let session = session.clone();
+                        async move {
+                            if let Err(e) = session.send_json(response).await {
+                                error!("Error while sending event notification to session: {}", e);
+                                return false;
+                            }
+                            true
+                        }
+                    }).await;
+
                     Ok(response)
                 }
             }
@@ -264,7 +277,24 @@ impl<W> WebSocketHandler for XSWDWebSocketHandler<W>
 where
     W: ShareableTid<'static> + XSWDHandler
 {
+    async fn on_connection(&self, session: &WebSocketSessionShared<Self>) -> Result<Option<HttpResponse>, anyhow::Error> {
+        let mut connections = self.connections.write().await;
+        connections.insert(session.clone(), Arc::new(ConnectionContext::new()));
+
+        Ok(None)
+    }
+
     async fn on_close(&self, session: &WebSocketSessionShared<Self>) -> Result<(), anyhow::Error> {
+        let connection = {
+            let mut connections = self.connections.write().await;
+            connections.remove(session)
+        };
+
+        // Drop all the node events subscriptions of this connection
+        if let Some(connection) = connection {
+            connection.close().await;
+        }
+
         let app = {
             let mut applications = self.applications.write().await;
             applications.remove(session)
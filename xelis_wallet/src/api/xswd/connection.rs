@@ -0,0 +1,132 @@
+use std::{borrow::Cow, collections::HashMap, future::Future, sync::Arc};
+
+use log::trace;
+use serde_json::{json, Value};
+use xelis_common::{
+    api::daemon::NotifyEvent as DaemonNotifyEvent,
+    rpc::{Id, RpcResponse},
+    tokio::{
+        spawn_task,
+        sync::{broadcast, Mutex},
+        task
+    }
+};
+
+// Connection scoped context
+// It is created when a connection is established
+// and keeps track of all its active event subscriptions
+// so they can be dropped at once when the connection is closed
+pub struct ConnectionContext {
+    subscriptions: Mutex<HashMap<DaemonNotifyEvent, task::JoinHandle<()>>>,
+}
+
+pub type ConnectionContextShared = Arc<ConnectionContext>;
+
+impl ConnectionContext {
+    pub fn new() -> Self {
+        Self {
+            subscriptions: Mutex::new(HashMap::new())
+        }
+    }
+
+    // Handle an event response from the XSWD
+    // If a stream is provided, a task is spawned to forward every value
+    // mapped to the requested id through the `send` function
+    // Otherwise, the event is unsubscribed
+    // `send` must return false to stop the forwarding
+    pub async fn on_event<F, Fut>(&self, event: DaemonNotifyEvent, stream: Option<(broadcast::Receiver<Value>, Option<Id>)>, send: F)
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send
+    {
+        match stream {
+            Some((stream, id)) => self.subscribe(event, stream, id, send).await,
+            None => {
+                self.unsubscribe(&event).await;
+            }
+        }
+    }
+
+    // Register a new subscription for this connection
+    // Nothing is done if the event is already subscribed
+    pub async fn subscribe<F, Fut>(&self, event: DaemonNotifyEvent, mut stream: broadcast::Receiver<Value>, id: Option<Id>, send: F)
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send
+    {
+        let mut subscriptions = self.subscriptions.lock().await;
+        if subscriptions.contains_key(&event) {
+            trace!("event already subscribed on this connection");
+            return;
+        }
+
+        let handle = spawn_task("xswd-event-listener", async move {
+            while let Ok(value) = stream.recv().await {
+                // we need to map the result to the requested id
+                let response = json!(RpcResponse::new(Cow::Borrowed(&id), Cow::Borrowed(&value)));
+                if !send(response).await {
+                    break;
+                }
+            }
+        });
+
+        subscriptions.insert(event, handle);
+    }
+
+    // Unsubscribe from an event and stop its forwarding task
+    pub async fn unsubscribe(&self, event: &DaemonNotifyEvent) -> bool {
+        let mut subscriptions = self.subscriptions.lock().await;
+        match subscriptions.remove(event) {
+            Some(handle) => {
+                handle.abort();
+                true
+            },
+            None => false
+        }
+    }
+
+    // Number of active subscriptions
+    pub async fn count(&self) -> usize {
+        self.subscriptions.lock().await.len()
+    }
+
+    // Drain all the subscriptions of the connection
+    // Each task is awaited after being aborted so its receiver is dropped
+    pub async fn close(&self) {
+        let handles = {
+            let mut subscriptions = self.subscriptions.lock().await;
+            subscriptions.drain()
+                .map(|(_, handle)| handle)
+                .collect::<Vec<_>>()
+        };
+
+        trace!("closing {} subscriptions", handles.len());
+        for handle in handles {
+            handle.abort();
+            let _ = handle.await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_close_drops_subscriptions() {
+        let context = ConnectionContext::new();
+        let (new_block, _) = broadcast::channel(1);
+        let (new_topoheight, _) = broadcast::channel(1);
+
+        context.on_event(DaemonNotifyEvent::NewBlock, Some((new_block.subscribe(), None)), |_| async { true }).await;
+        context.on_event(DaemonNotifyEvent::NewTopoHeight, Some((new_topoheight.subscribe(), None)), |_| async { true }).await;
+        assert_eq!(context.count().await, 2);
+        assert_eq!(new_block.receiver_count(), 1);
+        assert_eq!(new_topoheight.receiver_count(), 1);
+
+        context.close().await;
+        assert_eq!(context.count().await, 0);
+        assert_eq!(new_block.receiver_count(), 0);
+        assert_eq!(new_topoheight.receiver_count(), 0);
+    }
+}
@@ -34,7 +34,9 @@ pub enum XSWDError {
     #[error("Application permissions are not signed")]
     ApplicationPermissionsNotSigned,
     #[error("Invalid signature for application data")]
-    InvalidSignatureForApplicationData
+    InvalidSignatureForApplicationData,
+    #[error("A request with the same id is already in progress")]
+    DuplicateRequestId
 }
 
 impl From<XSWDError> for InternalRpcError {
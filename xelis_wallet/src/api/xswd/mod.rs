@@ -1,21 +1,23 @@
 mod error;
 mod types;
 mod relayer;
+mod connection;
 
 use anyhow::{Context as _, Error};
 use async_trait::async_trait;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use serde_json::{
     Value,
     json
 };
 use xelis_common::{
     api::{
-        wallet::{NotifyEvent, XSWDPrefetchPermissions},
+        wallet::{NotifyEvent, PrefetchResult, XSWDPrefetchPermissions, XSWDUpdateMetadata},
         daemon::NotifyEvent as DaemonNotifyEvent
     },
     async_handler,
     crypto::elgamal::PublicKey as DecompressedPublicKey,
+    time::Instant,
     rpc::{
         server::websocket::Events,
         *
@@ -26,7 +28,17 @@ use log::{debug, info};
 
 pub use error::XSWDError;
 pub use types::*;
-pub use relayer::{XSWDRelayer, XSWDRelayerShared};
+pub use connection::*;
+pub use relayer::{
+    BoxedRelayConnection,
+    BoxedRelayTransport,
+    ReconnectConfig,
+    RelayConnection,
+    RelayTransport,
+    WebSocketTransport,
+    XSWDRelayer,
+    XSWDRelayerShared
+};
 
 // XSWD Protocol (XELIS Secure WebSocket DApp)
 // is a way to communicate with the XELIS Wallet
@@ -76,6 +88,11 @@ pub trait XSWDHandler {
     async fn on_prefetch_permissions_request(&self, _: &AppStateShared, _: XSWDPrefetchPermissions) -> Result<IndexMap<String, Permission>, Error> {
         Ok(IndexMap::new())
     }
+
+    // When a permission of an application was updated
+    // The expiry is set for an Allow permission granted for a limited time
+    // This is optional and can be used to persist it
+    async fn on_permission_change(&self, _: &AppStateShared, _: &str, _: Permission, _: Option<Instant>) {}
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -92,6 +109,7 @@ where
     pub fn new(mut handler: RPCHandler<W>) -> Self {
         // Register internal methods
         handler.register_method_with_params("xswd.prefetch_permissions", async_handler!(prefetch_permissions::<W>));
+        handler.register_method_with_params("xswd.update_metadata", async_handler!(update_metadata));
 
         Self {
             events: Events::new(&mut handler),
@@ -124,23 +142,7 @@ where
         hex::decode(&app_data.get_id())
             .map_err(|_| XSWDError::InvalidHexaApplicationId)?;
 
-        if app_data.get_name().len() > 32 {
-            return Err(XSWDError::ApplicationNameTooLong)
-        }
-
-        if app_data.get_description().len() > 255 {
-            return Err(XSWDError::ApplicationDescriptionTooLong)
-        }
-
-        if let Some(url) = &app_data.get_url() {
-            if url.len() > 255 {
-                return Err(XSWDError::InvalidURLFormat)
-            }
-
-            if !url.starts_with("http://") && !url.starts_with("https://") {
-                return Err(XSWDError::InvalidURLFormat)
-            }
-        }
+        verify_metadata(app_data.get_name(), app_data.get_description(), app_data.get_url().as_deref())?;
 
         if app_data.get_permissions().len() > 255 {
             return Err(XSWDError::TooManyPermissions)
@@ -153,7 +155,14 @@ where
                 perm.as_str()
             };
 
-            if !self.handler.has_method(trimmed_perm) {
+            // A wildcard must match at least one registered method
+            // and can't grant every method at once
+            let known = match trimmed_perm.strip_suffix(WILDCARD) {
+                Some(prefix) => !prefix.is_empty() && self.handler.has_method_with_prefix(prefix),
+                None => self.handler.has_method(trimmed_perm)
+            };
+
+            if !known {
                 debug!("Permission '{}' is unknown", perm);
                 return Err(XSWDError::UnknownMethodInPermissionsList(perm.clone()))
             }
@@ -202,6 +211,13 @@ where
         P: XSWDProvider
     {
         let mut request = parse_request_from_bytes(message)?;
+        // Reject a request reusing the id of one still in progress
+        let _in_flight = match request.id.as_ref() {
+            Some(id) => Some(app.track_request(id)
+                .ok_or_else(|| RpcResponseError::new(request.id.clone(), XSWDError::DuplicateRequestId))?),
+            None => None
+        };
+
         // Redirect all node methods to the node method handler
         if request.method.starts_with("node.") {
             // Remove the 5 first chars (node.)
@@ -256,6 +272,15 @@ where
     // verify the permission for a request
     // if the permission is not set, it will request it to the user
     pub async fn verify_permission_for_request<P>(&self, provider: &P, app: &AppStateShared, request: &RpcRequest) -> Result<(), RpcResponseError>
+    where
+        P: XSWDProvider,
+    {
+        self.verify_permission_for_request_at(provider, app, request, Instant::now()).await
+    }
+
+    // verify the permission for a request at the given instant
+    // used to check the expiry of the permissions granted with a TTL
+    pub async fn verify_permission_for_request_at<P>(&self, provider: &P, app: &AppStateShared, request: &RpcRequest, now: Instant) -> Result<(), RpcResponseError>
     where
         P: XSWDProvider,
     {
@@ -267,15 +292,23 @@ where
             return Err(RpcResponseError::new(request.id.clone(), XSWDError::ApplicationNotFound))
         }
 
-        let permission = {
-            let permissions = app.get_permissions().lock().await;
-            permissions.get(&request.method)
-                .copied()
+        let permission = match app.resolve_permission(&request.method, now).await {
+            Some((permission, expired)) => {
+                // The TTL of an Allow permission elapsed, it must be asked again
+                if let Some(key) = expired {
+                    self.handler.get_data()
+                        .on_permission_change(app, &key, Permission::Ask, None).await;
+                }
+
+                Some(permission)
+            },
+            None => None
         };
 
         match permission {
             // If the permission wasn't mentionned at AppState creation
-            // It is directly rejected
+            // It is directly rejected, as denied if the app uses an allow list
+            None if app.is_allow_list() => Err(RpcResponseError::new(request.id.clone(), XSWDError::PermissionDenied)),
             None =>  Err(RpcResponseError::new(request.id.clone(), XSWDError::PermissionInvalid)),
             // User has already accepted this method
             Some(Permission::Allow) => Ok(()),
@@ -290,14 +323,16 @@ where
                 match result {
                     PermissionResult::Accept => Ok(()),
                     PermissionResult::Reject => Err(RpcResponseError::new(request.id.clone(), XSWDError::PermissionDenied)),
-                    PermissionResult::AlwaysAccept => {
-                        let mut permissions = app.get_permissions().lock().await;
-                        permissions.insert(request.method.clone(), Permission::Allow);
+                    PermissionResult::AlwaysAccept(ttl) => {
+                        app.allow_permission(request.method.clone(), ttl, now).await;
+                        self.handler.get_data()
+                            .on_permission_change(app, &request.method, Permission::Allow, ttl.map(|ttl| now + ttl)).await;
                         Ok(())
                     },
                     PermissionResult::AlwaysReject => {
-                        let mut permissions = app.get_permissions().lock().await;
-                        permissions.insert(request.method.clone(), Permission::Allow);
+                        app.set_permission(request.method.clone(), Permission::Reject).await;
+                        self.handler.get_data()
+                            .on_permission_change(app, &request.method, Permission::Reject, None).await;
                         Err(RpcResponseError::new(request.id.clone(), XSWDError::PermissionDenied))
                     }   
                 }
@@ -306,9 +341,62 @@ where
     }
 }
 
+// Verify the mutable metadata of an application
+fn verify_metadata(name: &str, description: &str, url: Option<&str>) -> Result<(), XSWDError> {
+    if name.len() > 32 {
+        return Err(XSWDError::ApplicationNameTooLong)
+    }
+
+    if description.len() > 255 {
+        return Err(XSWDError::ApplicationDescriptionTooLong)
+    }
+
+    if let Some(url) = url {
+        if url.len() > 255 {
+            return Err(XSWDError::InvalidURLFormat)
+        }
+
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(XSWDError::InvalidURLFormat)
+        }
+    }
+
+    Ok(())
+}
+
+/// Internal RPC method used by XSWD
+/// To update the metadata of an application without re-registering it
+/// Permissions are left untouched
+pub async fn update_metadata(context: &Context<'_, '_>, params: XSWDUpdateMetadata) -> Result<bool, InternalRpcError> {
+    let app: &AppStateShared = context.get()
+        .context("XSWD App State not found in context")?;
+
+    if params.name.is_none() && params.description.is_none() && params.url.is_none() {
+        return Err(InternalRpcError::InvalidParams("No metadata to update"))
+    }
+
+    let mut metadata = app.get_metadata();
+    if let Some(name) = params.name {
+        metadata.name = name;
+    }
+
+    if let Some(description) = params.description {
+        metadata.description = description;
+    }
+
+    if let Some(url) = params.url {
+        metadata.url = Some(url);
+    }
+
+    verify_metadata(&metadata.name, &metadata.description, metadata.url.as_deref())?;
+    app.set_metadata(metadata);
+
+    Ok(true)
+}
+
 /// Internal RPC method used by XSWD
 /// To request in one time the permissions
-pub async fn prefetch_permissions<W: ShareableTid<'static> + XSWDHandler>(context: &Context<'_, '_>, params: XSWDPrefetchPermissions) -> Result<bool, InternalRpcError> {
+pub async fn prefetch_permissions<W: ShareableTid<'static> + XSWDHandler>(context: &Context<'_, '_>, params: XSWDPrefetchPermissions) -> Result<PrefetchResult, InternalRpcError> {
     if params.permissions.is_empty() {
         return Err(InternalRpcError::InvalidParams("Permissions list cannot be empty"))
     }
@@ -327,9 +415,9 @@ pub async fn prefetch_permissions<W: ShareableTid<'static> + XSWDHandler>(contex
     }
 
     {
-        let lock = app.get_permissions().lock().await;
+        let permissions = app.permissions_snapshot().await;
         for perm in params.permissions.iter() {
-            if !lock.contains_key(perm) {
+            if !permissions.contains_key(perm) {
                 debug!("Permission '{}' is unknown", perm);
                 return Err(InternalRpcError::InvalidParams("Unknown method in permissions list"))
             }
@@ -338,16 +426,444 @@ pub async fn prefetch_permissions<W: ShareableTid<'static> + XSWDHandler>(contex
 
     let wallet = handler.get_data();
 
+    let requested = params.permissions.clone();
+
     app.set_requesting(true);
     let res = wallet.on_prefetch_permissions_request(app, params).await?;
 
-    if !res.is_empty() {
-        let mut permissions = app.get_permissions().lock().await;
-        for (method, perm) in res {
-            permissions.insert(method, perm);
+    let changes = res.iter()
+        .map(|(method, permission)| (method.clone(), *permission))
+        .collect::<Vec<_>>();
+    let result = prefetch_result(&requested, &res);
+    app.set_permissions(res).await;
+    app.set_requesting(false);
+
+    for (method, permission) in changes {
+        wallet.on_permission_change(app, &method, permission, None).await;
+    }
+
+    Ok(result)
+}
+
+// Report which requested methods were granted or denied
+// by the permissions returned by the handler
+fn prefetch_result(requested: &IndexSet<String>, response: &IndexMap<String, Permission>) -> PrefetchResult {
+    let mut result = PrefetchResult::default();
+    for method in requested {
+        match response.get(method) {
+            Some(Permission::Allow) => result.granted.push(method.clone()),
+            _ => result.denied.push(method.clone())
         }
     }
-    app.set_requesting(false);
 
-    Ok(true)
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex as StdMutex}, time::Duration};
+    use xelis_common::{crypto::KeyPair, tokio::sync::Notify};
+    use super::*;
+
+    struct MockHandler {
+        keypair: KeyPair,
+        prompts: AtomicUsize,
+        // Hold the permission prompt until notified
+        gate: Option<Notify>,
+        // Answer given to every permission prompt
+        answer: PermissionResult,
+        // Permission changes notified
+        changes: StdMutex<Vec<(String, Permission, Option<Instant>)>>
+    }
+
+    tid!(MockHandler);
+
+    impl MockHandler {
+        fn new(answer: PermissionResult) -> Self {
+            Self {
+                keypair: KeyPair::new(),
+                prompts: AtomicUsize::new(0),
+                gate: None,
+                answer,
+                changes: StdMutex::new(Vec::new())
+            }
+        }
+
+        // Every prompt is held until the gate is notified
+        fn with_gate(answer: PermissionResult) -> Self {
+            Self {
+                gate: Some(Notify::new()),
+                ..Self::new(answer)
+            }
+        }
+    }
+
+    #[async_trait]
+    impl XSWDHandler for MockHandler {
+        async fn request_permission(&self, _: &AppStateShared, _: PermissionRequest<'_>) -> Result<PermissionResult, Error> {
+            self.prompts.fetch_add(1, Ordering::SeqCst);
+            if let Some(gate) = self.gate.as_ref() {
+                gate.notified().await;
+            }
+
+            Ok(self.answer)
+        }
+
+        async fn cancel_request_permission(&self, _: &AppStateShared) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error> {
+            Ok(self.keypair.get_public_key())
+        }
+
+        async fn call_node_with(&self, _: &AppStateShared, request: RpcRequest) -> Result<XSWDResponse, RpcResponseError> {
+            Err(RpcResponseError::new(request.id, InternalRpcError::InternalError("Node calls are not supported")))
+        }
+
+        async fn on_app_disconnect(&self, _: AppStateShared) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn on_permission_change(&self, _: &AppStateShared, method: &str, new: Permission, expires_at: Option<Instant>) {
+            self.changes.lock().unwrap().push((method.to_owned(), new, expires_at));
+        }
+    }
+
+    struct MockProvider;
+
+    #[async_trait]
+    impl XSWDProvider for MockProvider {
+        async fn has_app_with_id(&self, _: &str) -> bool {
+            true
+        }
+    }
+
+    fn create_app(methods: &[&str]) -> AppStateShared {
+        let data: ApplicationData = serde_json::from_value(json!({
+            "id": "0".repeat(64),
+            "name": "test",
+            "description": "test app",
+            "url": null,
+            "permissions": methods
+        })).unwrap();
+
+        Arc::new(AppState::new(data))
+    }
+
+    fn create_allow_list_app(methods: &[&str]) -> AppStateShared {
+        let data: ApplicationData = serde_json::from_value(json!({
+            "id": "0".repeat(64),
+            "name": "test",
+            "description": "test app",
+            "url": null,
+            "permissions": methods,
+            "allow_list": true
+        })).unwrap();
+
+        Arc::new(AppState::new(data))
+    }
+
+    fn create_request(method: &str) -> RpcRequest {
+        RpcRequest {
+            jsonrpc: "2.0".to_owned(),
+            id: None,
+            method: method.to_owned(),
+            params: None
+        }
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_always_reject_is_remembered() {
+        let handler = MockHandler::new(PermissionResult::AlwaysReject);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["get_balance"]);
+        let request = create_request("get_balance");
+
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &request).await.is_err());
+        assert_eq!(xswd.handler().get_data().prompts.load(Ordering::SeqCst), 1);
+
+        let snapshot = app.permissions_snapshot().await;
+        assert!(matches!(snapshot.get("get_balance"), Some(Permission::Reject)));
+
+        // Same method again must be rejected without asking the user
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &request).await.is_err());
+        assert_eq!(xswd.handler().get_data().prompts.load(Ordering::SeqCst), 1);
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_duplicate_request_id_in_flight() {
+        let handler = MockHandler::with_gate(PermissionResult::AlwaysReject);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["schema"]);
+        let message = serde_json::to_vec(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "schema"
+        })).unwrap();
+
+        // First request waits on the permission prompt
+        // while the second one reuses its id
+        let first = xswd.on_request(&MockProvider, &app, &message);
+        let second = async {
+            let res = xswd.on_request(&MockProvider, &app, &message).await;
+            xswd.handler().get_data().gate.as_ref().unwrap().notify_one();
+            res
+        };
+        let (first, second) = futures::join!(first, second);
+
+        let duplicate = XSWDError::DuplicateRequestId.to_string();
+        let Err(err) = second else {
+            panic!("second request should be rejected");
+        };
+        assert!(err.to_string().contains(&duplicate));
+        assert_eq!(xswd.handler().get_data().prompts.load(Ordering::SeqCst), 1);
+
+        let Err(err) = first else {
+            panic!("first request should be denied by the user");
+        };
+        assert!(!err.to_string().contains(&duplicate));
+
+        // The id is released once the first request is done
+        let Err(err) = xswd.on_request(&MockProvider, &app, &message).await else {
+            panic!("request should be denied by the stored permission");
+        };
+        assert!(!err.to_string().contains(&duplicate));
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_update_metadata() {
+        let handler = MockHandler::new(PermissionResult::AlwaysReject);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["get_balance"]);
+        app.set_permission("get_balance".to_owned(), Permission::Allow).await;
+
+        let update = |params: Value| serde_json::to_vec(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "xswd.update_metadata",
+            "params": params
+        })).unwrap();
+
+        let message = update(json!({ "description": "updated description" }));
+        let Ok(XSWDResponse::Request(Some(response))) = xswd.on_request(&MockProvider, &app, &message).await else {
+            panic!("update should succeed");
+        };
+        assert_eq!(response["result"], json!(true));
+
+        assert_eq!(app.get_description(), "updated description");
+        assert_eq!(app.get_name(), "test");
+        assert!(app.get_url().is_none());
+
+        // Invalid URL is rejected and nothing is updated
+        let message = update(json!({ "description": "other", "url": "ftp://xelis.io" }));
+        let Ok(XSWDResponse::Request(Some(response))) = xswd.on_request(&MockProvider, &app, &message).await else {
+            panic!("update should return a response");
+        };
+        assert!(response.get("error").is_some());
+        assert_eq!(app.get_description(), "updated description");
+
+        // Permissions are unchanged and no prompt was made
+        let permissions = app.permissions_snapshot().await;
+        assert_eq!(permissions.len(), 1);
+        assert!(matches!(permissions.get("get_balance"), Some(Permission::Allow)));
+        assert_eq!(xswd.handler().get_data().prompts.load(Ordering::SeqCst), 0);
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_allow_list() {
+        let handler = MockHandler::new(PermissionResult::Accept);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_allow_list_app(&["get_balance", "get_address"]);
+        app.set_permission("get_address".to_owned(), Permission::Allow).await;
+        let prompts = || xswd.handler().get_data().prompts.load(Ordering::SeqCst);
+
+        // Listed method to ask still prompts the user
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_balance")).await.is_ok());
+        assert_eq!(prompts(), 1);
+
+        // Listed method already allowed passes silently
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_address")).await.is_ok());
+        assert_eq!(prompts(), 1);
+
+        // Any other method is denied without prompting
+        let Err(err) = xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_nonce")).await else {
+            panic!("non listed method should be denied");
+        };
+        assert!(err.to_string().contains(&XSWDError::PermissionDenied.to_string()));
+        assert_eq!(prompts(), 1);
+
+        // Without the allow list, the method is invalid
+        let app = create_app(&["get_balance"]);
+        let Err(err) = xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_nonce")).await else {
+            panic!("unknown method should be rejected");
+        };
+        assert!(err.to_string().contains(&XSWDError::PermissionInvalid.to_string()));
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_permission_change_notified() {
+        let handler = MockHandler::new(PermissionResult::AlwaysAccept(None));
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["get_balance"]);
+        let request = create_request("get_balance");
+
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &request).await.is_ok());
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &request).await.is_ok());
+
+        // Only the transition from Ask to Allow is notified
+        let changes = xswd.handler().get_data().changes.lock().unwrap().clone();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].0, "get_balance");
+        assert!(matches!(changes[0].1, Permission::Allow));
+        assert!(changes[0].2.is_none());
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_always_accept_with_ttl_expires() {
+        let handler = MockHandler::new(PermissionResult::AlwaysAccept(Some(Duration::from_secs(60))));
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["get_balance"]);
+        let request = create_request("get_balance");
+        let prompts = || xswd.handler().get_data().prompts.load(Ordering::SeqCst);
+
+        // Mocked clock, only moved forward by the test
+        let start = Instant::now();
+        assert!(xswd.verify_permission_for_request_at(&MockProvider, &app, &request, start).await.is_ok());
+        assert_eq!(prompts(), 1);
+
+        // Still valid, allowed silently
+        let before_expiry = start + Duration::from_secs(59);
+        assert!(xswd.verify_permission_for_request_at(&MockProvider, &app, &request, before_expiry).await.is_ok());
+        assert_eq!(prompts(), 1);
+        assert!(matches!(app.get_permission("get_balance", before_expiry).await, Some(Permission::Allow)));
+
+        // Expired, the user is asked again
+        let after_expiry = start + Duration::from_secs(60);
+        assert!(matches!(app.get_permission("get_balance", after_expiry).await, Some(Permission::Ask)));
+        assert!(xswd.verify_permission_for_request_at(&MockProvider, &app, &request, after_expiry).await.is_ok());
+        assert_eq!(prompts(), 2);
+
+        // The expiry and both grants are notified with their expiry
+        let changes = xswd.handler().get_data().changes.lock().unwrap().clone();
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(changes[0], (_, Permission::Allow, Some(expires_at)) if expires_at == start + Duration::from_secs(60)));
+        assert!(matches!(changes[1], (_, Permission::Ask, None)));
+        assert!(matches!(changes[2], (_, Permission::Allow, Some(expires_at)) if expires_at == after_expiry + Duration::from_secs(60)));
+
+        // Accepting forever removes the expiry
+        app.allow_permission("get_balance".to_owned(), None, after_expiry).await;
+        let later = after_expiry + Duration::from_secs(3600);
+        assert!(xswd.verify_permission_for_request_at(&MockProvider, &app, &request, later).await.is_ok());
+        assert_eq!(prompts(), 2);
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_wildcard_permission() {
+        let handler = MockHandler::new(PermissionResult::Reject);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["get_*"]);
+        app.set_permission("get_*".to_owned(), Permission::Allow).await;
+
+        // Any method with the prefix is allowed
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_balance")).await.is_ok());
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_address")).await.is_ok());
+        assert_eq!(xswd.handler().get_data().prompts.load(Ordering::SeqCst), 0);
+
+        // Other methods aren't covered
+        let Err(err) = xswd.verify_permission_for_request(&MockProvider, &app, &create_request("build_transaction")).await else {
+            panic!("method outside of the wildcard should be rejected");
+        };
+        assert!(err.to_string().contains(&XSWDError::PermissionInvalid.to_string()));
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_exact_permission_over_wildcard() {
+        let handler = MockHandler::new(PermissionResult::Accept);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let app = create_app(&["get_*", "get_balance"]);
+        app.set_permissions([
+            ("get_*".to_owned(), Permission::Allow),
+            ("get_balance".to_owned(), Permission::Reject),
+        ]).await;
+
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_balance")).await.is_err());
+        assert!(xswd.verify_permission_for_request(&MockProvider, &app, &create_request("get_nonce")).await.is_ok());
+        assert_eq!(xswd.handler().get_data().prompts.load(Ordering::SeqCst), 0);
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_verify_application_wildcard() {
+        struct NoAppProvider;
+
+        #[async_trait]
+        impl XSWDProvider for NoAppProvider {
+            async fn has_app_with_id(&self, _: &str) -> bool {
+                false
+            }
+        }
+
+        let handler = MockHandler::new(PermissionResult::Reject);
+        let xswd = XSWD::new(RPCHandler::new(handler, None));
+        let data = |permissions: &[&str]| -> ApplicationData {
+            serde_json::from_value(json!({
+                "id": "0".repeat(64),
+                "name": "test",
+                "description": "test app",
+                "url": null,
+                "permissions": permissions
+            })).unwrap()
+        };
+
+        // The internal methods match the prefix
+        assert!(xswd.verify_application(&NoAppProvider, &data(&["xswd.*"])).await.is_ok());
+
+        // No registered method matches the prefix
+        assert!(matches!(
+            xswd.verify_application(&NoAppProvider, &data(&["unknown_*"])).await,
+            Err(XSWDError::UnknownMethodInPermissionsList(perm)) if perm == "unknown_*"
+        ));
+
+        // A bare wildcard would match every method
+        for perm in ["*", "wallet.*"] {
+            assert!(matches!(
+                xswd.verify_application(&NoAppProvider, &data(&[perm])).await,
+                Err(XSWDError::UnknownMethodInPermissionsList(p)) if p == perm
+            ));
+        }
+
+        assert!(matches_wildcard("get_*", "get_balance"));
+        assert!(!matches_wildcard("get_*", "build_transaction"));
+        assert!(!matches_wildcard("get_balance", "get_balance"));
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_prefetch_permissions_partial_grant() {
+        let requested: IndexSet<String> = ["get_balance", "get_address", "get_nonce"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let app = create_app(&["get_balance", "get_address", "get_nonce"]);
+
+        // Previously allowed for a limited time
+        let start = Instant::now();
+        app.allow_permission("get_balance".to_owned(), Some(Duration::from_secs(60)), start).await;
+
+        // User only allows the balance and rejects the nonce
+        let mut response = IndexMap::new();
+        response.insert("get_balance".to_owned(), Permission::Allow);
+        response.insert("get_nonce".to_owned(), Permission::Reject);
+
+        let result = prefetch_result(&requested, &response);
+        assert_eq!(result.granted, vec!["get_balance".to_owned()]);
+        assert_eq!(result.denied, vec!["get_address".to_owned(), "get_nonce".to_owned()]);
+
+        // Granted permissions are still applied
+        app.set_permissions(response).await;
+        let later = start + Duration::from_secs(3600);
+        assert!(matches!(app.get_permission("get_balance", later).await, Some(Permission::Allow)));
+        assert!(matches!(app.get_permission("get_address", later).await, Some(Permission::Ask)));
+        assert!(matches!(app.get_permission("get_nonce", later).await, Some(Permission::Reject)));
+    }
 }
\ No newline at end of file
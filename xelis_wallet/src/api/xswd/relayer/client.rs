@@ -1,21 +1,21 @@
-use std::{borrow::Cow, collections::HashMap, sync::Arc};
+use std::{sync::{Arc, Mutex as StdMutex}, time::Duration};
 
-use futures::{SinkExt, StreamExt};
-use log::{error, debug};
-use serde_json::json;
+use async_trait::async_trait;
+use futures::{future, SinkExt, StreamExt};
+use log::{error, debug, warn};
+use metrics::counter;
 use tokio_tungstenite_wasm::{
     WebSocketStream,
     Message,
     connect,
 };
 use xelis_common::{
-    api::daemon::NotifyEvent,
-    rpc::{RpcResponse, ShareableTid},
+    rpc::ShareableTid,
     tokio::{
         select,
         spawn_task,
-        sync::{RwLock, mpsc},
-        task
+        sync::mpsc,
+        time::sleep
     }
 };
 use crate::api::{
@@ -24,6 +24,7 @@ use crate::api::{
         XSWDRelayerShared
     },
     AppStateShared,
+    ConnectionContext,
     EncryptionMode,
     XSWDHandler,
     XSWDResponse,
@@ -31,13 +32,111 @@ use crate::api::{
 
 enum InternalMessage {
     Send(String),
+    Event(String),
     Close,
 }
 
+// A connection established with the relay
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait RelayConnection {
+    // Wait for the next message
+    // None is returned once the connection is closed
+    async fn recv(&mut self) -> Option<Vec<u8>>;
+
+    // Send a message to the relay
+    async fn send(&mut self, bytes: Vec<u8>) -> Result<(), anyhow::Error>;
+}
+
+// Open the connections to the relay
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait RelayTransport {
+    async fn connect(&self, target: &str) -> Result<BoxedRelayConnection, anyhow::Error>;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub type BoxedRelayConnection = Box<dyn RelayConnection>;
+#[cfg(target_arch = "wasm32")]
+pub type BoxedRelayTransport = Box<dyn RelayTransport>;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxedRelayConnection = Box<dyn RelayConnection + Send>;
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxedRelayTransport = Box<dyn RelayTransport + Send + Sync>;
+
+// Default transport using a WebSocket
+pub struct WebSocketTransport;
+
+struct WebSocketConnection(WebSocketStream);
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl RelayTransport for WebSocketTransport {
+    async fn connect(&self, target: &str) -> Result<BoxedRelayConnection, anyhow::Error> {
+        let ws = connect(target).await?;
+        Ok(Box::new(WebSocketConnection(ws)))
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl RelayConnection for WebSocketConnection {
+    async fn recv(&mut self) -> Option<Vec<u8>> {
+        match self.0.next().await? {
+            Ok(Message::Text(text)) => {
+                let bytes: &[u8] = text.as_ref();
+                Some(bytes.to_vec())
+            },
+            Ok(Message::Binary(bytes)) => Some(bytes.to_vec()),
+            Ok(Message::Close(_)) | Err(_) => None
+        }
+    }
+
+    async fn send(&mut self, bytes: Vec<u8>) -> Result<(), anyhow::Error> {
+        self.0.send(Message::Binary(bytes.into())).await?;
+        Ok(())
+    }
+}
+
+// Reconnection policy when the relay drops the connection
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectConfig {
+    // Attempts before considering the connection lost
+    pub max_attempts: u32,
+    // Delay before the first attempt, doubled after each failure
+    pub base_delay: Duration,
+}
+
+impl ReconnectConfig {
+    // Delay to wait before the given attempt
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(1 << attempt.min(16))
+    }
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
 pub struct ClientImpl {
     target: String,
     sender: mpsc::Sender<InternalMessage>,
-    events: RwLock<HashMap<NotifyEvent, task::JoinHandle<()>>>,
+    // Node events subscriptions of this connection
+    connection: ConnectionContext,
+    transport: BoxedRelayTransport,
+    encryption_mode: Option<EncryptionMode>,
+    reconnect: ReconnectConfig,
+    // Registration response sent again on each reconnection
+    registration: StdMutex<Option<String>>,
+    // Bounded queue for the events notifications
+    // If none, events are sent through the messages channel
+    events: Option<mpsc::Sender<String>>,
 }
 
 pub type Client = Arc<ClientImpl>;
@@ -47,25 +146,57 @@ impl ClientImpl {
     where
         W: ShareableTid<'static> + XSWDHandler
     {
-        // Create a cipher based on the provided encryption mode
-        let cipher = Cipher::new(encryption_mode)?;
+        let reconnect = relayer.reconnect_config();
+        let event_queue = relayer.event_queue_size();
+        Self::with_transport(target, relayer, encryption_mode, state, Box::new(WebSocketTransport), reconnect, event_queue).await
+    }
+
+    pub async fn with_transport<W>(
+        target: String,
+        relayer: XSWDRelayerShared<W>,
+        encryption_mode: Option<EncryptionMode>,
+        state: AppStateShared,
+        transport: BoxedRelayTransport,
+        reconnect: ReconnectConfig,
+        event_queue: Option<usize>
+    ) -> Result<Client, anyhow::Error>
+    where
+        W: ShareableTid<'static> + XSWDHandler
+    {
+        // Verify the encryption mode before connecting
+        Cipher::new(encryption_mode.clone())?;
 
-        let ws = connect(&target).await?;
+        let conn = transport.connect(&target).await?;
         let (sender, receiver) = mpsc::channel(64);
+        let (events, events_receiver) = match event_queue {
+            Some(size) => {
+                let (events, events_receiver) = mpsc::channel(size.max(1));
+                (Some(events), Some(events_receiver))
+            },
+            None => (None, None)
+        };
 
         let client = Arc::new(Self {
             target,
             sender,
-            events: RwLock::new(HashMap::new()),
+            connection: ConnectionContext::new(),
+            transport,
+            encryption_mode,
+            reconnect,
+            registration: StdMutex::new(None),
+            events,
         });
 
         {
             let client = client.clone();
             spawn_task(format!("xswd-relayer-{}", state.get_id()), async move {
-                if let Err(e) = Self::background_task(client, ws, &state, &relayer, receiver, cipher).await {
-                    debug!("Error on xswd relayer #{}: {}", state.get_id(), e);
+                if let Err(e) = Self::run(&client, conn, &state, &relayer, receiver, events_receiver).await {
+                    error!("Error on xswd relayer #{}: {}", state.get_id(), e);
                 }
-    
+
+                // Drop all the node events subscriptions of this connection
+                client.connection.close().await;
+
                 relayer.on_close(state).await;
             });
         }
@@ -77,6 +208,14 @@ impl ClientImpl {
         &self.target
     }
 
+    // Send the registration response to the application
+    // It is kept to be sent again after a reconnection
+    pub async fn register<V: ToString>(&self, response: V) -> bool {
+        let response = response.to_string();
+        *self.registration.lock().expect("Poisoned") = Some(response.clone());
+        self.send_message(response).await
+    }
+
     pub async fn send_message<V: ToString>(&self, msg: V) -> bool {
         if let Err(e) = self.sender.send(InternalMessage::Send(msg.to_string())).await {
             error!("Error while sending message: {}", e);
@@ -86,39 +225,147 @@ impl ClientImpl {
         true
     }
 
+    // Send an event notification to the application
+    // With a bounded event queue, the event is dropped
+    // if the application doesn't keep up with them
+    pub async fn send_event<V: ToString>(&self, msg: V) -> bool {
+        let msg = msg.to_string();
+        match &self.events {
+            Some(events) => match events.try_send(msg) {
+                Ok(()) => {},
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("Event queue of {} is full, dropping event", self.target);
+                    counter!("xelis_xswd_relayer_events_dropped").increment(1);
+                    return false;
+                },
+                Err(e) => {
+                    error!("Error while queuing event: {}", e);
+                    return false;
+                }
+            },
+            None => if let Err(e) = self.sender.send(InternalMessage::Event(msg)).await {
+                error!("Error while sending event: {}", e);
+                return false;
+            }
+        };
+
+        counter!("xelis_xswd_relayer_events_queued").increment(1);
+        true
+    }
+
     pub async fn close(&self) {
         if let Err(e) = self.sender.send(InternalMessage::Close).await {
             error!("Error while sending close message: {}", e);
         }
     }
 
-    async fn background_task<W>(
-        client: Client,
-        mut ws: WebSocketStream,
+    // Handle the connection until it is closed by us
+    // or until the relay can't be reached anymore
+    // Only a lost connection is retried, any other error is returned
+    async fn run<W>(
+        client: &Client,
+        mut conn: BoxedRelayConnection,
         state: &AppStateShared,
         relayer: &XSWDRelayerShared<W>,
         mut receiver: mpsc::Receiver<InternalMessage>,
-        mut cipher: Cipher
+        mut events: Option<mpsc::Receiver<String>>
     ) -> Result<(), anyhow::Error>
     where
         W: ShareableTid<'static> + XSWDHandler
     {
+        let mut reconnected = false;
         loop {
-            select! {
-                msg = ws.next() => {
-                    let Some(Ok(msg)) = msg else {
-                        break;
-                    };
+            if Self::background_task(client, &mut conn, state, relayer, &mut receiver, &mut events, reconnected).await? {
+                return Ok(());
+            }
 
-                    let bytes: &[u8] = match &msg {
-                        Message::Text(bytes) => bytes.as_ref(),
-                        Message::Binary(bytes) => &bytes,
-                        Message::Close(_) => {
-                            break;
-                        }
+            debug!("Connection lost on xswd relayer #{}", state.get_id());
+            match client.reconnect().await {
+                Some(new_conn) => {
+                    debug!("Reconnected xswd relayer #{}", state.get_id());
+                    conn = new_conn;
+                    reconnected = true;
+                },
+                None => {
+                    debug!("Couldn't reconnect xswd relayer #{}", state.get_id());
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // Try to reach the relay again with an exponential backoff
+    async fn reconnect(&self) -> Option<BoxedRelayConnection> {
+        for attempt in 0..self.reconnect.max_attempts {
+            sleep(self.reconnect.delay(attempt)).await;
+            match self.transport.connect(&self.target).await {
+                Ok(conn) => return Some(conn),
+                Err(e) => debug!("Reconnection attempt #{} to {} failed: {}", attempt + 1, self.target, e)
+            }
+        }
+
+        None
+    }
+
+    // Wait for the next queued event
+    // Never resolves if no event queue is configured
+    async fn next_event(events: &mut Option<mpsc::Receiver<String>>) -> Option<String> {
+        match events {
+            Some(events) => events.recv().await,
+            None => future::pending().await
+        }
+    }
+
+    // Send the bytes to the relay
+    // Returns false if the connection is lost
+    async fn send_bytes(conn: &mut BoxedRelayConnection, bytes: Vec<u8>) -> bool {
+        if let Err(e) = conn.send(bytes).await {
+            debug!("Error while sending to the relay: {}", e);
+            return false;
+        }
+
+        true
+    }
+
+    // Returns true if the connection was closed by us
+    // and false if the connection was lost
+    async fn background_task<W>(
+        client: &Client,
+        conn: &mut BoxedRelayConnection,
+        state: &AppStateShared,
+        relayer: &XSWDRelayerShared<W>,
+        receiver: &mut mpsc::Receiver<InternalMessage>,
+        events: &mut Option<mpsc::Receiver<String>>,
+        reconnected: bool
+    ) -> Result<bool, anyhow::Error>
+    where
+        W: ShareableTid<'static> + XSWDHandler
+    {
+        // Each connection authenticates again using the same key
+        let mut cipher = Cipher::new(client.encryption_mode.clone())?;
+
+        if reconnected {
+            let registration = client.registration.lock()
+                .expect("Poisoned")
+                .clone();
+
+            if let Some(registration) = registration {
+                let output = cipher.encrypt(registration.as_bytes())?
+                    .into_owned();
+                if !Self::send_bytes(conn, output).await {
+                    return Ok(false);
+                }
+            }
+        }
+
+        loop {
+            select! {
+                msg = conn.recv() => {
+                    let Some(bytes) = msg else {
+                        return Ok(false);
                     };
 
-                    let output = cipher.decrypt(bytes)?;
+                    let output = cipher.decrypt(&bytes)?;
                     let response = match relayer.on_message(state, &output).await {
                         Ok(response) => match response {
                             XSWDResponse::Request(value) => match value {
@@ -126,31 +373,13 @@ impl ClientImpl {
                                 None => continue,
                             },
                             XSWDResponse::Event(event, stream, value) => {
-                                let mut lock = client.events.write().await;
-
-                                match stream {
-                                    Some((mut stream, id)) => {
-                                        if !lock.contains_key(&event) {
-                                            // spawn a task to handle the event stream
-                                            let client = client.clone();
-                                            let handle = spawn_task("xswd-relayer-event-listener", async move {
-                                                while let Ok(value) = stream.recv().await {
-                                                    let response = json!(RpcResponse::new(Cow::Borrowed(&id), Cow::Borrowed(&value)));
-                                                    if !client.send_message(response.to_string()).await {
-                                                        break;
-                                                    }
-                                                }
-                                            });
-
-                                            lock.insert(event, handle);
-                                        }
-                                    },
-                                    None => {
-                                        if let Some(handle) = lock.remove(&event) {
-                                            handle.abort();
-                                        }
+                                let listener = client.clone();
+                                client.connection.on_event(event, stream, move |response| {
+                                    let client = listener.clone();
+                                    async move {
+                                        client.send_message(response).await
                                     }
-                                }
+                                }).await;
 
                                 match value {
                                     Some(v) => v,
@@ -164,26 +393,47 @@ impl ClientImpl {
                     // Encrypt response before sending
                     let encrypted_response = cipher.encrypt(response.to_string().as_bytes())?
                         .into_owned();
-                    ws.send(Message::Binary(encrypted_response.into())).await?;
+                    if !Self::send_bytes(conn, encrypted_response).await {
+                        return Ok(false);
+                    }
                 },
                 msg = receiver.recv() => {
                     let Some(msg) = msg else {
-                        break;
+                        return Ok(true);
                     };
 
                     match msg {
                         InternalMessage::Send(msg) => {
                             let output = cipher.encrypt(msg.as_bytes())?
                                 .into_owned();
-                            ws.send(Message::Binary(output.into())).await?;
+                            if !Self::send_bytes(conn, output).await {
+                                return Ok(false);
+                            }
+                        },
+                        InternalMessage::Event(msg) => {
+                            let output = cipher.encrypt(msg.as_bytes())?
+                                .into_owned();
+                            if !Self::send_bytes(conn, output).await {
+                                return Ok(false);
+                            }
+                            counter!("xelis_xswd_relayer_events_sent").increment(1);
                         },
-                        InternalMessage::Close => break,
+                        InternalMessage::Close => return Ok(true),
                     }
                 },
-                else => break,
+                msg = Self::next_event(events) => {
+                    let Some(msg) = msg else {
+                        return Ok(true);
+                    };
+
+                    let output = cipher.encrypt(msg.as_bytes())?
+                        .into_owned();
+                    if !Self::send_bytes(conn, output).await {
+                        return Ok(false);
+                    }
+                    counter!("xelis_xswd_relayer_events_sent").increment(1);
+                },
             };
         }
-
-        Ok(())
     }
-}
\ No newline at end of file
+}
@@ -30,6 +30,14 @@ use super::{
     XSWDResponse
 };
 
+pub use client::{
+    BoxedRelayConnection,
+    BoxedRelayTransport,
+    ReconnectConfig,
+    RelayConnection,
+    RelayTransport,
+    WebSocketTransport
+};
 use client::*;
 
 // XSWD as a client mode
@@ -46,6 +54,11 @@ where
     xswd: XSWD<W>,
     applications: RwLock<HashMap<AppStateShared, Client>>,
     concurrency: usize,
+    // Used by the clients when the relay drops the connection
+    reconnect: ReconnectConfig,
+    // Max events queued per client before dropping them
+    // If none, notifying waits for the slow clients
+    event_queue: Option<usize>,
 }
 
 pub type XSWDRelayerShared<W> = Arc<XSWDRelayer<W>>;
@@ -55,14 +68,38 @@ where
     W: ShareableTid<'static> + XSWDHandler
 {
     pub fn new(handler: RPCHandler<W>, concurrency: usize) -> XSWDRelayerShared<W> {
+        Self::with_reconnect(handler, concurrency, ReconnectConfig::default())
+    }
+
+    // Create a relayer with a custom reconnection policy
+    pub fn with_reconnect(handler: RPCHandler<W>, concurrency: usize, reconnect: ReconnectConfig) -> XSWDRelayerShared<W> {
+        Self::with_config(handler, concurrency, reconnect, None)
+    }
 
+    // Create a relayer with a custom reconnection policy
+    // and an optional bounded events queue per client
+    pub fn with_config(handler: RPCHandler<W>, concurrency: usize, reconnect: ReconnectConfig, event_queue: Option<usize>) -> XSWDRelayerShared<W> {
         Arc::new(Self {
             xswd: XSWD::new(handler),
             applications: RwLock::new(HashMap::new()),
             concurrency,
+            reconnect,
+            event_queue,
         })
     }
 
+    // Reconnection policy of the clients
+    #[inline(always)]
+    pub fn reconnect_config(&self) -> ReconnectConfig {
+        self.reconnect
+    }
+
+    // Events queue size of the clients
+    #[inline(always)]
+    pub fn event_queue_size(&self) -> Option<usize> {
+        self.event_queue
+    }
+
     // On close delete all clients
     pub async fn close(&self) {
         let mut applications = self.applications.write().await;
@@ -91,7 +128,7 @@ where
                 if let Some(id) = subscriptions.get(event) {
                     if let Some(client) = sessions.get(&app) {
                         let response = json!(RpcResponse::new(Cow::Borrowed(&id), Cow::Borrowed(value)));
-                        client.send_message(response.to_string()).await;
+                        client.send_event(response.to_string()).await;
                     }
                 }
             })
@@ -106,7 +143,7 @@ where
         let client = ClientImpl::new(app_data.relayer, Arc::clone(self), app_data.encryption_mode, state.clone()).await?;
 
         let response = self.xswd.add_application(&state).await?;
-        client.send_message(response).await;
+        client.register(response).await;
 
         {
             debug!("XSWD Relayer: Added new application #{}", state.get_id());
@@ -149,4 +186,251 @@ where
             .find(|v| v.get_id() == id)
             .is_some()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, sync::Mutex as StdMutex, time::Duration};
+    use anyhow::Error;
+    use xelis_common::{
+        crypto::{elgamal::PublicKey as DecompressedPublicKey, KeyPair},
+        rpc::{tid, InternalRpcError, RpcRequest},
+        tokio::{sync::{mpsc, Semaphore}, time::timeout}
+    };
+    use serde_json::Value;
+    use crate::api::{ApplicationData, EncryptionMode, PermissionRequest, PermissionResult};
+    use super::*;
+
+    struct MockHandler {
+        keypair: KeyPair
+    }
+
+    tid!(MockHandler);
+
+    #[async_trait]
+    impl XSWDHandler for MockHandler {
+        async fn request_permission(&self, _: &AppStateShared, _: PermissionRequest<'_>) -> Result<PermissionResult, Error> {
+            Ok(PermissionResult::Reject)
+        }
+
+        async fn cancel_request_permission(&self, _: &AppStateShared) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error> {
+            Ok(self.keypair.get_public_key())
+        }
+
+        async fn call_node_with(&self, _: &AppStateShared, request: RpcRequest) -> Result<XSWDResponse, RpcResponseError> {
+            Err(RpcResponseError::new(request.id, InternalRpcError::InternalError("Node calls are not supported")))
+        }
+
+        async fn on_app_disconnect(&self, _: AppStateShared) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    struct MockConnection {
+        incoming: mpsc::UnboundedReceiver<Vec<u8>>,
+        outgoing: mpsc::UnboundedSender<Vec<u8>>,
+        // Permits required to complete each send
+        gate: Option<Arc<Semaphore>>
+    }
+
+    #[async_trait]
+    impl RelayConnection for MockConnection {
+        async fn recv(&mut self) -> Option<Vec<u8>> {
+            self.incoming.recv().await
+        }
+
+        async fn send(&mut self, bytes: Vec<u8>) -> Result<(), Error> {
+            self.outgoing.send(bytes)?;
+            // A slow connection is stuck until the gate is opened
+            if let Some(gate) = &self.gate {
+                gate.acquire().await?.forget();
+            }
+            Ok(())
+        }
+    }
+
+    // Each connect call consumes the next connection
+    // Once empty, the relay is unreachable
+    struct MockTransport {
+        connections: StdMutex<VecDeque<MockConnection>>
+    }
+
+    #[async_trait]
+    impl RelayTransport for MockTransport {
+        async fn connect(&self, _: &str) -> Result<BoxedRelayConnection, Error> {
+            let conn = self.connections.lock().unwrap()
+                .pop_front()
+                .ok_or_else(|| anyhow::anyhow!("relay unreachable"))?;
+            Ok(Box::new(conn))
+        }
+    }
+
+    // Returns the connection with the relay side channels
+    fn create_connection() -> (MockConnection, mpsc::UnboundedSender<Vec<u8>>, mpsc::UnboundedReceiver<Vec<u8>>) {
+        create_gated_connection(None)
+    }
+
+    fn create_gated_connection(gate: Option<Arc<Semaphore>>) -> (MockConnection, mpsc::UnboundedSender<Vec<u8>>, mpsc::UnboundedReceiver<Vec<u8>>) {
+        let (incoming_sender, incoming) = mpsc::unbounded_channel();
+        let (outgoing, outgoing_receiver) = mpsc::unbounded_channel();
+        (MockConnection { incoming, outgoing, gate }, incoming_sender, outgoing_receiver)
+    }
+
+    fn create_relayer() -> XSWDRelayerShared<MockHandler> {
+        let reconnect = ReconnectConfig {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        XSWDRelayer::with_reconnect(RPCHandler::new(MockHandler { keypair: KeyPair::new() }, None), 1, reconnect)
+    }
+
+    fn create_app() -> AppStateShared {
+        create_app_with_id("0".repeat(64))
+    }
+
+    fn create_app_with_id(id: String) -> AppStateShared {
+        let data: ApplicationData = serde_json::from_value(json!({
+            "id": id,
+            "name": "test",
+            "description": "test app",
+            "url": null,
+            "permissions": []
+        })).unwrap();
+
+        Arc::new(AppState::new(data))
+    }
+
+    async fn next_message(receiver: &mut mpsc::UnboundedReceiver<Vec<u8>>) -> Option<String> {
+        let bytes = timeout(Duration::from_secs(5), receiver.recv()).await.ok()??;
+        Some(String::from_utf8(bytes).unwrap())
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_reconnect_after_drop() {
+        let (first, first_relay, mut first_output) = create_connection();
+        let (second, _second_relay, mut second_output) = create_connection();
+        let transport = MockTransport {
+            connections: StdMutex::new(VecDeque::from([first, second]))
+        };
+
+        let relayer = create_relayer();
+        let state = create_app();
+        let reconnect = relayer.reconnect_config();
+        let client = ClientImpl::with_transport("relay".to_owned(), relayer.clone(), None, state.clone(), Box::new(transport), reconnect, None).await.unwrap();
+        relayer.applications().write().await.insert(state.clone(), client.clone());
+
+        assert!(client.register("registered").await);
+        assert_eq!(next_message(&mut first_output).await.as_deref(), Some("registered"));
+
+        // The relay drops the first connection
+        drop(first_relay);
+
+        // Registration is sent again and the delivery resumes on the new connection
+        assert_eq!(next_message(&mut second_output).await.as_deref(), Some("registered"));
+        assert!(client.send_message("hello").await);
+        assert_eq!(next_message(&mut second_output).await.as_deref(), Some("hello"));
+        assert!(relayer.has_app_with_id(state.get_id()).await);
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_no_reconnect_on_error() {
+        let (first, first_relay, _first_output) = create_connection();
+        let (second, _second_relay, mut second_output) = create_connection();
+        let transport = MockTransport {
+            connections: StdMutex::new(VecDeque::from([first, second]))
+        };
+
+        let relayer = create_relayer();
+        let state = create_app();
+        let reconnect = relayer.reconnect_config();
+        let encryption_mode = Some(EncryptionMode::AES { key: [1; 32] });
+        let client = ClientImpl::with_transport("relay".to_owned(), relayer.clone(), encryption_mode, state.clone(), Box::new(transport), reconnect, None).await.unwrap();
+        relayer.applications().write().await.insert(state.clone(), client.clone());
+        assert!(client.register("registered").await);
+
+        // The message can't be decrypted, this isn't a connection loss
+        first_relay.send(vec![0; 4]).unwrap();
+
+        let closed = timeout(Duration::from_secs(5), async {
+            while relayer.has_app_with_id(state.get_id()).await {
+                xelis_common::tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await;
+        assert!(closed.is_ok());
+
+        // The second connection was never used
+        assert!(second_output.try_recv().is_err());
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_reconnect_permanent_failure() {
+        let (first, first_relay, _first_output) = create_connection();
+        let transport = MockTransport {
+            connections: StdMutex::new(VecDeque::from([first]))
+        };
+
+        let relayer = create_relayer();
+        let state = create_app();
+        let reconnect = relayer.reconnect_config();
+        let client = ClientImpl::with_transport("relay".to_owned(), relayer.clone(), None, state.clone(), Box::new(transport), reconnect, None).await.unwrap();
+        relayer.applications().write().await.insert(state.clone(), client);
+
+        drop(first_relay);
+
+        // Every attempt fails, the application is closed
+        let closed = timeout(Duration::from_secs(5), async {
+            while relayer.has_app_with_id(state.get_id()).await {
+                xelis_common::tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }).await;
+        assert!(closed.is_ok());
+    }
+
+    async fn next_topoheight(receiver: &mut mpsc::UnboundedReceiver<Vec<u8>>) -> Option<Value> {
+        let message: Value = serde_json::from_str(&next_message(receiver).await?).unwrap();
+        Some(message["result"]["topoheight"].clone())
+    }
+
+    #[xelis_common::tokio::test(crate = "xelis_common::tokio")]
+    async fn test_event_queue_drops_for_slow_client() {
+        let queue = 2;
+        let gate = Arc::new(Semaphore::new(0));
+        let (slow, _slow_relay, mut slow_output) = create_gated_connection(Some(gate.clone()));
+        let (fast, _fast_relay, mut fast_output) = create_connection();
+
+        let handler = RPCHandler::new(MockHandler { keypair: KeyPair::new() }, None);
+        let relayer = XSWDRelayer::with_config(handler, 2, ReconnectConfig::default(), Some(queue));
+        let slow_app = create_app_with_id("0".repeat(64));
+        let fast_app = create_app_with_id("1".repeat(64));
+        for (state, conn) in [(&slow_app, slow), (&fast_app, fast)] {
+            let transport = MockTransport {
+                connections: StdMutex::new(VecDeque::from([conn]))
+            };
+            let client = ClientImpl::with_transport("relay".to_owned(), relayer.clone(), None, state.clone(), Box::new(transport), relayer.reconnect_config(), relayer.event_queue_size()).await.unwrap();
+            relayer.applications().write().await.insert(state.clone(), client);
+            relayer.xswd.events().subscribe(state.clone(), NotifyEvent::NewTopoHeight, None).await;
+        }
+
+        // The slow client is stuck while sending the first event
+        relayer.notify_event(&NotifyEvent::NewTopoHeight, json!({ "topoheight": 0 })).await;
+        assert_eq!(next_topoheight(&mut slow_output).await, Some(json!(0)));
+        assert_eq!(next_topoheight(&mut fast_output).await, Some(json!(0)));
+
+        let total = 10;
+        for topoheight in 1..total {
+            relayer.notify_event(&NotifyEvent::NewTopoHeight, json!({ "topoheight": topoheight })).await;
+            assert_eq!(next_topoheight(&mut fast_output).await, Some(json!(topoheight)));
+        }
+
+        // Only the queued events are delivered to the slow client
+        gate.add_permits(total);
+        for topoheight in 1..=queue {
+            assert_eq!(next_topoheight(&mut slow_output).await, Some(json!(topoheight)));
+        }
+        assert!(timeout(Duration::from_millis(100), slow_output.recv()).await.is_err());
+    }
+}
@@ -1,16 +1,20 @@
 use indexmap::{IndexMap, IndexSet};
 use serde::{Serialize, Deserialize};
 use std::{
+    collections::{HashMap, HashSet},
     fmt,
     hash::{Hash, Hasher},
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc
-    }
+        Arc,
+        Mutex as StdMutex
+    },
+    time::Duration
 };
 use xelis_common::{
-    rpc::{RpcRequest, tid},
+    rpc::{Id, RpcRequest, tid},
     serializer::*,
+    time::Instant,
     tokio::sync::Mutex
 };
 
@@ -20,25 +24,49 @@ pub struct XSWDAppId(pub Arc<String>);
 
 tid!(XSWDAppId);
 
+// Trailing character of a permission matching all the methods with its prefix
+pub const WILDCARD: char = '*';
+
+// Check if a wildcard permission such as "get_*" matches the method
+pub fn matches_wildcard(permission: &str, method: &str) -> bool {
+    permission.strip_suffix(WILDCARD)
+        .is_some_and(|prefix| method.starts_with(prefix))
+}
+
 // Application state shared between all threads
 // Built from the application data
 pub struct AppState {
     // Application ID in hexadecimal format
     id: XSWDAppId,
-    // Name of the app
-    name: String,
-    // Small description of the app
-    description: String,
-    // URL of the app if exists
-    url: Option<String>,
+    // Name, description and URL of the app
+    // They can be updated after the registration
+    metadata: StdMutex<AppMetadata>,
     // All permissions for each method based on user config
     permissions: Mutex<IndexMap<String, Permission>>,
+    // Expiry of the Allow permissions granted for a limited time
+    // Only updated while holding the permissions lock
+    permissions_expiry: StdMutex<HashMap<String, Instant>>,
+    // Methods not in the permissions are denied silently
+    allow_list: bool,
     // Do we have a pending request?
-    is_requesting: AtomicBool
+    is_requesting: AtomicBool,
+    // Request ids currently being processed
+    in_flight_requests: StdMutex<HashSet<Id>>
 }
 
 tid!(AppState);
 
+// Mutable metadata of an application
+#[derive(Clone, Debug)]
+pub struct AppMetadata {
+    // Name of the app
+    pub name: String,
+    // Small description of the app
+    pub description: String,
+    // URL of the app if exists
+    pub url: Option<String>
+}
+
 impl Hash for AppState {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.id.0.hash(state);
@@ -59,22 +87,32 @@ impl AppState {
     pub fn new(data: ApplicationData) -> Self {
         Self {
             id: XSWDAppId(Arc::new(data.id)),
-            name: data.name,
-            description: data.description,
-            url: data.url,
+            metadata: StdMutex::new(AppMetadata {
+                name: data.name,
+                description: data.description,
+                url: data.url
+            }),
             permissions: Mutex::new(data.permissions.into_iter().map(|k| (k, Permission::Ask)).collect()),
-            is_requesting: AtomicBool::new(false)
+            permissions_expiry: StdMutex::new(HashMap::new()),
+            allow_list: data.allow_list,
+            is_requesting: AtomicBool::new(false),
+            in_flight_requests: StdMutex::new(HashSet::new())
         }
     }
 
     pub fn with_permissions(data: ApplicationData, permissions: IndexMap<String, Permission>) -> Self {
         Self {
             id: XSWDAppId(Arc::new(data.id)),
-            name: data.name,
-            description: data.description,
-            url: data.url,
+            metadata: StdMutex::new(AppMetadata {
+                name: data.name,
+                description: data.description,
+                url: data.url
+            }),
             permissions: Mutex::new(permissions),
-            is_requesting: AtomicBool::new(false)
+            permissions_expiry: StdMutex::new(HashMap::new()),
+            allow_list: data.allow_list,
+            is_requesting: AtomicBool::new(false),
+            in_flight_requests: StdMutex::new(HashSet::new())
         }
     }
 
@@ -88,24 +126,122 @@ impl AppState {
         &self.id.0
     }
 
-    #[inline(always)]
-    pub fn get_name(&self) -> &String {
-        &self.name
+    pub fn get_name(&self) -> String {
+        self.get_metadata().name
     }
 
-    #[inline(always)]
-    pub fn get_description(&self) -> &String {
-        &self.description
+    pub fn get_description(&self) -> String {
+        self.get_metadata().description
     }
 
-    #[inline(always)]
-    pub fn get_url(&self) -> &Option<String> {
-        &self.url
+    pub fn get_url(&self) -> Option<String> {
+        self.get_metadata().url
+    }
+
+    // Clone the current metadata under a single lock
+    pub fn get_metadata(&self) -> AppMetadata {
+        self.metadata.lock()
+            .expect("Poisoned")
+            .clone()
     }
 
+    // Replace the metadata of the app
+    // It must be verified before
+    pub fn set_metadata(&self, metadata: AppMetadata) {
+        *self.metadata.lock().expect("Poisoned") = metadata;
+    }
+
+    // Are the methods restricted to the requested permissions
     #[inline(always)]
-    pub fn get_permissions(&self) -> &Mutex<IndexMap<String, Permission>> {
-        &self.permissions
+    pub fn is_allow_list(&self) -> bool {
+        self.allow_list
+    }
+
+    // Find the permission entry covering a method
+    // An exact permission takes precedence over the wildcards,
+    // otherwise the longest matching wildcard is used
+    fn find_permission_index(permissions: &IndexMap<String, Permission>, method: &str) -> Option<usize> {
+        permissions.get_index_of(method)
+            .or_else(|| permissions.keys()
+                .enumerate()
+                .filter(|(_, key)| matches_wildcard(key, method))
+                .max_by_key(|(_, key)| key.len())
+                .map(|(index, _)| index)
+            )
+    }
+
+    // Is the permission under this key an Allow that expired at the given instant
+    fn is_expired(&self, key: &str, permission: Permission, now: Instant) -> bool {
+        matches!(permission, Permission::Allow) && self.permissions_expiry.lock()
+            .expect("Poisoned")
+            .get(key)
+            .is_some_and(|expires_at| *expires_at <= now)
+    }
+
+    // Get the permission of a method at the given instant
+    // An expired Allow permission is reported as Ask
+    // but is only reset by `resolve_permission`
+    pub async fn get_permission(&self, method: &str, now: Instant) -> Option<Permission> {
+        let permissions = self.permissions.lock().await;
+        let (key, permission) = permissions.get_index(Self::find_permission_index(&permissions, method)?)?;
+        if self.is_expired(key, *permission, now) {
+            return Some(Permission::Ask);
+        }
+
+        Some(*permission)
+    }
+
+    // Resolve the permission of a method at the given instant
+    // An expired Allow permission is reset to Ask
+    // and the key of the expired permission is returned with it
+    pub async fn resolve_permission(&self, method: &str, now: Instant) -> Option<(Permission, Option<String>)> {
+        let mut permissions = self.permissions.lock().await;
+        let index = Self::find_permission_index(&permissions, method)?;
+        let (key, permission) = permissions.get_index_mut(index)?;
+        if self.is_expired(key, *permission, now) {
+            self.permissions_expiry.lock()
+                .expect("Poisoned")
+                .remove(key.as_str());
+            *permission = Permission::Ask;
+
+            return Some((Permission::Ask, Some(key.clone())));
+        }
+
+        Some((*permission, None))
+    }
+
+    // Allow a method, only until the TTL elapses if one is given
+    pub async fn allow_permission(&self, method: String, ttl: Option<Duration>, now: Instant) {
+        let mut permissions = self.permissions.lock().await;
+        let mut expiry = self.permissions_expiry.lock().expect("Poisoned");
+        match ttl {
+            Some(ttl) => expiry.insert(method.clone(), now + ttl),
+            None => expiry.remove(&method)
+        };
+
+        permissions.insert(method, Permission::Allow);
+    }
+
+    // Set the permission of a method without any expiry
+    pub async fn set_permission(&self, method: String, permission: Permission) {
+        self.set_permissions([(method, permission)]).await;
+    }
+
+    // Set several permissions at once without any expiry
+    // Any previous TTL of these methods is dropped
+    pub async fn set_permissions(&self, values: impl IntoIterator<Item = (String, Permission)>) {
+        let mut permissions = self.permissions.lock().await;
+        let mut expiry = self.permissions_expiry.lock().expect("Poisoned");
+        for (method, permission) in values {
+            expiry.remove(&method);
+            permissions.insert(method, permission);
+        }
+    }
+
+    // Clone all the permissions under a single lock
+    // so the caller doesn't hold it while waiting on the user
+    pub async fn permissions_snapshot(&self) -> IndexMap<String, Permission> {
+        self.permissions.lock().await.clone()
     }
 
     #[inline(always)]
@@ -117,6 +253,35 @@ impl AppState {
     pub fn set_requesting(&self, value: bool) {
         self.is_requesting.store(value, Ordering::SeqCst);
     }
+
+    // Mark the request id as in flight until the returned guard is dropped
+    // Returns None if a request with the same id is already in flight
+    pub fn track_request(&self, id: &Id) -> Option<InFlightRequest<'_>> {
+        let mut in_flight = self.in_flight_requests.lock()
+            .expect("Poisoned");
+        if !in_flight.insert(id.clone()) {
+            return None
+        }
+
+        Some(InFlightRequest {
+            app: self,
+            id: id.clone()
+        })
+    }
+}
+
+// Guard releasing the request id once the request is done
+pub struct InFlightRequest<'a> {
+    app: &'a AppState,
+    id: Id
+}
+
+impl Drop for InFlightRequest<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.app.in_flight_requests.lock()
+            .expect("Poisoned");
+        in_flight.remove(&self.id);
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -132,7 +297,11 @@ pub struct ApplicationData {
     // Permissions per RPC method
     // This is useful to request in one time all permissions
     #[serde(default)]
-    permissions: IndexSet<String>
+    permissions: IndexSet<String>,
+    // Only the requested permissions can be used
+    // Any other method is denied without prompting the user
+    #[serde(default)]
+    allow_list: bool
 }
 
 impl ApplicationData {
@@ -155,10 +324,14 @@ impl ApplicationData {
     pub fn get_permissions(&self) -> &IndexSet<String> {
         &self.permissions
     }
-}
 
-impl Serializer for ApplicationData {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+    pub fn is_allow_list(&self) -> bool {
+        self.allow_list
+    }
+
+    // Read all the fields except the allow list flag
+    // It was added later and is only written as a trailing byte
+    fn read_fields(reader: &mut Reader) -> Result<Self, ReaderError> {
         let id = reader.read_string()?;
         let name = reader.read_string()?;
         let description = reader.read_string()?;
@@ -170,11 +343,12 @@ impl Serializer for ApplicationData {
             name,
             description,
             url,
-            permissions
+            permissions,
+            allow_list: false
         })
     }
 
-    fn write(&self, writer: &mut Writer) {
+    fn write_fields(&self, writer: &mut Writer) {
         self.id.write(writer);
         self.name.write(writer);
         self.description.write(writer);
@@ -183,6 +357,23 @@ impl Serializer for ApplicationData {
     }
 }
 
+impl Serializer for ApplicationData {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let mut data = Self::read_fields(reader)?;
+        // Older encodings don't have the allow list flag
+        if reader.size() > 0 {
+            data.allow_list = bool::read(reader)?;
+        }
+
+        Ok(data)
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        self.write_fields(writer);
+        self.allow_list.write(writer);
+    }
+}
+
 pub type EncryptionKey = [u8; 32];
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -241,10 +432,16 @@ pub struct ApplicationDataRelayer {
 
 impl Serializer for ApplicationDataRelayer {
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let app_data = ApplicationData::read(reader)?;
+        let mut app_data = ApplicationData::read_fields(reader)?;
         let n = reader.read_u16()?;
         let relayer = reader.read_string_with_size(n as _)?;
         let encryption_mode = Option::read(reader)?;
+        // The allow list flag of the app is written last
+        // to stay compatible with the older encodings
+        if reader.size() > 0 {
+            app_data.allow_list = bool::read(reader)?;
+        }
+
         Ok(Self {
             app_data,
             relayer,
@@ -253,13 +450,14 @@ impl Serializer for ApplicationDataRelayer {
     }
 
     fn write(&self, writer: &mut Writer) {
-        self.app_data.write(writer);
+        self.app_data.write_fields(writer);
 
         let bytes = self.relayer.as_bytes();
         writer.write_u16(bytes.len() as u16);
         bytes.write(writer);
 
         self.encryption_mode.write(writer);
+        self.app_data.allow_list.write(writer);
     }
 }
 
@@ -286,17 +484,19 @@ pub enum PermissionRequest<'a> {
     Request(&'a RpcRequest)
 }
 
+#[derive(Clone, Copy, Debug)]
 pub enum PermissionResult {
     Accept,
     Reject,
-    AlwaysAccept,
+    // Accept the method until the TTL elapses, forever if none
+    AlwaysAccept(Option<Duration>),
     AlwaysReject
 }
 
 impl PermissionResult {
     pub fn is_positive(&self) -> bool {
         match self {
-            Self::Accept | Self::AlwaysAccept => true,
+            Self::Accept | Self::AlwaysAccept(_) => true,
             _ => false
         }
     }
@@ -315,4 +515,54 @@ mod tests {
         let deserialized: EncryptionMode = serde_json::from_str(&serialized).unwrap();
         assert_eq!(aes_mode, deserialized);
     }
+
+    fn create_app_data(allow_list: bool) -> ApplicationData {
+        ApplicationData {
+            id: "0".repeat(64),
+            name: "Test".to_owned(),
+            description: "Test application".to_owned(),
+            url: None,
+            permissions: IndexSet::from(["get_balance".to_owned()]),
+            allow_list
+        }
+    }
+
+    #[test]
+    fn test_application_data_relayer_serialization() {
+        let data = ApplicationDataRelayer {
+            app_data: create_app_data(true),
+            relayer: "wss://relayer.xelis.io".to_owned(),
+            encryption_mode: Some(EncryptionMode::AES { key: [1; 32] })
+        };
+
+        let decoded = ApplicationDataRelayer::from_bytes(&data.to_bytes()).unwrap();
+        assert!(decoded.app_data.is_allow_list());
+        assert_eq!(decoded.app_data.get_permissions(), data.app_data.get_permissions());
+        assert_eq!(decoded.relayer, data.relayer);
+        assert_eq!(decoded.encryption_mode, data.encryption_mode);
+    }
+
+    #[test]
+    fn test_application_data_old_encoding() {
+        // Encoded before the allow list flag existed
+        let app_data = create_app_data(false);
+        let mut bytes = Vec::new();
+        let mut writer = Writer::new(&mut bytes);
+        app_data.write_fields(&mut writer);
+
+        let decoded = ApplicationData::from_bytes(&bytes).unwrap();
+        assert!(!decoded.is_allow_list());
+        assert_eq!(decoded.get_id(), app_data.get_id());
+
+        let relayer = "wss://relayer.xelis.io";
+        let mut writer = Writer::new(&mut bytes);
+        writer.write_u16(relayer.len() as u16);
+        relayer.as_bytes().write(&mut writer);
+        Option::<EncryptionMode>::None.write(&mut writer);
+
+        let decoded = ApplicationDataRelayer::from_bytes(&bytes).unwrap();
+        assert!(!decoded.app_data.is_allow_list());
+        assert_eq!(decoded.relayer, relayer);
+        assert!(decoded.encryption_mode.is_none());
+    }
 }
\ No newline at end of file
@@ -31,6 +31,8 @@ pub const KEY_SIZE: usize = 32;
 pub const DEFAULT_DAEMON_ADDRESS: &str = "http://127.0.0.1:8080";
 // Auto reconnect interval in seconds for Network Handler
 pub const AUTO_RECONNECT_INTERVAL: u64 = 5;
+// Time in seconds given to the API Server to finish its in-flight requests
+pub const API_SERVER_STOP_TIMEOUT: u64 = 10;
 
 lazy_static! {
     pub static ref PASSWORD_ALGORITHM: Argon2<'static> = {
@@ -349,6 +349,15 @@ impl DaemonAPI {
         Ok(multisig)
     }
 
+    pub async fn get_multisig_at_topoheight(&self, address: &Address, topoheight: u64) -> Result<GetMultisigAtTopoHeightResult> {
+        trace!("get_multisig_at_topoheight");
+        let multisig = self.client.call_with("get_multisig_at_topoheight", &GetMultisigAtTopoHeightParams {
+            address: Cow::Borrowed(address),
+            topoheight,
+        }).await?;
+        Ok(multisig)
+    }
+
     pub async fn get_contract_logs(&self, tx_hash: &Hash) -> Result<Vec<RPCContractLog<'static>>> {
         trace!("get contract outputs");
         let outputs = self.client.call_with("get_contract_logs", &GetContractLogsParams {
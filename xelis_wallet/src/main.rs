@@ -297,7 +297,7 @@ async fn xswd_handler(mut receiver: UnboundedReceiver<XSWDEvent>, prompt: Sharea
 #[cfg(feature = "xswd")]
 async fn xswd_handle_request_application(prompt: &ShareablePrompt, app_state: AppStateShared) -> Result<PermissionResult, Error> {
     let mut message = format!("XSWD: Application {} ({}) request access to your wallet", app_state.get_name(), app_state.get_id());
-    let permissions = app_state.get_permissions().lock().await;
+    let permissions = app_state.permissions_snapshot().await;
     if !permissions.is_empty() {
         message += &format!("\r\nPermissions ({}):", permissions.len());
         for perm in permissions.keys() {
@@ -333,7 +333,7 @@ async fn xswd_handle_request_permission(prompt: &ShareablePrompt, app_state: App
     Ok(match answer.as_str() {
         "a" => PermissionResult::Accept,
         "d" => PermissionResult::Reject,
-        "aa" => PermissionResult::AlwaysAccept,
+        "aa" => PermissionResult::AlwaysAccept(None),
         "ad" => PermissionResult::AlwaysReject,
         _ => unreachable!()
     })
@@ -1541,6 +1541,10 @@ async fn deploy_contract(manager: &CommandManager, mut args: ArgumentManager) ->
     let tx_type = TransactionTypeBuilder::DeployContract(DeployContractBuilder {
         module: module_hex,
         contract_version,
+        dependencies: Vec::new(),
+        upgradeable: false,
+        upgrade: None,
+        source_hash: None,
         invoke,
     });
 
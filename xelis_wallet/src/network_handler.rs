@@ -5,7 +5,7 @@ use std::{
     time::Duration
 };
 use futures::{stream::{self, FuturesUnordered}, StreamExt, TryStreamExt};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use thiserror::Error;
 use anyhow::{Context, Error};
 use log::{debug, error, info, trace, warn};
@@ -410,6 +410,21 @@ impl NetworkHandler {
                             None
                         }
                     },
+                    RPCTransactionType::MultiSigUpdate(_) => {
+                        if is_owner {
+                            // Only the delta is in the TX, fetch the resulting setup
+                            let (participants, threshold) = match self.api.get_multisig_at_topoheight(address, topoheight).await?.state {
+                                MultisigState::Active { participants, threshold } => {
+                                    (participants.into_iter().map(|p| p.to_public_key()).collect(), threshold)
+                                },
+                                MultisigState::Deleted => (IndexSet::new(), 0)
+                            };
+
+                            Some(EntryData::MultiSig { participants, threshold, fee: tx.fee, nonce: tx.nonce })
+                        } else {
+                            None
+                        }
+                    },
                     RPCTransactionType::InvokeContract(payload) => {
                         if is_owner {
                             let payload = payload.into_owned();
@@ -2,6 +2,7 @@ use std::{collections::{HashMap, HashSet}, ops::{Deref, DerefMut}};
 use log::{debug, trace};
 use xelis_common::{
     account::CiphertextCache,
+    block::TopoHeight,
     crypto::{elgamal::Ciphertext, Hash, Hashable, PublicKey},
     transaction::{builder::{AccountState, FeeHelper}, Reference, Transaction}
 };
@@ -58,6 +59,9 @@ pub struct TransactionBuilderState {
     mainnet: bool,
     // Balances of the wallet
     balances: HashMap<Hash, Balance>,
+    // Assets for which the output balance of a version was selected
+    // Their version topoheight may be above the reference
+    output_balances: HashSet<Hash>,
     // Reference at which the transaction is built
     reference: Reference,
     // Nonce of the transaction
@@ -77,6 +81,7 @@ impl TransactionBuilderState {
             inner: EstimateFeesState::new(),
             mainnet,
             balances: HashMap::new(),
+            output_balances: HashSet::new(),
             reference,
             nonce,
             fee_limit,
@@ -125,6 +130,14 @@ impl TransactionBuilderState {
     }
 
     pub fn add_balance(&mut self, asset: Hash, balance: Balance) {
+        self.output_balances.remove(&asset);
+        self.balances.insert(asset, balance);
+    }
+
+    // Add a balance selected from the output of its version
+    // It is the balance before the outgoing transaction of that version
+    pub fn add_output_balance(&mut self, asset: Hash, balance: Balance) {
+        self.output_balances.insert(asset.clone());
         self.balances.insert(asset, balance);
     }
 
@@ -157,6 +170,7 @@ impl TransactionBuilderState {
             assets: self.balances.keys().cloned().collect(),
         });
 
+        self.output_balances.clear();
         for (asset, balance) in self.balances.drain() {
             debug!("Setting balance for asset {} to {} ({})", asset, balance.amount, balance.ciphertext);
             storage.set_unconfirmed_balance_for(asset, balance).await?;
@@ -198,6 +212,13 @@ impl AccountState for TransactionBuilderState {
         self.reference.clone()
     }
 
+    fn get_last_balance_topoheight(&self) -> Result<Option<TopoHeight>, Self::Error> {
+        Ok(self.balances.iter()
+            .filter(|(asset, _)| !self.output_balances.contains(*asset))
+            .map(|(_, balance)| balance.topoheight)
+            .max())
+    }
+
     fn get_account_balance(&self, asset: &Hash) -> Result<u64, Self::Error> {
         self.balances.get(asset).map(|b| b.amount).ok_or_else(|| WalletError::BalanceNotFound(asset.clone()))
     }
@@ -246,4 +267,41 @@ impl DerefMut for TransactionBuilderState {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.inner
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::{
+        crypto::elgamal::Ciphertext,
+        transaction::builder::GenerationError
+    };
+    use super::*;
+
+    fn balance(topoheight: TopoHeight) -> Balance {
+        Balance::new(100, CiphertextCache::Decompressed(None, Ciphertext::zero()), topoheight)
+    }
+
+    #[test]
+    fn test_last_balance_topoheight() {
+        let reference = Reference {
+            topoheight: 10,
+            hash: Hash::zero(),
+        };
+        let mut state = TransactionBuilderState::new(false, reference, 0, None);
+        assert_eq!(state.get_last_balance_topoheight().unwrap(), None);
+
+        state.add_balance(Hash::zero(), balance(5));
+        state.add_balance(Hash::max(), balance(8));
+        assert_eq!(state.get_last_balance_topoheight().unwrap(), Some(8));
+        assert!(state.validate_reference_consistency().is_ok());
+
+        // The output balance is the one before the outgoing transaction
+        state.add_output_balance(Hash::max(), balance(12));
+        assert_eq!(state.get_last_balance_topoheight().unwrap(), Some(5));
+        assert!(state.validate_reference_consistency().is_ok());
+
+        // A balance updated after the reference can't be used
+        state.add_balance(Hash::max(), balance(12));
+        assert!(matches!(state.validate_reference_consistency(), Err(GenerationError::InconsistentReference(10, 12))));
+    }
+}
@@ -144,11 +144,17 @@ use {
 };
 
 #[cfg(feature = "api_server")]
-use crate::api::{
-    XSWDServer,
-    WalletRpcServer,
-    AuthConfig,
-    APIServer,
+use {
+    std::time::Duration,
+    crate::{
+        api::{
+            XSWDServer,
+            WalletRpcServer,
+            AuthConfig,
+            APIServer,
+        },
+        config::API_SERVER_STOP_TIMEOUT
+    }
 };
 
 // Recover option for wallet creation
@@ -476,7 +482,7 @@ impl Wallet {
             {
                 let mut lock = self.api_server.lock().await;
                 if let Some(server) = lock.take() {
-                    server.stop().await;
+                    server.stop(true, Duration::from_secs(API_SERVER_STOP_TIMEOUT)).await;
                 }
             }
         }
@@ -695,7 +701,7 @@ impl Wallet {
     pub async fn stop_api_server(&self) -> Result<(), Error> {
         let mut lock = self.api_server.lock().await;
         let rpc_server = lock.take().ok_or(WalletError::RPCServerNotRunning)?;
-        rpc_server.stop().await;
+        rpc_server.stop(true, Duration::from_secs(API_SERVER_STOP_TIMEOUT)).await;
         Ok(())
     }
 
@@ -956,7 +962,11 @@ impl Wallet {
                                         };
 
                                         debug!("Using previous balance for asset {} ({}) with amount {}", asset, balance.ciphertext, balance.amount);
-                                        state.add_balance((*asset).clone(), balance);
+                                        if select_output_balance {
+                                            state.add_output_balance((*asset).clone(), balance);
+                                        } else {
+                                            state.add_balance((*asset).clone(), balance);
+                                        }
                                     }
                                 }
 
@@ -1011,7 +1021,11 @@ impl Wallet {
                                     };
 
                                     debug!("Using stable balance for asset {} ({}) with amount {}", asset, balance.ciphertext, balance.amount);
-                                    state.add_balance((*asset).clone(), balance);
+                                    if output {
+                                        state.add_output_balance((*asset).clone(), balance);
+                                    } else {
+                                        state.add_balance((*asset).clone(), balance);
+                                    }
 
                                     // Build the stable reference
                                     // We need to find the highest stable point